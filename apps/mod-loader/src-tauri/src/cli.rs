@@ -0,0 +1,302 @@
+use crate::modpack::{
+    export_modpack, install_modpack, ConflictPolicy, ExportOptions, InstallOptions,
+};
+use crate::mods::{get_global_mods, get_installed_mods, set_mods_enabled};
+use crate::saves::{import_save, list_saves};
+use crate::verify::{verify_mods, VerifyStatus};
+use crate::{set_log_output, LogOutput};
+use clap::{Parser, Subcommand};
+use serde::Serialize;
+use std::path::Path;
+use std::process::ExitCode;
+
+/// Manage Hytale mods, modpacks and saves without the mod loader app.
+///
+/// Each subcommand runs the same code as the app's command of the same name.
+#[derive(Parser)]
+#[command(name = "orbis-mods", version)]
+struct Cli {
+    /// Print results as JSON
+    #[arg(long, global = true)]
+    json: bool,
+
+    /// Log progress to stderr
+    #[arg(short, long, global = true)]
+    verbose: bool,
+
+    /// Hytale data directory, the one containing UserData
+    #[arg(long, global = true, env = "HYTALE_ROOT")]
+    hytale_root: Option<String>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// List the mods a save enables, or every mod in UserData/Mods
+    List {
+        /// Save name or path
+        #[arg(long)]
+        save: Option<String>,
+    },
+    /// List the saves in UserData/Saves
+    Saves,
+    /// Install a modpack zip into a save
+    InstallModpack {
+        modpack: String,
+        /// Save name or path
+        #[arg(long)]
+        save: String,
+        /// How to handle a mod UserData/Mods already has
+        #[arg(
+            long,
+            default_value = "keep_newer",
+            value_parser = ["keep_newer", "keep_existing", "replace", "keep_both"]
+        )]
+        conflict_policy: String,
+        /// Only print what would be installed
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Import a save from a zip into UserData/Saves
+    ImportSave { zip: String },
+    /// Export a save's enabled mods as a modpack zip
+    Export {
+        /// Save name or path
+        #[arg(long)]
+        save: String,
+        #[arg(short, long)]
+        output: String,
+        /// Leave out the mods' config files
+        #[arg(long)]
+        no_configs: bool,
+    },
+    /// Check that a save's enabled mods are installed and unchanged.
+    /// Exits with status 1 if any are missing or modified.
+    Verify {
+        /// Save name or path
+        #[arg(long)]
+        save: String,
+    },
+    /// Enable mods in a save's config.json
+    Enable {
+        /// Save name or path
+        #[arg(long)]
+        save: String,
+        /// "Group:Name" of each mod
+        #[arg(required = true)]
+        mod_keys: Vec<String>,
+    },
+    /// Disable mods in a save's config.json
+    Disable {
+        /// Save name or path
+        #[arg(long)]
+        save: String,
+        /// "Group:Name" of each mod
+        #[arg(required = true)]
+        mod_keys: Vec<String>,
+    },
+}
+
+impl Cli {
+    fn hytale_root(&self) -> Result<String, String> {
+        self.hytale_root
+            .clone()
+            .ok_or_else(|| "--hytale-root (or HYTALE_ROOT) is required".to_string())
+    }
+
+    /// A save given by path, or by name under UserData/Saves
+    fn save_path(&self, save: &str) -> Result<String, String> {
+        if Path::new(save).is_dir() {
+            return Ok(save.to_string());
+        }
+
+        let path = Path::new(&self.hytale_root()?)
+            .join("UserData")
+            .join("Saves")
+            .join(save);
+        if !path.is_dir() {
+            return Err(format!("Save '{}' not found", save));
+        }
+        Ok(path.to_string_lossy().to_string())
+    }
+
+    /// Print `value` as JSON, or as text with `human`. Results are written with
+    /// `println!`; the crate's own `println!` is progress logging.
+    fn print<T: Serialize>(&self, value: &T, human: impl FnOnce(&T)) -> Result<(), String> {
+        if self.json {
+            let json = serde_json::to_string_pretty(value)
+                .map_err(|e| format!("Failed to serialize output: {}", e))?;
+            println!("{}", json);
+        } else {
+            human(value);
+        }
+        Ok(())
+    }
+}
+
+fn execute(cli: &Cli) -> Result<ExitCode, String> {
+    match &cli.command {
+        Command::List { save: Some(save) } => {
+            let mods = get_installed_mods(cli.save_path(save)?, cli.hytale_root()?)?;
+            cli.print(&mods, |mods| {
+                for m in mods {
+                    println!(
+                        "{}:{}\t{}\t{}\t{:?}",
+                        m.manifest.group,
+                        m.manifest.name,
+                        m.manifest.version,
+                        m.jar_name,
+                        m.location
+                    );
+                }
+            })?;
+        }
+        Command::List { save: None } => {
+            let mods = get_global_mods(cli.hytale_root()?)?;
+            cli.print(&mods, |mods| {
+                for m in mods {
+                    println!(
+                        "{}:{}\t{}\t{}\tused by {} saves",
+                        m.manifest.group,
+                        m.manifest.name,
+                        m.manifest.version,
+                        m.jar_name,
+                        m.used_by.len()
+                    );
+                }
+            })?;
+        }
+        Command::Saves => {
+            let saves = list_saves(cli.hytale_root()?)?;
+            cli.print(&saves, |saves| {
+                for save in saves {
+                    println!(
+                        "{}\t{} mods enabled\t{}",
+                        save.name, save.enabled_mods, save.path
+                    );
+                }
+            })?;
+        }
+        Command::InstallModpack {
+            modpack,
+            save,
+            conflict_policy,
+            dry_run,
+        } => {
+            let conflict_policy: ConflictPolicy =
+                serde_json::from_value(serde_json::Value::String(conflict_policy.clone()))
+                    .map_err(|e| format!("Invalid conflict policy: {}", e))?;
+            let options = InstallOptions {
+                dry_run: *dry_run,
+                conflict_policy,
+                ..Default::default()
+            };
+
+            let outcome = install_modpack(
+                modpack.clone(),
+                cli.save_path(save)?,
+                cli.hytale_root()?,
+                Some(options),
+            )?;
+            cli.print(&outcome, |outcome| {
+                for archive in &outcome.plan.archives {
+                    println!("{:?}\t{}", archive.action, archive.file_name);
+                }
+                match &outcome.receipt {
+                    Some(receipt) => println!(
+                        "Installed {} (receipt {})",
+                        outcome.plan.modpack_name, receipt.id
+                    ),
+                    None => println!("Dry run, nothing installed"),
+                }
+            })?;
+        }
+        Command::ImportSave { zip } => {
+            let save_name = import_save(zip.clone(), cli.hytale_root()?)?;
+            cli.print(&save_name, |name| println!("Imported save '{}'", name))?;
+        }
+        Command::Export {
+            save,
+            output,
+            no_configs,
+        } => {
+            let options = ExportOptions {
+                output_path: output.clone(),
+                include_configs: !no_configs,
+            };
+            let summary = export_modpack(cli.save_path(save)?, cli.hytale_root()?, options)?;
+            cli.print(&summary, |summary| {
+                for key in &summary.missing {
+                    eprintln!("Warning: {} has no archive and was left out", key);
+                }
+                println!(
+                    "Exported {} mods to {}",
+                    summary.mods.len(),
+                    summary.output_path
+                );
+            })?;
+        }
+        Command::Verify { save } => {
+            let report = verify_mods(cli.save_path(save)?, cli.hytale_root()?)?;
+            cli.print(&report, |report| {
+                for m in &report.mods {
+                    println!(
+                        "{:?}\t{}\t{}",
+                        m.status,
+                        m.mod_key,
+                        m.file_name.as_deref().unwrap_or("-")
+                    );
+                }
+            })?;
+
+            if !report.ok {
+                let failed = report
+                    .mods
+                    .iter()
+                    .filter(|m| {
+                        matches!(m.status, VerifyStatus::Missing | VerifyStatus::HashMismatch)
+                    })
+                    .count();
+                eprintln!("{} mods are missing or modified", failed);
+                return Ok(ExitCode::FAILURE);
+            }
+        }
+        Command::Enable { save, mod_keys } | Command::Disable { save, mod_keys } => {
+            let enabled = matches!(cli.command, Command::Enable { .. });
+            let changed = set_mods_enabled(cli.save_path(save)?, mod_keys.clone(), enabled)?;
+            cli.print(&changed, |changed| {
+                if changed.is_empty() {
+                    println!("Nothing changed");
+                }
+                for key in changed {
+                    println!("{} {}", if enabled { "Enabled" } else { "Disabled" }, key);
+                }
+            })?;
+        }
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Entry point of the `orbis-mods` binary
+pub fn run() -> ExitCode {
+    let cli = Cli::parse();
+
+    // Stdout is for results; the commands' progress messages only go to
+    // stderr, and only with --verbose
+    set_log_output(if cli.verbose {
+        LogOutput::Stderr
+    } else {
+        LogOutput::Off
+    });
+
+    match execute(&cli) {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -1,6 +1,58 @@
+use std::fmt;
+use std::process::ExitCode;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// Where progress messages go. The app logs to stdout; the CLI keeps stdout
+/// for its results.
+#[derive(Clone, Copy)]
+pub(crate) enum LogOutput {
+    Stdout,
+    Stderr,
+    Off,
+}
+
+static LOG_OUTPUT: AtomicU8 = AtomicU8::new(LogOutput::Stdout as u8);
+
+pub(crate) fn set_log_output(output: LogOutput) {
+    LOG_OUTPUT.store(output as u8, Ordering::Relaxed);
+}
+
+pub(crate) fn log_line(args: fmt::Arguments) {
+    match LOG_OUTPUT.load(Ordering::Relaxed) {
+        x if x == LogOutput::Stdout as u8 => println!("{}", args),
+        x if x == LogOutput::Stderr as u8 => eprintln!("{}", args),
+        _ => {}
+    }
+}
+
+/// Progress logging for every module below, following `set_log_output`
+macro_rules! log {
+    ($($arg:tt)*) => {
+        $crate::log_line(format_args!($($arg)*))
+    };
+}
+
+mod cli;
 mod launcher;
+pub mod loader_core;
+mod manifest;
+mod modpack;
 mod mods;
+mod orphans;
+mod pack_cache;
+mod prefabs;
+mod profiles;
+mod receipts;
+mod remote;
+mod resources;
 mod saves;
+mod server;
+mod state;
+mod sync;
+mod upgrade;
+mod verify;
+mod watcher;
+mod worlds;
 
 // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
 #[tauri::command]
@@ -18,18 +70,65 @@ pub fn run() {
         .plugin(tauri_plugin_http::init())
         .plugin(tauri_plugin_os::init())
         .plugin(tauri_plugin_opener::init())
+        .manage(watcher::WatcherState::default())
         .invoke_handler(tauri::generate_handler![
             greet,
+            manifest::inspect_mod,
             mods::get_installed_mods,
             mods::add_mod_to_config,
+            mods::set_mods_enabled,
             mods::register_jar_in_config,
             mods::delete_mod,
             mods::get_global_mods,
             mods::delete_global_mod,
-            mods::install_modpack,
+            mods::move_mod,
+            orphans::find_orphans,
+            orphans::clean_orphans,
+            modpack::install_modpack,
+            modpack::export_modpack,
+            modpack::install_cached_modpack,
+            modpack::create_save_from_modpack,
+            pack_cache::list_cached_modpacks,
+            pack_cache::remove_cached_modpack,
+            profiles::list_profiles,
+            profiles::create_profile_from_save,
+            profiles::delete_profile,
+            profiles::apply_profile,
+            profiles::diff_profile,
+            receipts::list_install_receipts,
+            receipts::uninstall_modpack,
+            prefabs::list_prefabs,
+            prefabs::install_prefab,
+            prefabs::remove_prefab,
+            resources::inspect_resource,
+            resources::install_resource,
+            upgrade::upgrade_modpack,
+            verify::verify_mods,
             saves::import_save,
+            saves::list_saves,
+            saves::duplicate_save,
+            saves::rename_save,
+            saves::delete_save,
+            saves::list_trashed_saves,
+            saves::restore_save,
+            server::deploy_modpack_to_server,
+            server::deploy_save_to_server,
+            remote::sync_save_to_remote,
+            remote::sync_profile_to_remote,
+            sync::diff_saves,
+            sync::sync_mods,
+            watcher::start_watching,
+            watcher::stop_watching,
+            worlds::install_world,
+            worlds::get_world_metadata,
+            worlds::get_required_mods,
             launcher::launch_hytale
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
+
+/// Run the `orbis-mods` command-line tool
+pub fn run_cli() -> ExitCode {
+    cli::run()
+}
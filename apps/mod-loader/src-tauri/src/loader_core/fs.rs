@@ -0,0 +1,244 @@
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{self, Cursor, Read, Seek};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// A readable, seekable file, as zip archives need
+pub trait ReadSeek: Read + Seek {}
+
+impl<T: Read + Seek> ReadSeek for T {}
+
+/// The filesystem operations the core needs. `OsFs` is the real disk;
+/// `MemoryFs` keeps everything in memory for tests.
+///
+/// Like `std::fs`, writing a file requires its parent directory to exist.
+pub trait FileSystem {
+    fn exists(&self, path: &Path) -> bool;
+    fn is_file(&self, path: &Path) -> bool;
+    fn is_dir(&self, path: &Path) -> bool;
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>>;
+    fn open(&self, path: &Path) -> io::Result<Box<dyn ReadSeek + '_>>;
+    /// Paths of a directory's entries, sorted
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>>;
+    fn write(&self, path: &Path, data: &[u8]) -> io::Result<()>;
+    /// Write everything `reader` yields to `path`, returning the byte count
+    fn write_from(&self, path: &Path, reader: &mut dyn Read) -> io::Result<u64>;
+    fn create_dir_all(&self, path: &Path) -> io::Result<()>;
+    fn remove_file(&self, path: &Path) -> io::Result<()>;
+    fn remove_dir_all(&self, path: &Path) -> io::Result<()>;
+
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        String::from_utf8(self.read(path)?)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+/// The real filesystem, through `std::fs`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OsFs;
+
+impl FileSystem for OsFs {
+    fn exists(&self, path: &Path) -> bool {
+        path.exists()
+    }
+
+    fn is_file(&self, path: &Path) -> bool {
+        path.is_file()
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        path.is_dir()
+    }
+
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        fs::read(path)
+    }
+
+    fn open(&self, path: &Path) -> io::Result<Box<dyn ReadSeek + '_>> {
+        Ok(Box::new(File::open(path)?))
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        let mut paths = fs::read_dir(path)?
+            .map(|entry| entry.map(|e| e.path()))
+            .collect::<io::Result<Vec<_>>>()?;
+        paths.sort();
+        Ok(paths)
+    }
+
+    fn write(&self, path: &Path, data: &[u8]) -> io::Result<()> {
+        fs::write(path, data)
+    }
+
+    fn write_from(&self, path: &Path, reader: &mut dyn Read) -> io::Result<u64> {
+        let mut file = File::create(path)?;
+        io::copy(reader, &mut file)
+    }
+
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        fs::create_dir_all(path)
+    }
+
+    fn remove_file(&self, path: &Path) -> io::Result<()> {
+        fs::remove_file(path)
+    }
+
+    fn remove_dir_all(&self, path: &Path) -> io::Result<()> {
+        fs::remove_dir_all(path)
+    }
+}
+
+#[derive(Debug, Clone)]
+enum MemoryEntry {
+    File(Vec<u8>),
+    Dir,
+}
+
+/// A filesystem held in memory. Paths are used as given, so stick to
+/// absolute ones (e.g. "/hytale/UserData/Mods").
+#[derive(Debug, Default)]
+pub struct MemoryFs {
+    entries: Mutex<BTreeMap<PathBuf, MemoryEntry>>,
+}
+
+fn not_found(path: &Path) -> io::Error {
+    io::Error::new(io::ErrorKind::NotFound, format!("{:?} not found", path))
+}
+
+impl MemoryFs {
+    pub fn new() -> Self {
+        MemoryFs::default()
+    }
+
+    /// Add a file, creating its parent directories
+    pub fn insert_file(&self, path: impl AsRef<Path>, data: impl Into<Vec<u8>>) {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            self.mkdirs(parent);
+        }
+        self.lock()
+            .insert(path.to_path_buf(), MemoryEntry::File(data.into()));
+    }
+
+    /// Every file's path, sorted
+    pub fn files(&self) -> Vec<PathBuf> {
+        self.lock()
+            .iter()
+            .filter(|(_, entry)| matches!(entry, MemoryEntry::File(_)))
+            .map(|(path, _)| path.clone())
+            .collect()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BTreeMap<PathBuf, MemoryEntry>> {
+        // A panic while holding the lock can't leave the map half-updated
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn mkdirs(&self, path: &Path) {
+        let mut entries = self.lock();
+        for dir in path.ancestors().filter(|p| !p.as_os_str().is_empty()) {
+            entries.entry(dir.to_path_buf()).or_insert(MemoryEntry::Dir);
+        }
+    }
+
+    fn check_parent(&self, path: &Path) -> io::Result<()> {
+        match path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            Some(parent) if !self.is_dir(parent) => Err(not_found(parent)),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl FileSystem for MemoryFs {
+    fn exists(&self, path: &Path) -> bool {
+        self.lock().contains_key(path)
+    }
+
+    fn is_file(&self, path: &Path) -> bool {
+        matches!(self.lock().get(path), Some(MemoryEntry::File(_)))
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        matches!(self.lock().get(path), Some(MemoryEntry::Dir))
+    }
+
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        match self.lock().get(path) {
+            Some(MemoryEntry::File(data)) => Ok(data.clone()),
+            Some(MemoryEntry::Dir) => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{:?} is a directory", path),
+            )),
+            None => Err(not_found(path)),
+        }
+    }
+
+    fn open(&self, path: &Path) -> io::Result<Box<dyn ReadSeek + '_>> {
+        Ok(Box::new(Cursor::new(self.read(path)?)))
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        if !self.is_dir(path) {
+            return Err(not_found(path));
+        }
+
+        Ok(self
+            .lock()
+            .keys()
+            .filter(|p| p.parent() == Some(path))
+            .cloned()
+            .collect())
+    }
+
+    fn write(&self, path: &Path, data: &[u8]) -> io::Result<()> {
+        self.check_parent(path)?;
+        if self.is_dir(path) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{:?} is a directory", path),
+            ));
+        }
+
+        self.lock()
+            .insert(path.to_path_buf(), MemoryEntry::File(data.to_vec()));
+        Ok(())
+    }
+
+    fn write_from(&self, path: &Path, reader: &mut dyn Read) -> io::Result<u64> {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data)?;
+        self.write(path, &data)?;
+        Ok(data.len() as u64)
+    }
+
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        if self.is_file(path) {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("{:?} is a file", path),
+            ));
+        }
+        self.mkdirs(path);
+        Ok(())
+    }
+
+    fn remove_file(&self, path: &Path) -> io::Result<()> {
+        let mut entries = self.lock();
+        match entries.get(path) {
+            Some(MemoryEntry::File(_)) => {
+                entries.remove(path);
+                Ok(())
+            }
+            _ => Err(not_found(path)),
+        }
+    }
+
+    fn remove_dir_all(&self, path: &Path) -> io::Result<()> {
+        if !self.is_dir(path) {
+            return Err(not_found(path));
+        }
+        self.lock().retain(|p, _| !p.starts_with(path));
+        Ok(())
+    }
+}
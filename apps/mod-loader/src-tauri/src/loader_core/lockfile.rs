@@ -0,0 +1,82 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::io::{self, Read};
+
+/// Descriptor written at the root of exported modpacks
+pub const PACK_LOCK_FILE: &str = "orbis-pack.json";
+
+pub const PACK_LOCK_FORMAT_VERSION: u32 = 1;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PackLock {
+    pub format_version: u32,
+    #[serde(default)]
+    pub mods: Vec<PackLockMod>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PackLockMod {
+    /// "Group:Name" from the mod's manifest
+    pub key: String,
+    pub version: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resource_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version_id: Option<String>,
+    /// Archive name under Mods/
+    pub file_name: String,
+    pub sha256: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub config: Option<PackLockConfig>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PackLockConfig {
+    /// Archive name under Configs/
+    pub file_name: String,
+    pub sha256: String,
+}
+
+impl PackLock {
+    pub fn find_by_file(&self, file_name: &str) -> Option<&PackLockMod> {
+        self.mods.iter().find(|m| m.file_name == file_name)
+    }
+
+    pub fn find_by_config(&self, config_file_name: &str) -> Option<&PackLockMod> {
+        self.mods.iter().find(|m| {
+            m.config
+                .as_ref()
+                .is_some_and(|c| c.file_name == config_file_name)
+        })
+    }
+}
+
+/// Parse orbis-pack.json, rejecting formats newer than this loader understands
+pub fn parse_pack_lock(contents: &str) -> Result<PackLock, String> {
+    let lock: PackLock = serde_json::from_str(contents)
+        .map_err(|e| format!("Failed to parse {}: {}", PACK_LOCK_FILE, e))?;
+
+    if lock.format_version > PACK_LOCK_FORMAT_VERSION {
+        return Err(format!(
+            "{} format version {} is not supported (max {})",
+            PACK_LOCK_FILE, lock.format_version, PACK_LOCK_FORMAT_VERSION
+        ));
+    }
+
+    Ok(lock)
+}
+
+/// Lowercase hex SHA-256 of a byte slice
+pub fn sha256_bytes(data: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(data))
+}
+
+/// Lowercase hex SHA-256 of everything a reader yields
+pub fn sha256_reader<R: Read>(reader: &mut R) -> io::Result<String> {
+    let mut hasher = Sha256::new();
+    io::copy(reader, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
@@ -0,0 +1,404 @@
+use super::fs::FileSystem;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{Read, Seek};
+use std::path::Path;
+use zip::ZipArchive;
+
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ModManifest {
+    #[serde(rename = "Group")]
+    pub group: String,
+    #[serde(rename = "Name")]
+    pub name: String,
+    #[serde(rename = "Version")]
+    pub version: String,
+    #[serde(rename = "Description")]
+    #[serde(default)]
+    pub description: String,
+    #[serde(rename = "Authors")]
+    #[serde(default)]
+    pub authors: Vec<ModAuthor>,
+    #[serde(rename = "Website")]
+    pub website: Option<String>,
+    #[serde(rename = "ServerVersion")]
+    #[serde(default)]
+    pub server_version: String,
+    #[serde(rename = "Dependencies")]
+    #[serde(default)]
+    pub dependencies: HashMap<String, String>,
+    #[serde(rename = "OptionalDependencies")]
+    #[serde(default)]
+    pub optional_dependencies: HashMap<String, String>,
+    #[serde(rename = "DisabledByDefault")]
+    #[serde(default)]
+    pub disabled_by_default: bool,
+    #[serde(rename = "Main")]
+    #[serde(default)]
+    pub main: String,
+    #[serde(rename = "IncludesAssetPack")]
+    #[serde(default)]
+    pub includes_asset_pack: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ModAuthor {
+    #[serde(rename = "Name")]
+    pub name: String,
+}
+
+impl ModManifest {
+    /// "Group:Name", the key config.json enables the mod under
+    pub fn key(&self) -> String {
+        format!("{}:{}", self.group, self.name)
+    }
+}
+
+/// Kind of content in an archive, matching the Orbis catalog's resource types
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ResourceType {
+    /// Server code: a manifest with a `Main` class and no assets
+    #[default]
+    Plugin,
+    /// Assets under `Common/` (and possibly `Server/`), no code
+    AssetPack,
+    /// Code that also ships assets (`IncludesAssetPack`, or `Common/`/`Server/` entries)
+    Mod,
+    /// Server data under `Server/` only, no code
+    DataPack,
+    /// Loose `.prefab.json` or `.schematic` structures
+    Prefab,
+    /// A save folder, with world data under `universe/`
+    World,
+}
+
+/// Whether any entry lives under `dir` (e.g. "Common/"), ignoring case
+pub(crate) fn has_dir(names: &[String], dir: &str) -> bool {
+    names.iter().any(|name| {
+        name.len() > dir.len()
+            && name
+                .get(..dir.len())
+                .is_some_and(|prefix| prefix.eq_ignore_ascii_case(dir))
+    })
+}
+
+/// Classify content that has a manifest, from its `Main`/`IncludesAssetPack`
+/// fields and the entries next to it
+pub fn classify_manifest(manifest: &ModManifest, names: &[String]) -> ResourceType {
+    let has_common = has_dir(names, "Common/");
+    let has_server = has_dir(names, "Server/");
+
+    if !manifest.main.trim().is_empty() {
+        if manifest.includes_asset_pack || has_common || has_server {
+            ResourceType::Mod
+        } else {
+            ResourceType::Plugin
+        }
+    } else if has_server && !has_common && !manifest.includes_asset_pack {
+        ResourceType::DataPack
+    } else {
+        ResourceType::AssetPack
+    }
+}
+
+/// A deviation from the canonical `manifest.json` at the archive root that was
+/// tolerated while reading a manifest
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ManifestFallback {
+    /// Found under a top-level folder instead of the archive root
+    NestedFolder,
+    /// File name differs from `manifest.json` in casing
+    FileNameCasing,
+    /// File starts with a UTF-8 byte order mark
+    ByteOrderMark,
+    /// JSON has trailing commas before `}` or `]`
+    TrailingCommas,
+}
+
+impl ManifestFallback {
+    pub fn describe(self) -> &'static str {
+        match self {
+            ManifestFallback::NestedFolder => "manifest is inside a top-level folder",
+            ManifestFallback::FileNameCasing => "manifest file name has non-standard casing",
+            ManifestFallback::ByteOrderMark => "manifest starts with a UTF-8 BOM",
+            ManifestFallback::TrailingCommas => "manifest has trailing commas",
+        }
+    }
+}
+
+/// One mod manifest found in an archive or unpacked mod
+#[derive(Debug, Serialize, Clone)]
+pub struct DiscoveredManifest {
+    /// Path of the manifest inside the archive or directory
+    pub entry_path: String,
+    pub manifest: ModManifest,
+    pub fallbacks: Vec<ManifestFallback>,
+    /// Classified from the manifest and the entries next to it
+    pub resource_type: ResourceType,
+}
+
+/// Compare two manifest versions segment by segment ("1.10.0" > "1.9.2"),
+/// numerically where both segments are numbers. Missing segments count as
+/// zero, so "2.0" == "2.0.0".
+pub fn compare_versions(a: &str, b: &str) -> std::cmp::Ordering {
+    let split = |v: &str| -> Vec<String> {
+        v.split(|c: char| !c.is_ascii_alphanumeric())
+            .filter(|s| !s.is_empty())
+            .map(|s| s.to_lowercase())
+            .collect()
+    };
+    let (a, b) = (split(a), split(b));
+    let zero = "0".to_string();
+
+    for i in 0..a.len().max(b.len()) {
+        let (x, y) = (a.get(i).unwrap_or(&zero), b.get(i).unwrap_or(&zero));
+        let ordering = match (x.parse::<u64>(), y.parse::<u64>()) {
+            (Ok(x), Ok(y)) => x.cmp(&y),
+            _ => x.cmp(y),
+        };
+        if ordering != std::cmp::Ordering::Equal {
+            return ordering;
+        }
+    }
+
+    std::cmp::Ordering::Equal
+}
+
+/// Drop commas that directly precede a closing `}` or `]`, leaving strings alone
+fn strip_trailing_commas(json: &str) -> String {
+    let chars: Vec<char> = json.chars().collect();
+    let mut out = String::with_capacity(json.len());
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for (i, &c) in chars.iter().enumerate() {
+        if in_string {
+            out.push(c);
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == '"' {
+                in_string = false;
+            }
+            continue;
+        }
+
+        match c {
+            '"' => {
+                in_string = true;
+                out.push(c);
+            }
+            ',' => {
+                let next = chars[i + 1..].iter().find(|c| !c.is_whitespace());
+                if !matches!(next, Some('}') | Some(']')) {
+                    out.push(c);
+                }
+            }
+            _ => out.push(c),
+        }
+    }
+
+    out
+}
+
+/// Parse manifest JSON, tolerating a BOM and trailing commas
+pub fn parse_manifest_text(contents: &str) -> Result<(ModManifest, Vec<ManifestFallback>), String> {
+    let mut fallbacks = Vec::new();
+
+    let contents = match contents.strip_prefix('\u{feff}') {
+        Some(rest) => {
+            fallbacks.push(ManifestFallback::ByteOrderMark);
+            rest
+        }
+        None => contents,
+    };
+
+    match serde_json::from_str(contents) {
+        Ok(manifest) => Ok((manifest, fallbacks)),
+        Err(e) => {
+            let cleaned = strip_trailing_commas(contents);
+            if cleaned == contents {
+                return Err(format!("Failed to parse manifest.json: {}", e));
+            }
+
+            let manifest = serde_json::from_str(&cleaned)
+                .map_err(|_| format!("Failed to parse manifest.json: {}", e))?;
+            fallbacks.push(ManifestFallback::TrailingCommas);
+            Ok((manifest, fallbacks))
+        }
+    }
+}
+
+/// Where a zip entry could hold a mod manifest, and which fallbacks it needs
+fn manifest_candidate(name: &str) -> Option<Vec<ManifestFallback>> {
+    let parts: Vec<&str> = name.split('/').collect();
+    let file_name = *parts.last()?;
+
+    if !file_name.eq_ignore_ascii_case(MANIFEST_FILE) {
+        return None;
+    }
+
+    let mut fallbacks = Vec::new();
+    match parts.len() {
+        1 => {}
+        2 => fallbacks.push(ManifestFallback::NestedFolder),
+        _ => return None,
+    }
+
+    if file_name != MANIFEST_FILE {
+        fallbacks.push(ManifestFallback::FileNameCasing);
+    }
+
+    Some(fallbacks)
+}
+
+/// Whether any of an archive's entries could be a mod manifest
+pub fn has_manifest_candidate(names: &[String]) -> bool {
+    names.iter().any(|name| manifest_candidate(name).is_some())
+}
+
+/// Entries in the same folder as a manifest (or below it), relative to that folder
+fn entries_beside(names: &[String], manifest_path: &str) -> Vec<String> {
+    let folder = match manifest_path.rsplit_once('/') {
+        Some((folder, _)) => format!("{}/", folder),
+        None => String::new(),
+    };
+
+    names
+        .iter()
+        .filter_map(|name| name.strip_prefix(&folder))
+        .map(String::from)
+        .collect()
+}
+
+/// Find every mod manifest in an archive: `manifest.json` at the root, then
+/// manifests one folder down (mods zipped with their folder, or archives that
+/// bundle several mods), matching the file name case-insensitively
+pub fn discover_manifests_in_archive<R: Read + Seek>(
+    reader: R,
+) -> Result<Vec<DiscoveredManifest>, String> {
+    let mut archive =
+        ZipArchive::new(reader).map_err(|e| format!("Failed to read archive: {}", e))?;
+
+    let names: Vec<String> = archive.file_names().map(String::from).collect();
+    let mut candidates: Vec<(String, Vec<ManifestFallback>)> = names
+        .iter()
+        .filter_map(|name| manifest_candidate(name).map(|f| (name.clone(), f)))
+        .collect();
+    candidates.sort_by_key(|(name, fallbacks)| (fallbacks.len(), name.clone()));
+
+    let mut found = Vec::new();
+    let mut errors = Vec::new();
+
+    for (name, mut fallbacks) in candidates {
+        let mut contents = String::new();
+        let read = archive
+            .by_name(&name)
+            .map_err(|e| e.to_string())
+            .and_then(|mut f| f.read_to_string(&mut contents).map_err(|e| e.to_string()));
+        if let Err(e) = read {
+            errors.push(format!("{}: {}", name, e));
+            continue;
+        }
+
+        match parse_manifest_text(&contents) {
+            Ok((manifest, parse_fallbacks)) => {
+                fallbacks.extend(parse_fallbacks);
+                let resource_type = classify_manifest(&manifest, &entries_beside(&names, &name));
+                found.push(DiscoveredManifest {
+                    entry_path: name,
+                    manifest,
+                    fallbacks,
+                    resource_type,
+                });
+            }
+            Err(e) => errors.push(format!("{}: {}", name, e)),
+        }
+    }
+
+    if found.is_empty() {
+        if errors.is_empty() {
+            return Err("manifest.json not found in archive".to_string());
+        }
+        return Err(errors.join("; "));
+    }
+
+    Ok(found)
+}
+
+/// Find every mod manifest in a mod archive or unpacked mod directory
+pub fn discover_manifests(
+    fs: &dyn FileSystem,
+    path: &Path,
+) -> Result<Vec<DiscoveredManifest>, String> {
+    if !fs.is_dir(path) {
+        let file = fs
+            .open(path)
+            .map_err(|e| format!("Failed to open archive file: {}", e))?;
+        return discover_manifests_in_archive(file);
+    }
+
+    let contents = fs
+        .read_to_string(&path.join(MANIFEST_FILE))
+        .map_err(|e| format!("Failed to read manifest.json: {}", e))?;
+    let (manifest, fallbacks) = parse_manifest_text(&contents)?;
+    let resource_type = classify_manifest(&manifest, &entry_names(fs, path)?);
+
+    Ok(vec![DiscoveredManifest {
+        entry_path: MANIFEST_FILE.to_string(),
+        manifest,
+        fallbacks,
+        resource_type,
+    }])
+}
+
+fn collect_dir_names(
+    fs: &dyn FileSystem,
+    dir: &Path,
+    prefix: &str,
+    out: &mut Vec<String>,
+) -> Result<(), String> {
+    let entries = fs
+        .read_dir(dir)
+        .map_err(|e| format!("Failed to read directory {:?}: {}", dir, e))?;
+
+    for path in entries {
+        let name = match path.file_name() {
+            Some(name) => name.to_string_lossy().to_string(),
+            None => continue,
+        };
+        if fs.is_dir(&path) {
+            out.push(format!("{}{}/", prefix, name));
+            collect_dir_names(fs, &path, &format!("{}{}/", prefix, name), out)?;
+        } else {
+            out.push(format!("{}{}", prefix, name));
+        }
+    }
+
+    Ok(())
+}
+
+/// Every entry of an archive, or every path under a directory, `/`-separated
+/// with a trailing `/` for directories
+pub fn entry_names(fs: &dyn FileSystem, path: &Path) -> Result<Vec<String>, String> {
+    let mut names = Vec::new();
+
+    if fs.is_dir(path) {
+        collect_dir_names(fs, path, "", &mut names)?;
+    } else {
+        let file = fs
+            .open(path)
+            .map_err(|e| format!("Failed to open archive file: {}", e))?;
+        let archive =
+            ZipArchive::new(file).map_err(|e| format!("Failed to read archive: {}", e))?;
+        names.extend(archive.file_names().map(String::from));
+    }
+
+    names.sort();
+    Ok(names)
+}
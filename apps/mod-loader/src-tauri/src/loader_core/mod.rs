@@ -0,0 +1,29 @@
+//! The mod loader's logic without Tauri: plain functions and types over a
+//! `FileSystem`, returning `String` errors and leaving logging to the caller.
+//! The app's commands and the `orbis-mods` CLI wrap these with `OsFs`; tests
+//! run them against `MemoryFs`.
+
+pub mod fs;
+pub mod lockfile;
+pub mod manifest;
+pub mod mod_index;
+pub mod modpack_installer;
+pub mod modpack_upgrade;
+pub mod orbis_metadata;
+pub mod receipts;
+pub mod resource_installer;
+pub mod save_config;
+pub mod save_importer;
+pub mod server_deploy;
+
+#[cfg(test)]
+mod tests;
+
+pub use fs::{FileSystem, MemoryFs, OsFs};
+pub use mod_index::{ModIndex, ModLocation, ResolvedMod};
+pub use modpack_installer::{InstallJournal, ModpackInstaller};
+pub use receipts::{InstallReceipt, ReceiptStore};
+pub use resource_installer::ResourceInstaller;
+pub use save_config::SaveConfig;
+pub use save_importer::SaveImporter;
+pub use server_deploy::ServerDeployer;
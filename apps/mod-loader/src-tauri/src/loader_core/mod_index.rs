@@ -0,0 +1,147 @@
+use super::fs::FileSystem;
+use super::manifest::{
+    discover_manifests, DiscoveredManifest, ManifestFallback, ModManifest, ResourceType,
+    MANIFEST_FILE,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Where a mod archive lives
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ModLocation {
+    /// <save>/mods, only visible to that save
+    SaveLocal,
+    /// UserData/Mods, shared by every save
+    #[default]
+    Global,
+}
+
+/// A mod archive or unpacked mod found by `ModIndex`
+#[derive(Debug, Clone)]
+pub struct ResolvedMod {
+    pub path: PathBuf,
+    pub manifest: ModManifest,
+    pub location: ModLocation,
+    /// Global archive with the same Group:Name that this one takes precedence over
+    pub shadowed_global: Option<PathBuf>,
+    pub manifest_fallbacks: Vec<ManifestFallback>,
+    pub resource_type: ResourceType,
+}
+
+/// Whether a file is a mod archive (.jar or .zip)
+pub fn is_mod_archive(path: &Path) -> bool {
+    match path.extension().and_then(|s| s.to_str()) {
+        Some(ext) => {
+            let ext = ext.to_lowercase();
+            ext == "jar" || ext == "zip"
+        }
+        None => false,
+    }
+}
+
+/// Whether a path is an unpacked mod (a directory with manifest.json at its root)
+pub fn is_mod_directory(fs: &dyn FileSystem, path: &Path) -> bool {
+    fs.is_dir(path) && fs.is_file(&path.join(MANIFEST_FILE))
+}
+
+/// Whether a path is a mod, packed or unpacked
+pub fn is_mod_entry(fs: &dyn FileSystem, path: &Path) -> bool {
+    is_mod_archive(path) || is_mod_directory(fs, path)
+}
+
+/// Map "Group:Name" -> (path, manifest) for every manifest of every mod in a directory.
+/// An archive bundling several mods appears once per mod; unreadable mods are left out.
+fn index_mods_dir(
+    fs: &dyn FileSystem,
+    mods_dir: &Path,
+) -> HashMap<String, (PathBuf, DiscoveredManifest)> {
+    let mut index = HashMap::new();
+
+    let entries = match fs.read_dir(mods_dir) {
+        Ok(entries) => entries,
+        Err(_) => return index,
+    };
+
+    for path in entries {
+        if !is_mod_entry(fs, &path) {
+            continue;
+        }
+        if let Ok(manifests) = discover_manifests(fs, &path) {
+            for discovered in manifests {
+                index.insert(discovered.manifest.key(), (path.clone(), discovered));
+            }
+        }
+    }
+
+    index
+}
+
+fn resolve(path: PathBuf, discovered: DiscoveredManifest, location: ModLocation) -> ResolvedMod {
+    ResolvedMod {
+        path,
+        manifest: discovered.manifest,
+        location,
+        shadowed_global: None,
+        manifest_fallbacks: discovered.fallbacks,
+        resource_type: discovered.resource_type,
+    }
+}
+
+/// The mods visible from UserData/Mods and, for a save, its own mods/ dir,
+/// indexed by "Group:Name"
+#[derive(Debug, Clone, Default)]
+pub struct ModIndex {
+    mods: HashMap<String, ResolvedMod>,
+}
+
+impl ModIndex {
+    /// Index the mods in UserData/Mods
+    pub fn global(fs: &dyn FileSystem, global_mods_dir: &Path) -> Self {
+        let mods = index_mods_dir(fs, global_mods_dir)
+            .into_iter()
+            .map(|(key, (path, discovered))| (key, resolve(path, discovered, ModLocation::Global)))
+            .collect();
+
+        ModIndex { mods }
+    }
+
+    /// Index the mods a save can see. Mods in the save's own mods/ dir take
+    /// precedence over global ones with the same Group:Name.
+    pub fn for_save(fs: &dyn FileSystem, save_path: &Path, global_mods_dir: &Path) -> Self {
+        let mut index = ModIndex::global(fs, global_mods_dir);
+
+        for (key, (path, discovered)) in index_mods_dir(fs, &save_path.join("mods")) {
+            let mut resolved = resolve(path, discovered, ModLocation::SaveLocal);
+            resolved.shadowed_global = index.mods.remove(&key).map(|global| global.path);
+            index.mods.insert(key, resolved);
+        }
+
+        index
+    }
+
+    pub fn get(&self, mod_key: &str) -> Option<&ResolvedMod> {
+        self.mods.get(mod_key)
+    }
+
+    pub fn contains(&self, mod_key: &str) -> bool {
+        self.mods.contains_key(mod_key)
+    }
+
+    pub fn len(&self) -> usize {
+        self.mods.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.mods.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &ResolvedMod)> {
+        self.mods.iter()
+    }
+
+    pub fn into_map(self) -> HashMap<String, ResolvedMod> {
+        self.mods
+    }
+}
@@ -0,0 +1,794 @@
+use super::fs::FileSystem;
+use super::lockfile::{parse_pack_lock, sha256_reader, PackLock, PACK_LOCK_FILE};
+use super::manifest::{compare_versions, discover_manifests_in_archive, ModManifest};
+use super::mod_index::ModIndex;
+use super::orbis_metadata::{read_orbis_metadata, write_orbis_metadata, OrbisMetadataEntry};
+use super::save_config::SaveConfig;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::io::{Cursor, Read, Seek};
+use std::path::{Path, PathBuf};
+use zip::ZipArchive;
+
+/// Which receipt list a written file belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileScope {
+    /// UserData/Mods
+    Global,
+    /// The save directory
+    Save,
+}
+
+/// Carries out the installer's writes and remembers what they replaced, so an
+/// install can be reverted. The app's `InstallReceipt` backs files up before
+/// overwriting them; tests can simply record the calls.
+pub trait InstallJournal {
+    /// Write `data` to `path`, creating its parent directories
+    fn write_file(
+        &mut self,
+        fs: &dyn FileSystem,
+        scope: FileScope,
+        path: &Path,
+        data: &[u8],
+        mod_key: Option<String>,
+    ) -> Result<(), String>;
+
+    /// Delete the file at `path`, if there is one
+    fn remove_file(
+        &mut self,
+        fs: &dyn FileSystem,
+        scope: FileScope,
+        path: &Path,
+        mod_key: Option<String>,
+    ) -> Result<(), String>;
+
+    /// A config.json entry's state before the install changes it
+    fn record_config_entry(&mut self, mod_key: &str, previous: Option<bool>);
+
+    /// An orbis-metadata.json entry before the install changes it
+    fn record_metadata_entry(&mut self, file_name: &str, previous: Option<OrbisMetadataEntry>);
+}
+
+/// Read orbis-pack.json from the root of a modpack, if present
+fn read_pack_lock<R: Read + Seek>(archive: &mut ZipArchive<R>) -> Result<Option<PackLock>, String> {
+    let mut lock_file = match archive.by_name(PACK_LOCK_FILE) {
+        Ok(f) => f,
+        Err(_) => return Ok(None),
+    };
+
+    let mut contents = String::new();
+    lock_file
+        .read_to_string(&mut contents)
+        .map_err(|e| format!("Failed to read {}: {}", PACK_LOCK_FILE, e))?;
+
+    parse_pack_lock(&contents).map(Some)
+}
+
+/// Check every Mods/ and Configs/ entry against the hashes in orbis-pack.json
+/// before anything is written. The lock must list exactly the pack's entries:
+/// an unlisted entry or a listed one missing from the zip fails the install.
+fn verify_pack_lock<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    lock: &PackLock,
+) -> Result<(), String> {
+    let mut seen = HashSet::new();
+
+    for i in 0..archive.len() {
+        let mut zip_file = archive
+            .by_index(i)
+            .map_err(|e| format!("Failed to read zip entry: {}", e))?;
+
+        let name = zip_file.name().to_string();
+        if name.ends_with('/') {
+            continue;
+        }
+
+        let expected = if let Some(file_name) = name.strip_prefix("Mods/") {
+            lock.find_by_file(file_name).map(|m| m.sha256.clone())
+        } else if let Some(file_name) = name.strip_prefix("Configs/") {
+            lock.find_by_config(file_name)
+                .and_then(|m| m.config.as_ref())
+                .map(|c| c.sha256.clone())
+        } else {
+            continue;
+        };
+
+        let expected =
+            expected.ok_or_else(|| format!("{} is not listed in {}", name, PACK_LOCK_FILE))?;
+
+        let actual =
+            sha256_reader(&mut zip_file).map_err(|e| format!("Failed to hash {}: {}", name, e))?;
+
+        if !actual.eq_ignore_ascii_case(&expected) {
+            return Err(format!(
+                "Hash mismatch for {}: expected {}, got {}",
+                name, expected, actual
+            ));
+        }
+
+        seen.insert(name);
+    }
+
+    for lock_mod in &lock.mods {
+        let mut expected = vec![format!("Mods/{}", lock_mod.file_name)];
+        if let Some(config) = &lock_mod.config {
+            expected.push(format!("Configs/{}", config.file_name));
+        }
+
+        if let Some(missing) = expected.iter().find(|name| !seen.contains(*name)) {
+            return Err(format!(
+                "{} lists {} but the modpack does not contain it",
+                PACK_LOCK_FILE, missing
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// A mod archive a modpack install wrote to UserData/Mods
+pub struct InstalledArchive {
+    /// Name under the pack's Mods/, as orbis-pack.json lists it
+    pub pack_file_name: String,
+    /// Name it was written under, which differs when both versions were kept
+    pub file_name: String,
+    pub manifest: ModManifest,
+}
+
+/// Record Orbis metadata for installed archives listed in orbis-pack.json.
+/// Returns warnings for archives whose manifest disagrees with the lock.
+pub fn apply_pack_lock_metadata(
+    fs: &dyn FileSystem,
+    global_mods_dir: &Path,
+    lock: &PackLock,
+    installed: &[InstalledArchive],
+    journal: &mut dyn InstallJournal,
+) -> Result<Vec<String>, String> {
+    let mut metadata = read_orbis_metadata(fs, global_mods_dir)?;
+    let installed_at = chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true);
+    let mut warnings = Vec::new();
+    let mut changed = false;
+
+    for InstalledArchive {
+        pack_file_name,
+        file_name,
+        manifest,
+    } in installed
+    {
+        let entry = match lock.find_by_file(pack_file_name) {
+            Some(e) => e,
+            None => continue,
+        };
+
+        let mod_key = manifest.key();
+        if entry.key != mod_key || entry.version != manifest.version {
+            warnings.push(format!(
+                "{} declares {}@{} but {} lists {}@{}",
+                file_name, mod_key, manifest.version, PACK_LOCK_FILE, entry.key, entry.version
+            ));
+        }
+
+        let resource_id = match &entry.resource_id {
+            Some(id) => id.clone(),
+            None => continue,
+        };
+
+        // Keep catalog details the frontend recorded for the same resource
+        let previous = metadata
+            .get(file_name)
+            .filter(|m| m.id == resource_id)
+            .cloned();
+
+        journal.record_metadata_entry(file_name, metadata.get(file_name).cloned());
+        metadata.insert(
+            file_name.clone(),
+            OrbisMetadataEntry {
+                id: resource_id,
+                slug: previous.as_ref().and_then(|m| m.slug.clone()),
+                name: previous
+                    .as_ref()
+                    .map(|m| m.name.clone())
+                    .unwrap_or_else(|| manifest.name.clone()),
+                author: previous
+                    .as_ref()
+                    .map(|m| m.author.clone())
+                    .or_else(|| manifest.authors.first().map(|a| a.name.clone()))
+                    .unwrap_or_default(),
+                icon_url: previous.as_ref().and_then(|m| m.icon_url.clone()),
+                version: entry.version.clone(),
+                version_id: entry.version_id.clone(),
+                installed_at: installed_at.clone(),
+                sha256: Some(entry.sha256.clone()),
+            },
+        );
+        changed = true;
+    }
+
+    if changed {
+        write_orbis_metadata(fs, global_mods_dir, &metadata)?;
+    }
+
+    Ok(warnings)
+}
+
+/// A mod archive from a modpack's Mods/ folder
+pub struct PackMod {
+    pub file_name: String,
+    pub data: Vec<u8>,
+    pub manifest: Option<ModManifest>,
+}
+
+/// A file unpacked from a modpack, relative to the directory it installs into
+pub struct PackFile {
+    pub relative_path: PathBuf,
+    pub data: Vec<u8>,
+}
+
+/// A modpack read fully into memory, with hashes already verified
+pub struct PackContents {
+    pub name: String,
+    pub lock: Option<PackLock>,
+    pub mods: Vec<PackMod>,
+    /// Files from Configs/*.zip, relative to the save's mods/ dir
+    pub configs: Vec<PackFile>,
+    /// World data from World/, relative to the save root
+    pub world: Vec<PackFile>,
+    /// Entries that were skipped or couldn't be fully read
+    pub warnings: Vec<String>,
+}
+
+/// A plain file name, with no directory components that could escape the target dir
+pub fn is_safe_file_name(name: &str) -> bool {
+    !name.is_empty() && name != "." && name != ".." && !name.contains(['/', '\\', ':'])
+}
+
+/// Unpack a zip nested inside a modpack, skipping entries that would escape the target dir
+fn read_nested_archive(
+    data: Vec<u8>,
+    archive_name: &str,
+    warnings: &mut Vec<String>,
+) -> Result<Vec<PackFile>, String> {
+    let mut archive = ZipArchive::new(Cursor::new(data))
+        .map_err(|e| format!("Failed to read archive {}: {}", archive_name, e))?;
+
+    let mut files = Vec::new();
+    for i in 0..archive.len() {
+        let mut entry = archive
+            .by_index(i)
+            .map_err(|e| format!("Failed to read entry in {}: {}", archive_name, e))?;
+
+        if entry.is_dir() {
+            continue;
+        }
+
+        let relative_path = match entry.enclosed_name() {
+            Some(p) => p.to_path_buf(),
+            None => {
+                warnings.push(format!(
+                    "Skipping unsafe path {} in {}",
+                    entry.name(),
+                    archive_name
+                ));
+                continue;
+            }
+        };
+
+        let mut data = Vec::new();
+        entry
+            .read_to_end(&mut data)
+            .map_err(|e| format!("Failed to read {:?}: {}", relative_path, e))?;
+
+        files.push(PackFile {
+            relative_path,
+            data,
+        });
+    }
+
+    Ok(files)
+}
+
+/// Read a modpack zip, verifying it against orbis-pack.json and rejecting unsafe paths
+pub fn read_pack(fs: &dyn FileSystem, modpack_path: &Path) -> Result<PackContents, String> {
+    let file = fs
+        .open(modpack_path)
+        .map_err(|e| format!("Failed to open modpack zip: {}", e))?;
+    let mut archive =
+        ZipArchive::new(file).map_err(|e| format!("Failed to read modpack zip: {}", e))?;
+
+    let lock = read_pack_lock(&mut archive)?;
+    if let Some(lock) = &lock {
+        verify_pack_lock(&mut archive, lock)?;
+    }
+
+    let mut contents = PackContents {
+        name: modpack_path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("modpack")
+            .to_string(),
+        lock,
+        mods: Vec::new(),
+        configs: Vec::new(),
+        world: Vec::new(),
+        warnings: Vec::new(),
+    };
+
+    for i in 0..archive.len() {
+        let mut zip_file = archive
+            .by_index(i)
+            .map_err(|e| format!("Failed to read zip entry: {}", e))?;
+
+        let name = zip_file.name().to_string();
+        if name.ends_with('/') {
+            continue;
+        }
+
+        if let Some(file_name) = name.strip_prefix("Mods/") {
+            if !is_safe_file_name(file_name) {
+                contents
+                    .warnings
+                    .push(format!("Skipping unsafe mod path {}", name));
+                continue;
+            }
+
+            let mut data = Vec::new();
+            zip_file
+                .read_to_end(&mut data)
+                .map_err(|e| format!("Failed to read {}: {}", name, e))?;
+
+            let manifest = match discover_manifests_in_archive(Cursor::new(&data)) {
+                Ok(manifests) => manifests.into_iter().next().map(|d| d.manifest),
+                Err(e) => {
+                    contents
+                        .warnings
+                        .push(format!("Failed to read manifest from {}: {}", name, e));
+                    None
+                }
+            };
+
+            contents.mods.push(PackMod {
+                file_name: file_name.to_string(),
+                data,
+                manifest,
+            });
+        } else if let Some(archive_name) = name.strip_prefix("Configs/") {
+            if !archive_name.ends_with(".zip") {
+                continue;
+            }
+
+            let mut config_data = Vec::new();
+            zip_file
+                .read_to_end(&mut config_data)
+                .map_err(|e| format!("Failed to read config zip {}: {}", archive_name, e))?;
+
+            let files = read_nested_archive(config_data, archive_name, &mut contents.warnings)?;
+            contents.configs.extend(files);
+        } else if let Some(world_entry) = name.strip_prefix("World/") {
+            let mut data = Vec::new();
+            zip_file
+                .read_to_end(&mut data)
+                .map_err(|e| format!("Failed to read {}: {}", name, e))?;
+
+            // A bundled WORLD resource archive, laid out like an exported save
+            if !world_entry.contains('/') && world_entry.ends_with(".zip") {
+                let files = read_nested_archive(data, world_entry, &mut contents.warnings)?;
+                contents.world.extend(files);
+                continue;
+            }
+
+            let relative_path = match zip_file
+                .enclosed_name()
+                .and_then(|p| p.strip_prefix("World").ok())
+            {
+                Some(p) => p.to_path_buf(),
+                None => {
+                    contents
+                        .warnings
+                        .push(format!("Skipping unsafe world path {}", name));
+                    continue;
+                }
+            };
+
+            contents.world.push(PackFile {
+                relative_path,
+                data,
+            });
+        }
+    }
+
+    Ok(contents)
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ArchiveStatus {
+    /// No mod with this Group:Name in UserData/Mods
+    New,
+    /// The same version is already installed
+    SameVersionPresent,
+    /// A different version is already installed
+    DifferentVersionPresent,
+}
+
+/// How to resolve a modpack archive whose Group:Name is already in UserData/Mods
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictPolicy {
+    /// Install the pack's archive only if its version is newer
+    #[default]
+    KeepNewer,
+    KeepExisting,
+    Replace,
+    /// Install alongside the existing archive
+    KeepBoth,
+}
+
+/// Options for `install_modpack`
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct InstallOptions {
+    /// Only compute the plan
+    #[serde(default)]
+    pub dry_run: bool,
+    #[serde(default)]
+    pub conflict_policy: ConflictPolicy,
+    /// Per-mod policy by "Group:Name", overriding `conflict_policy`
+    #[serde(default)]
+    pub conflict_overrides: HashMap<String, ConflictPolicy>,
+    /// The zip was downloaded by the loader and can be moved into the modpack
+    /// cache. Otherwise it belongs to the caller and is left where it is.
+    #[serde(default)]
+    pub downloaded: bool,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ArchiveAction {
+    /// Write the archive to `dest_path`
+    Write,
+    /// Write the archive and remove the existing one
+    Replace,
+    /// Leave the existing archive in place
+    Skip,
+}
+
+/// An archive the install would write to UserData/Mods
+#[derive(Debug, Serialize, Clone)]
+pub struct PlannedArchive {
+    pub file_name: String,
+    pub dest_path: String,
+    pub mod_key: Option<String>,
+    pub version: Option<String>,
+    pub status: ArchiveStatus,
+    pub existing_file: Option<String>,
+    pub existing_version: Option<String>,
+    pub action: ArchiveAction,
+}
+
+/// A config file the install would write to the save's mods/ dir
+#[derive(Debug, Serialize, Clone)]
+pub struct PlannedConfigFile {
+    pub path: String,
+    pub overwrites: bool,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigKeyAction {
+    /// Key is not in config.json yet
+    Add,
+    /// Key is present but disabled
+    Enable,
+    AlreadyEnabled,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct PlannedConfigEntry {
+    pub mod_key: String,
+    pub action: ConfigKeyAction,
+}
+
+/// Everything an install will do, computed before touching any file
+#[derive(Debug, Serialize, Clone)]
+pub struct InstallPlan {
+    pub modpack_name: String,
+    pub save_path: String,
+    pub archives: Vec<PlannedArchive>,
+    pub config_files: Vec<PlannedConfigFile>,
+    pub config_entries: Vec<PlannedConfigEntry>,
+}
+
+/// Pick a file name next to `file_name` that doesn't exist in `dir` yet
+pub(crate) fn alternate_file_name(
+    fs: &dyn FileSystem,
+    dir: &Path,
+    file_name: &str,
+    version: Option<&str>,
+) -> String {
+    let path = Path::new(file_name);
+    let stem = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or(file_name);
+    let extension = path
+        .extension()
+        .and_then(|s| s.to_str())
+        .map(|e| format!(".{}", e))
+        .unwrap_or_default();
+    let base = match version {
+        Some(version) => format!("{}-{}", stem, version),
+        None => stem.to_string(),
+    };
+
+    let candidate = format!("{}{}", base, extension);
+    if !fs.exists(&dir.join(&candidate)) {
+        return candidate;
+    }
+
+    (2..)
+        .map(|n| format!("{}-{}{}", base, n, extension))
+        .find(|name| !fs.exists(&dir.join(name)))
+        .unwrap_or(candidate)
+}
+
+/// Decide what to do with one archive given its status and the conflict policy
+pub fn resolve_archive_action(
+    status: ArchiveStatus,
+    policy: ConflictPolicy,
+    version: Option<&str>,
+    existing_version: Option<&str>,
+) -> ArchiveAction {
+    match (status, policy) {
+        (ArchiveStatus::New, _) => ArchiveAction::Write,
+        (ArchiveStatus::SameVersionPresent, ConflictPolicy::Replace) => ArchiveAction::Replace,
+        (ArchiveStatus::SameVersionPresent, _) => ArchiveAction::Skip,
+        (ArchiveStatus::DifferentVersionPresent, ConflictPolicy::KeepExisting) => {
+            ArchiveAction::Skip
+        }
+        (ArchiveStatus::DifferentVersionPresent, ConflictPolicy::Replace) => ArchiveAction::Replace,
+        (ArchiveStatus::DifferentVersionPresent, ConflictPolicy::KeepBoth) => ArchiveAction::Write,
+        (ArchiveStatus::DifferentVersionPresent, ConflictPolicy::KeepNewer) => {
+            match (version, existing_version) {
+                (Some(new), Some(old)) if compare_versions(new, old).is_gt() => {
+                    ArchiveAction::Replace
+                }
+                _ => ArchiveAction::Skip,
+            }
+        }
+    }
+}
+
+/// Installs modpacks into saves under one Hytale data directory: archives go
+/// to UserData/Mods, config files to the save's mods/ dir, and the mods are
+/// enabled in the save's config.json
+pub struct ModpackInstaller<'a> {
+    fs: &'a dyn FileSystem,
+    hytale_root: PathBuf,
+}
+
+impl<'a> ModpackInstaller<'a> {
+    pub fn new(fs: &'a dyn FileSystem, hytale_root: impl Into<PathBuf>) -> Self {
+        ModpackInstaller {
+            fs,
+            hytale_root: hytale_root.into(),
+        }
+    }
+
+    pub fn global_mods_dir(&self) -> PathBuf {
+        self.hytale_root.join("UserData").join("Mods")
+    }
+
+    /// Read and verify a modpack zip
+    pub fn read_pack(&self, modpack_path: &Path) -> Result<PackContents, String> {
+        read_pack(self.fs, modpack_path)
+    }
+
+    /// Work out what installing `pack` into a save would change
+    pub fn plan(
+        &self,
+        pack: &PackContents,
+        save_path: &Path,
+        options: &InstallOptions,
+    ) -> Result<InstallPlan, String> {
+        let global_mods_dir = self.global_mods_dir();
+        let save_mods_dir = save_path.join("mods");
+        let global_mods_index = ModIndex::global(self.fs, &global_mods_dir);
+        let config = SaveConfig::load(self.fs, save_path)?;
+
+        let mut plan = InstallPlan {
+            modpack_name: pack.name.clone(),
+            save_path: save_path.to_string_lossy().to_string(),
+            archives: Vec::new(),
+            config_files: Vec::new(),
+            config_entries: Vec::new(),
+        };
+
+        for pack_mod in &pack.mods {
+            let mod_key = pack_mod.manifest.as_ref().map(|m| m.key());
+            let version = pack_mod.manifest.as_ref().map(|m| m.version.clone());
+            let existing = mod_key.as_ref().and_then(|k| global_mods_index.get(k));
+
+            let status = match existing {
+                None => ArchiveStatus::New,
+                Some(existing) if Some(&existing.manifest.version) == version.as_ref() => {
+                    ArchiveStatus::SameVersionPresent
+                }
+                Some(_) => ArchiveStatus::DifferentVersionPresent,
+            };
+
+            let existing_file = existing.and_then(|existing| {
+                existing
+                    .path
+                    .file_name()
+                    .and_then(|s| s.to_str())
+                    .map(|s| s.to_string())
+            });
+            let existing_version = existing.map(|existing| existing.manifest.version.clone());
+
+            let policy = mod_key
+                .as_ref()
+                .and_then(|k| options.conflict_overrides.get(k))
+                .copied()
+                .unwrap_or(options.conflict_policy);
+            let action = resolve_archive_action(
+                status,
+                policy,
+                version.as_deref(),
+                existing_version.as_deref(),
+            );
+
+            // Never write over a file that isn't the archive being replaced: the
+            // existing version when keeping both, or an unrelated mod (or one
+            // without a readable manifest) that happens to have the same name
+            let occupied = self.fs.exists(&global_mods_dir.join(&pack_mod.file_name));
+            let replacing_it = action == ArchiveAction::Replace
+                && existing_file.as_deref() == Some(pack_mod.file_name.as_str());
+            let dest_file_name = if action != ArchiveAction::Skip && occupied && !replacing_it {
+                alternate_file_name(
+                    self.fs,
+                    &global_mods_dir,
+                    &pack_mod.file_name,
+                    version.as_deref(),
+                )
+            } else {
+                pack_mod.file_name.clone()
+            };
+
+            plan.archives.push(PlannedArchive {
+                file_name: pack_mod.file_name.clone(),
+                dest_path: global_mods_dir
+                    .join(&dest_file_name)
+                    .to_string_lossy()
+                    .to_string(),
+                mod_key: mod_key.clone(),
+                version,
+                status,
+                existing_file,
+                existing_version,
+                action,
+            });
+
+            if let Some(mod_key) = mod_key {
+                let action = match config.mods.get(&mod_key) {
+                    None => ConfigKeyAction::Add,
+                    Some(entry) if !entry.enabled => ConfigKeyAction::Enable,
+                    Some(_) => ConfigKeyAction::AlreadyEnabled,
+                };
+                if plan.config_entries.iter().all(|e| e.mod_key != mod_key) {
+                    plan.config_entries
+                        .push(PlannedConfigEntry { mod_key, action });
+                }
+            }
+        }
+
+        for config_file in &pack.configs {
+            let dest_path = save_mods_dir.join(&config_file.relative_path);
+            plan.config_files.push(PlannedConfigFile {
+                overwrites: self.fs.exists(&dest_path),
+                path: dest_path.to_string_lossy().to_string(),
+            });
+        }
+
+        Ok(plan)
+    }
+
+    /// Carry out a plan, making every change through the journal. Returns
+    /// warnings about the pack's orbis-pack.json.
+    pub fn install(
+        &self,
+        plan: &InstallPlan,
+        pack: &PackContents,
+        journal: &mut dyn InstallJournal,
+    ) -> Result<Vec<String>, String> {
+        let global_mods_dir = self.global_mods_dir();
+        let save_path = Path::new(&plan.save_path);
+        let save_mods_dir = save_path.join("mods");
+
+        self.fs
+            .create_dir_all(&global_mods_dir)
+            .map_err(|e| format!("Failed to create global mods directory: {}", e))?;
+        self.fs
+            .create_dir_all(&save_mods_dir)
+            .map_err(|e| format!("Failed to create save mods directory: {}", e))?;
+
+        let mut installed_archives = Vec::new();
+
+        for archive in &plan.archives {
+            let pack_mod = pack
+                .mods
+                .iter()
+                .find(|m| m.file_name == archive.file_name)
+                .ok_or_else(|| format!("{} not found in modpack", archive.file_name))?;
+
+            if archive.action == ArchiveAction::Skip {
+                continue;
+            }
+
+            journal.write_file(
+                self.fs,
+                FileScope::Global,
+                Path::new(&archive.dest_path),
+                &pack_mod.data,
+                archive.mod_key.clone(),
+            )?;
+
+            if archive.action == ArchiveAction::Replace {
+                if let Some(existing_file) = &archive.existing_file {
+                    let existing_path = global_mods_dir.join(existing_file);
+                    // Unpacked mods are usually a modder's working copy, never delete them
+                    if !self.fs.is_dir(&existing_path)
+                        && existing_path != Path::new(&archive.dest_path)
+                    {
+                        journal.remove_file(
+                            self.fs,
+                            FileScope::Global,
+                            &existing_path,
+                            archive.mod_key.clone(),
+                        )?;
+                    }
+                }
+            }
+
+            if let Some(manifest) = &pack_mod.manifest {
+                let dest_file_name = Path::new(&archive.dest_path)
+                    .file_name()
+                    .and_then(|s| s.to_str())
+                    .unwrap_or(&archive.file_name);
+                installed_archives.push(InstalledArchive {
+                    pack_file_name: archive.file_name.clone(),
+                    file_name: dest_file_name.to_string(),
+                    manifest: manifest.clone(),
+                });
+            }
+        }
+
+        for (config_file, planned) in pack.configs.iter().zip(&plan.config_files) {
+            journal.write_file(
+                self.fs,
+                FileScope::Save,
+                Path::new(&planned.path),
+                &config_file.data,
+                None,
+            )?;
+        }
+
+        if !plan.config_entries.is_empty() {
+            let mut config = SaveConfig::load(self.fs, save_path)?;
+            for entry in &plan.config_entries {
+                let previous = config.set_enabled(&entry.mod_key, true);
+                journal.record_config_entry(&entry.mod_key, previous);
+            }
+            config.save(self.fs, save_path)?;
+        }
+
+        match &pack.lock {
+            Some(lock) => apply_pack_lock_metadata(
+                self.fs,
+                &global_mods_dir,
+                lock,
+                &installed_archives,
+                journal,
+            ),
+            None => Ok(Vec::new()),
+        }
+    }
+}
@@ -0,0 +1,509 @@
+use super::fs::FileSystem;
+use super::lockfile::sha256_bytes;
+use super::manifest::discover_manifests;
+use super::mod_index::ModIndex;
+use super::modpack_installer::{
+    apply_pack_lock_metadata, ArchiveAction, ConflictPolicy, FileScope, InstallJournal,
+    InstallOptions, InstalledArchive, ModpackInstaller, PackContents,
+};
+use super::orbis_metadata::{read_orbis_metadata, write_orbis_metadata};
+use super::receipts::{
+    current_sha, revert_file, saves_enabling, InstallReceipt, ReceiptFile, RevertOutcome,
+};
+use super::save_config::SaveConfig;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Updated,
+    Unchanged,
+    /// The player edited the file since install; kept unless overwriting
+    Conflict,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ModChange {
+    pub mod_key: String,
+    pub kind: ChangeKind,
+    pub old_file: Option<String>,
+    pub new_file: Option<String>,
+    pub old_version: Option<String>,
+    pub new_version: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ConfigChange {
+    pub path: String,
+    pub kind: ChangeKind,
+    pub reason: Option<String>,
+}
+
+/// What upgrading an installed modpack to a newer version changes
+#[derive(Debug, Serialize, Clone)]
+pub struct UpgradePlan {
+    pub receipt_id: String,
+    pub from_modpack: String,
+    pub to_modpack: String,
+    pub mods: Vec<ModChange>,
+    pub configs: Vec<ConfigChange>,
+    /// False for a preview
+    pub applied: bool,
+}
+
+impl UpgradePlan {
+    /// Diff an install (by its receipt) against a newer version of the pack
+    pub fn new(fs: &dyn FileSystem, receipt: &InstallReceipt, pack: &PackContents) -> Self {
+        let save_mods_dir = Path::new(&receipt.save_path).join("mods");
+        UpgradePlan {
+            receipt_id: receipt.id.clone(),
+            from_modpack: receipt.modpack_name.clone(),
+            to_modpack: pack.name.clone(),
+            mods: plan_mods(fs, receipt, pack),
+            configs: plan_configs(fs, receipt, pack, &save_mods_dir),
+            applied: false,
+        }
+    }
+}
+
+/// Mods are matched by manifest identity, falling back to the archive name
+fn receipt_mod_key(file: &ReceiptFile) -> String {
+    file.mod_key
+        .clone()
+        .unwrap_or_else(|| file_name_of(&file.path))
+}
+
+pub fn file_name_of(path: &str) -> String {
+    Path::new(path)
+        .file_name()
+        .and_then(|s| s.to_str())
+        .unwrap_or("unknown")
+        .to_string()
+}
+
+/// Compare the archives an install wrote to UserData/Mods with a pack's mods
+pub fn plan_mods(
+    fs: &dyn FileSystem,
+    receipt: &InstallReceipt,
+    pack: &PackContents,
+) -> Vec<ModChange> {
+    let mut old: BTreeMap<String, &ReceiptFile> = BTreeMap::new();
+    for file in receipt.global_files.iter().filter(|f| !f.removed) {
+        old.insert(receipt_mod_key(file), file);
+    }
+
+    let mut new = BTreeMap::new();
+    for pack_mod in &pack.mods {
+        let key = pack_mod
+            .manifest
+            .as_ref()
+            .map(|m| m.key())
+            .unwrap_or_else(|| pack_mod.file_name.clone());
+        new.insert(key, pack_mod);
+    }
+
+    let keys: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+
+    keys.into_iter()
+        .map(|key| {
+            let old_file = old.get(key);
+            let new_mod = new.get(key);
+
+            let old_version = old_file.and_then(|f| {
+                discover_manifests(fs, Path::new(&f.path))
+                    .ok()
+                    .and_then(|found| found.into_iter().next())
+                    .map(|d| d.manifest.version)
+            });
+            let new_version = new_mod.and_then(|m| m.manifest.as_ref().map(|m| m.version.clone()));
+
+            let kind = match (old_file, new_mod) {
+                (Some(_), None) => ChangeKind::Removed,
+                (None, _) => ChangeKind::Added,
+                (Some(old_file), Some(new_mod)) => {
+                    if file_name_of(&old_file.path) == new_mod.file_name
+                        && old_file.sha256 == sha256_bytes(&new_mod.data)
+                    {
+                        ChangeKind::Unchanged
+                    } else {
+                        ChangeKind::Updated
+                    }
+                }
+            };
+
+            ModChange {
+                mod_key: key.clone(),
+                kind,
+                old_file: old_file.map(|f| file_name_of(&f.path)),
+                new_file: new_mod.map(|m| m.file_name.clone()),
+                old_version,
+                new_version,
+            }
+        })
+        .collect()
+}
+
+/// Compare the config files an install wrote to the save's mods/ dir with a
+/// pack's configs, flagging files the player edited since
+pub fn plan_configs(
+    fs: &dyn FileSystem,
+    receipt: &InstallReceipt,
+    pack: &PackContents,
+    save_mods_dir: &Path,
+) -> Vec<ConfigChange> {
+    let mut old = BTreeMap::new();
+    for file in &receipt.save_files {
+        old.insert(PathBuf::from(&file.path), file.sha256.clone());
+    }
+
+    let mut new = BTreeMap::new();
+    for config in &pack.configs {
+        new.insert(
+            save_mods_dir.join(&config.relative_path),
+            sha256_bytes(&config.data),
+        );
+    }
+
+    let paths: BTreeSet<&PathBuf> = old.keys().chain(new.keys()).collect();
+
+    paths
+        .into_iter()
+        .map(|path| {
+            let current = current_sha(fs, path);
+            let (kind, reason) = match (old.get(path), new.get(path)) {
+                (Some(old_sha), None) => match &current {
+                    Some(current) if current != old_sha => (
+                        ChangeKind::Conflict,
+                        Some("edited since install, no longer in modpack".to_string()),
+                    ),
+                    _ => (ChangeKind::Removed, None),
+                },
+                (None, Some(new_sha)) => match &current {
+                    Some(current) if current != new_sha => (
+                        ChangeKind::Conflict,
+                        Some("file already exists with different contents".to_string()),
+                    ),
+                    _ => (ChangeKind::Added, None),
+                },
+                (Some(old_sha), Some(new_sha)) => {
+                    if current.as_ref() == Some(new_sha)
+                        || (old_sha == new_sha && current.is_none())
+                    {
+                        (ChangeKind::Unchanged, None)
+                    } else if current.is_none() || current.as_ref() == Some(old_sha) {
+                        (ChangeKind::Updated, None)
+                    } else {
+                        (
+                            ChangeKind::Conflict,
+                            Some("edited since install".to_string()),
+                        )
+                    }
+                }
+                (None, None) => unreachable!(),
+            };
+
+            ConfigChange {
+                path: path.to_string_lossy().to_string(),
+                kind,
+                reason,
+            }
+        })
+        .collect()
+}
+
+/// Options for applying an upgrade
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct UpgradeOptions {
+    /// Write the new version of config files the player edited since install
+    #[serde(default)]
+    pub overwrite_conflicts: bool,
+    /// How to resolve an added or updated archive whose Group:Name is already
+    /// in UserData/Mods from somewhere other than this install
+    #[serde(default)]
+    pub conflict_policy: ConflictPolicy,
+    /// Per-mod policy by "Group:Name", overriding `conflict_policy`
+    #[serde(default)]
+    pub conflict_overrides: HashMap<String, ConflictPolicy>,
+}
+
+/// Revert an archive the old version installed, unless something else still
+/// needs it. Returns why it was kept, if it was.
+fn drop_old_archive(
+    fs: &dyn FileSystem,
+    receipt: &mut InstallReceipt,
+    path: &str,
+    mod_key: &str,
+    other_owned: &HashSet<String>,
+) -> Result<Option<String>, String> {
+    let file = match receipt.global_files.iter().find(|f| f.path == path) {
+        Some(f) => f.clone(),
+        None => return Ok(None),
+    };
+
+    receipt.forget_file(path);
+
+    if other_owned.contains(path) {
+        return Ok(Some("owned by another modpack install".to_string()));
+    }
+
+    let save_path = PathBuf::from(&receipt.save_path);
+    let hytale_root = PathBuf::from(&receipt.hytale_root);
+    let users = saves_enabling(fs, &hytale_root, &save_path, mod_key);
+    if !users.is_empty() {
+        return Ok(Some(format!("enabled in {}", users.join(", "))));
+    }
+
+    if let RevertOutcome::Kept(reason) = revert_file(fs, &file, receipt.backup_dir())? {
+        return Ok(Some(reason));
+    }
+
+    if let Some(entry) = receipt.take_metadata_entry(&file_name_of(path)) {
+        let global_mods_dir = hytale_root.join("UserData").join("Mods");
+        let mut metadata = read_orbis_metadata(fs, &global_mods_dir)?;
+        match entry.previous {
+            Some(previous) => {
+                metadata.insert(entry.file_name, previous);
+            }
+            None => {
+                metadata.remove(&entry.file_name);
+            }
+        }
+        write_orbis_metadata(fs, &global_mods_dir, &metadata)?;
+    }
+
+    Ok(None)
+}
+
+/// Carry out an upgrade plan, recording every change in the receipt so an
+/// uninstall still reverts everything
+/// - Writes added and updated archives like a modpack install: the archives
+///   this install wrote are replaced, anything else with the same Group:Name
+///   is resolved by the conflict policy, and a file name that is already
+///   taken gets a free one
+/// - Enables the new version's mods in the save
+/// - Reverts archives and config entries the new version dropped, keeping
+///   files listed in `other_owned` (other installs' receipts)
+/// - Keeps configs the player edited unless `overwrite_conflicts` is set
+///
+/// Returns notes about files that were kept and the pack's orbis-pack.json.
+pub fn apply_upgrade(
+    fs: &dyn FileSystem,
+    receipt: &mut InstallReceipt,
+    pack: &PackContents,
+    plan: &UpgradePlan,
+    other_owned: &HashSet<String>,
+    options: &UpgradeOptions,
+) -> Result<Vec<String>, String> {
+    let save_path = PathBuf::from(&receipt.save_path);
+    let installer = ModpackInstaller::new(fs, &receipt.hytale_root);
+    let global_mods_dir = installer.global_mods_dir();
+    let save_mods_dir = save_path.join("mods");
+    let mut notes = Vec::new();
+
+    // The installed archive of a mod this install put there is always replaced
+    let own_files: HashSet<String> = receipt
+        .global_files
+        .iter()
+        .filter(|f| !f.removed)
+        .map(|f| f.path.clone())
+        .collect();
+    let global_mods = ModIndex::global(fs, &global_mods_dir);
+    let mut install_options = InstallOptions {
+        conflict_policy: options.conflict_policy,
+        conflict_overrides: options.conflict_overrides.clone(),
+        ..Default::default()
+    };
+    for (key, installed) in global_mods.iter() {
+        if own_files.contains(installed.path.to_string_lossy().as_ref()) {
+            install_options
+                .conflict_overrides
+                .insert(key.clone(), ConflictPolicy::Replace);
+        }
+    }
+    let archives = installer.plan(pack, &save_path, &install_options)?.archives;
+
+    let mut config = SaveConfig::load(fs, &save_path)?;
+    let mut installed_archives = Vec::new();
+
+    for change in &plan.mods {
+        let old_path = change
+            .old_file
+            .as_ref()
+            .map(|f| global_mods_dir.join(f).to_string_lossy().to_string());
+
+        match change.kind {
+            ChangeKind::Added | ChangeKind::Updated => {
+                let new_file = change.new_file.as_ref().ok_or("Missing new file in plan")?;
+                let pack_mod = pack
+                    .mods
+                    .iter()
+                    .find(|m| &m.file_name == new_file)
+                    .ok_or_else(|| format!("{} not found in modpack", new_file))?;
+                let archive = archives
+                    .iter()
+                    .find(|a| &a.file_name == new_file)
+                    .ok_or_else(|| format!("{} not found in modpack", new_file))?;
+                let mod_key = pack_mod.manifest.as_ref().map(|m| m.key());
+
+                // Where the mod's archive is once this change is applied
+                let kept_path = if archive.action == ArchiveAction::Skip {
+                    let existing_file = archive.existing_file.as_deref().unwrap_or(new_file);
+                    notes.push(format!(
+                        "Keeping installed {} for {}",
+                        existing_file, change.mod_key
+                    ));
+                    global_mods_dir.join(existing_file)
+                } else {
+                    let dest_path = PathBuf::from(&archive.dest_path);
+                    receipt.write_file(
+                        fs,
+                        FileScope::Global,
+                        &dest_path,
+                        &pack_mod.data,
+                        mod_key.clone(),
+                    )?;
+
+                    // Replacing an archive this install didn't write; its own
+                    // old archive is dropped below
+                    if archive.action == ArchiveAction::Replace {
+                        if let Some(existing_file) = &archive.existing_file {
+                            let existing_path = global_mods_dir.join(existing_file);
+                            let existing = existing_path.to_string_lossy();
+                            if existing_path != dest_path
+                                && !own_files.contains(existing.as_ref())
+                                && !fs.is_dir(&existing_path)
+                            {
+                                receipt.remove_file(
+                                    fs,
+                                    FileScope::Global,
+                                    &existing_path,
+                                    mod_key.clone(),
+                                )?;
+                            }
+                        }
+                    }
+
+                    if let Some(manifest) = &pack_mod.manifest {
+                        installed_archives.push(InstalledArchive {
+                            pack_file_name: new_file.clone(),
+                            file_name: file_name_of(&archive.dest_path),
+                            manifest: manifest.clone(),
+                        });
+                    }
+                    dest_path
+                };
+
+                // A skipped mod is still provided by the installed archive
+                if let Some(mod_key) = mod_key {
+                    let previous = config.set_enabled(&mod_key, true);
+                    receipt.record_config_entry(&mod_key, previous);
+                }
+
+                // A renamed or skipped archive replaces the old file
+                if let Some(old_path) = old_path {
+                    if old_path != kept_path.to_string_lossy() {
+                        if let Some(reason) =
+                            drop_old_archive(fs, receipt, &old_path, &change.mod_key, other_owned)?
+                        {
+                            notes.push(format!("Keeping {} ({})", old_path, reason));
+                        }
+                    }
+                }
+            }
+            ChangeKind::Removed => {
+                if let Some(entry) = receipt.take_config_entry(&change.mod_key) {
+                    match entry.previous {
+                        Some(enabled) => {
+                            config.set_enabled(&entry.mod_key, enabled);
+                        }
+                        None => {
+                            config.mods.remove(&entry.mod_key);
+                        }
+                    }
+                }
+
+                if let Some(old_path) = old_path {
+                    if let Some(reason) =
+                        drop_old_archive(fs, receipt, &old_path, &change.mod_key, other_owned)?
+                    {
+                        notes.push(format!("Keeping {} ({})", old_path, reason));
+                    }
+                }
+            }
+            ChangeKind::Unchanged => {
+                // Same file name and contents as installed
+                let unchanged = pack
+                    .mods
+                    .iter()
+                    .find(|m| change.new_file.as_ref() == Some(&m.file_name));
+                if let Some(pack_mod) = unchanged {
+                    if let Some(manifest) = &pack_mod.manifest {
+                        installed_archives.push(InstalledArchive {
+                            pack_file_name: pack_mod.file_name.clone(),
+                            file_name: pack_mod.file_name.clone(),
+                            manifest: manifest.clone(),
+                        });
+                    }
+                }
+            }
+            ChangeKind::Conflict => {}
+        }
+    }
+
+    config.save(fs, &save_path)?;
+
+    for change in &plan.configs {
+        let path = Path::new(&change.path);
+        let in_pack = pack
+            .configs
+            .iter()
+            .find(|c| save_mods_dir.join(&c.relative_path) == path);
+
+        match (change.kind, in_pack) {
+            (ChangeKind::Added | ChangeKind::Updated, Some(config_file)) => {
+                receipt.write_file(fs, FileScope::Save, path, &config_file.data, None)?;
+            }
+            (ChangeKind::Conflict, Some(config_file)) if options.overwrite_conflicts => {
+                receipt.write_file(fs, FileScope::Save, path, &config_file.data, None)?;
+            }
+            (ChangeKind::Removed, None) => {
+                if let Some(file) = receipt
+                    .save_files
+                    .iter()
+                    .find(|f| f.path == change.path)
+                    .cloned()
+                {
+                    receipt.forget_file(&file.path);
+                    if !other_owned.contains(&file.path) {
+                        revert_file(fs, &file, receipt.backup_dir())?;
+                    }
+                }
+            }
+            (ChangeKind::Conflict, None) => {
+                // Edited file that the new version dropped: hand it over to the player
+                notes.push(format!("Keeping edited config {}", change.path));
+                receipt.forget_file(&change.path);
+            }
+            (ChangeKind::Conflict, Some(_)) => {
+                notes.push(format!("Keeping edited config {}", change.path));
+            }
+            _ => {}
+        }
+    }
+
+    if let Some(lock) = &pack.lock {
+        notes.extend(apply_pack_lock_metadata(
+            fs,
+            &global_mods_dir,
+            lock,
+            &installed_archives,
+            receipt,
+        )?);
+    }
+
+    Ok(notes)
+}
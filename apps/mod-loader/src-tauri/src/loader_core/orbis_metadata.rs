@@ -0,0 +1,58 @@
+use super::fs::FileSystem;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+pub const ORBIS_METADATA_FILE: &str = "orbis-metadata.json";
+
+/// Catalog details for an installed archive, keyed by file name in
+/// orbis-metadata.json next to the archives
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct OrbisMetadataEntry {
+    pub id: String,
+    pub slug: Option<String>,
+    pub name: String,
+    pub author: String,
+    #[serde(rename = "iconUrl")]
+    pub icon_url: Option<String>,
+    pub version: String,
+    #[serde(rename = "versionId")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version_id: Option<String>,
+    #[serde(rename = "installedAt")]
+    pub installed_at: String,
+    /// SHA-256 of the installed file, recorded where installs are verified
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+}
+
+pub type OrbisMetadata = HashMap<String, OrbisMetadataEntry>;
+
+/// Read orbis-metadata.json from a mods directory; empty if there is none
+pub fn read_orbis_metadata(fs: &dyn FileSystem, mods_dir: &Path) -> Result<OrbisMetadata, String> {
+    let metadata_path = mods_dir.join(ORBIS_METADATA_FILE);
+
+    if !fs.exists(&metadata_path) {
+        return Ok(HashMap::new());
+    }
+
+    let contents = fs
+        .read_to_string(&metadata_path)
+        .map_err(|e| format!("Failed to read orbis-metadata.json: {}", e))?;
+
+    serde_json::from_str(&contents)
+        .map_err(|e| format!("Failed to parse orbis-metadata.json: {}", e))
+}
+
+/// Write orbis-metadata.json to a mods directory
+pub fn write_orbis_metadata(
+    fs: &dyn FileSystem,
+    mods_dir: &Path,
+    metadata: &OrbisMetadata,
+) -> Result<(), String> {
+    let contents = serde_json::to_string_pretty(metadata)
+        .map_err(|e| format!("Failed to serialize orbis-metadata.json: {}", e))?;
+
+    fs.write(&mods_dir.join(ORBIS_METADATA_FILE), contents.as_bytes())
+        .map_err(|e| format!("Failed to write orbis-metadata.json: {}", e))
+}
@@ -0,0 +1,595 @@
+use super::fs::FileSystem;
+use super::lockfile::{sha256_bytes, sha256_reader};
+use super::modpack_installer::{FileScope, InstallJournal};
+use super::orbis_metadata::{read_orbis_metadata, write_orbis_metadata, OrbisMetadataEntry};
+use super::save_config::SaveConfig;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// A file written by a modpack install
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReceiptFile {
+    pub path: String,
+    pub sha256: String,
+    /// "Group:Name" of the mod archive, for files in UserData/Mods
+    #[serde(default)]
+    pub mod_key: Option<String>,
+    /// The file this one replaced, if any
+    #[serde(default)]
+    pub previous: Option<PreviousFile>,
+    /// The install deleted this file instead of writing it
+    #[serde(default)]
+    pub removed: bool,
+}
+
+/// Backup of a file overwritten by an install, relative to the receipt's backup dir
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PreviousFile {
+    pub backup: String,
+    pub sha256: String,
+}
+
+/// A config.json entry touched by an install
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReceiptConfigEntry {
+    pub mod_key: String,
+    /// Enabled state before the install, None if the key was added
+    pub previous: Option<bool>,
+}
+
+/// An orbis-metadata.json entry touched by an install
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReceiptMetadataEntry {
+    pub file_name: String,
+    pub previous: Option<OrbisMetadataEntry>,
+}
+
+/// Everything a modpack install changed, so it can be reverted
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct InstallReceipt {
+    pub id: String,
+    pub modpack_name: String,
+    pub save_path: String,
+    pub hytale_root: String,
+    /// Milliseconds since the Unix epoch
+    pub installed_at: u64,
+    #[serde(default)]
+    pub global_files: Vec<ReceiptFile>,
+    #[serde(default)]
+    pub save_files: Vec<ReceiptFile>,
+    #[serde(default)]
+    pub config_entries: Vec<ReceiptConfigEntry>,
+    #[serde(default)]
+    pub metadata_entries: Vec<ReceiptMetadataEntry>,
+    /// Content hash of the modpack zip, matching its entry in the modpack cache
+    #[serde(default)]
+    pub source_sha256: Option<String>,
+    /// Where overwritten files are backed up; set by the `ReceiptStore`
+    /// that created or read the receipt
+    #[serde(skip)]
+    backup_dir: PathBuf,
+}
+
+fn now_millis() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+impl InstallReceipt {
+    pub fn is_empty(&self) -> bool {
+        self.global_files.is_empty()
+            && self.save_files.is_empty()
+            && self.config_entries.is_empty()
+            && self.metadata_entries.is_empty()
+    }
+
+    pub fn backup_dir(&self) -> &Path {
+        &self.backup_dir
+    }
+
+    fn files_mut(&mut self, scope: FileScope) -> &mut Vec<ReceiptFile> {
+        match scope {
+            FileScope::Global => &mut self.global_files,
+            FileScope::Save => &mut self.save_files,
+        }
+    }
+
+    /// Copy a file into the receipt's backup dir before it is overwritten or deleted
+    fn back_up(&self, fs: &dyn FileSystem, path: &Path) -> Result<PreviousFile, String> {
+        fs.create_dir_all(&self.backup_dir)
+            .map_err(|e| format!("Failed to create receipt backup directory: {}", e))?;
+
+        let (backup, backup_path) = (0..)
+            .map(|n| format!("{}.bak", n))
+            .map(|name| {
+                let path = self.backup_dir.join(&name);
+                (name, path)
+            })
+            .find(|(_, path)| !fs.exists(path))
+            .ok_or("No free backup slot")?;
+
+        let data = fs
+            .read(path)
+            .map_err(|e| format!("Failed to back up {:?}: {}", path, e))?;
+        fs.write(&backup_path, &data)
+            .map_err(|e| format!("Failed to back up {:?}: {}", path, e))?;
+
+        Ok(PreviousFile {
+            backup,
+            sha256: sha256_bytes(&data),
+        })
+    }
+
+    /// Stop tracking a file (after it was reverted or handed back to the user)
+    pub fn forget_file(&mut self, path: &str) {
+        self.global_files.retain(|f| f.path != path);
+        self.save_files.retain(|f| f.path != path);
+    }
+
+    pub fn take_config_entry(&mut self, mod_key: &str) -> Option<ReceiptConfigEntry> {
+        let position = self
+            .config_entries
+            .iter()
+            .position(|e| e.mod_key == mod_key)?;
+        Some(self.config_entries.remove(position))
+    }
+
+    pub fn take_metadata_entry(&mut self, file_name: &str) -> Option<ReceiptMetadataEntry> {
+        let position = self
+            .metadata_entries
+            .iter()
+            .position(|e| e.file_name == file_name)?;
+        Some(self.metadata_entries.remove(position))
+    }
+}
+
+/// Installs write through the receipt, so every file they touch is recorded
+/// and backed up before it is overwritten or deleted
+impl InstallJournal for InstallReceipt {
+    fn write_file(
+        &mut self,
+        fs: &dyn FileSystem,
+        scope: FileScope,
+        path: &Path,
+        data: &[u8],
+        mod_key: Option<String>,
+    ) -> Result<(), String> {
+        let path_str = path.to_string_lossy().to_string();
+        let sha256 = sha256_bytes(data);
+
+        let already_tracked = self
+            .global_files
+            .iter()
+            .chain(self.save_files.iter())
+            .any(|f| f.path == path_str);
+
+        let previous = if !already_tracked && fs.is_file(path) {
+            Some(self.back_up(fs, path)?)
+        } else {
+            None
+        };
+
+        if let Some(parent) = path.parent() {
+            fs.create_dir_all(parent)
+                .map_err(|e| format!("Failed to create directory: {}", e))?;
+        }
+        fs.write(path, data)
+            .map_err(|e| format!("Failed to write file {:?}: {}", path, e))?;
+
+        let files = self.files_mut(scope);
+        match files.iter_mut().find(|f| f.path == path_str) {
+            Some(existing) => {
+                existing.sha256 = sha256;
+                existing.mod_key = mod_key.or(existing.mod_key.take());
+                existing.removed = false;
+            }
+            None => files.push(ReceiptFile {
+                path: path_str,
+                sha256,
+                mod_key,
+                previous,
+                removed: false,
+            }),
+        }
+
+        Ok(())
+    }
+
+    /// Delete `path`, keeping a backup so uninstall can put it back
+    fn remove_file(
+        &mut self,
+        fs: &dyn FileSystem,
+        scope: FileScope,
+        path: &Path,
+        mod_key: Option<String>,
+    ) -> Result<(), String> {
+        if !fs.is_file(path) {
+            return Ok(());
+        }
+
+        let previous = self.back_up(fs, path)?;
+        fs.remove_file(path)
+            .map_err(|e| format!("Failed to remove {:?}: {}", path, e))?;
+
+        self.files_mut(scope).push(ReceiptFile {
+            path: path.to_string_lossy().to_string(),
+            sha256: String::new(),
+            mod_key,
+            previous: Some(previous),
+            removed: true,
+        });
+
+        Ok(())
+    }
+
+    fn record_config_entry(&mut self, mod_key: &str, previous: Option<bool>) {
+        if self.config_entries.iter().all(|e| e.mod_key != mod_key) {
+            self.config_entries.push(ReceiptConfigEntry {
+                mod_key: mod_key.to_string(),
+                previous,
+            });
+        }
+    }
+
+    fn record_metadata_entry(&mut self, file_name: &str, previous: Option<OrbisMetadataEntry>) {
+        if self
+            .metadata_entries
+            .iter()
+            .all(|e| e.file_name != file_name)
+        {
+            self.metadata_entries.push(ReceiptMetadataEntry {
+                file_name: file_name.to_string(),
+                previous,
+            });
+        }
+    }
+}
+
+/// A file uninstall left in place, and why
+#[derive(Debug, Serialize, Clone)]
+pub struct KeptFile {
+    pub path: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct UninstallSummary {
+    pub removed_files: Vec<String>,
+    pub restored_files: Vec<String>,
+    pub kept_files: Vec<KeptFile>,
+    pub reverted_config_keys: Vec<String>,
+}
+
+/// What happened to a file when reverting it
+pub enum RevertOutcome {
+    Removed,
+    Restored,
+    Kept(String),
+}
+
+impl UninstallSummary {
+    fn record(&mut self, path: &str, outcome: RevertOutcome) {
+        match outcome {
+            RevertOutcome::Removed => self.removed_files.push(path.to_string()),
+            RevertOutcome::Restored => self.restored_files.push(path.to_string()),
+            RevertOutcome::Kept(reason) => self.kept_files.push(KeptFile {
+                path: path.to_string(),
+                reason,
+            }),
+        }
+    }
+
+    fn keep(&mut self, path: &str, reason: String) {
+        self.record(path, RevertOutcome::Kept(reason));
+    }
+}
+
+/// SHA-256 of a file, None if it cannot be read
+pub fn current_sha(fs: &dyn FileSystem, path: &Path) -> Option<String> {
+    let mut file = fs.open(path).ok()?;
+    sha256_reader(&mut file).ok()
+}
+
+/// Saves other than `exclude` that have `mod_key` enabled
+pub fn saves_enabling(
+    fs: &dyn FileSystem,
+    hytale_root: &Path,
+    exclude: &Path,
+    mod_key: &str,
+) -> Vec<String> {
+    let saves_dir = hytale_root.join("UserData").join("Saves");
+    let entries = match fs.read_dir(&saves_dir) {
+        Ok(e) => e,
+        Err(_) => return Vec::new(),
+    };
+
+    entries
+        .into_iter()
+        .filter(|path| fs.is_dir(path) && path != exclude)
+        .filter(|path| {
+            SaveConfig::load(fs, path)
+                .map(|c| c.is_enabled(mod_key))
+                .unwrap_or(false)
+        })
+        .map(|path| path.to_string_lossy().to_string())
+        .collect()
+}
+
+/// Put back (or remove) one file the install wrote, unless someone changed it since
+pub fn revert_file(
+    fs: &dyn FileSystem,
+    file: &ReceiptFile,
+    backup_dir: &Path,
+) -> Result<RevertOutcome, String> {
+    let path = Path::new(&file.path);
+    let restore = |previous: &PreviousFile| -> Result<(), String> {
+        let data = fs
+            .read(&backup_dir.join(&previous.backup))
+            .map_err(|e| format!("Failed to restore {:?}: {}", path, e))?;
+        fs.write(path, &data)
+            .map_err(|e| format!("Failed to restore {:?}: {}", path, e))
+    };
+
+    if file.removed {
+        if fs.exists(path) {
+            return Ok(RevertOutcome::Kept("recreated since install".to_string()));
+        }
+        if let Some(previous) = &file.previous {
+            restore(previous)?;
+        }
+        return Ok(RevertOutcome::Restored);
+    }
+
+    if let Some(sha) = current_sha(fs, path) {
+        if sha != file.sha256 {
+            return Ok(RevertOutcome::Kept("modified since install".to_string()));
+        }
+    }
+
+    match &file.previous {
+        Some(previous) => {
+            restore(previous)?;
+            Ok(RevertOutcome::Restored)
+        }
+        None => {
+            if fs.exists(path) {
+                fs.remove_file(path)
+                    .map_err(|e| format!("Failed to remove {:?}: {}", path, e))?;
+            }
+            Ok(RevertOutcome::Removed)
+        }
+    }
+}
+
+/// The install receipts in one directory: `<id>.json` for each receipt and
+/// `<id>/` for the files it backed up
+pub struct ReceiptStore<'a> {
+    fs: &'a dyn FileSystem,
+    dir: PathBuf,
+}
+
+impl<'a> ReceiptStore<'a> {
+    pub fn new(fs: &'a dyn FileSystem, dir: impl Into<PathBuf>) -> Self {
+        ReceiptStore {
+            fs,
+            dir: dir.into(),
+        }
+    }
+
+    /// An empty receipt for a new install, not saved yet
+    pub fn create(
+        &self,
+        modpack_name: &str,
+        save_path: &Path,
+        hytale_root: &Path,
+    ) -> InstallReceipt {
+        let installed_at = now_millis();
+        let slug: String = modpack_name
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+            .collect();
+        let base = format!("{}-{}", installed_at, slug);
+
+        // Two installs of the same pack within a millisecond must not share a receipt
+        let id = (1..)
+            .map(|n| match n {
+                1 => base.clone(),
+                n => format!("{}-{}", base, n),
+            })
+            .find(|id| {
+                !self.fs.exists(&self.dir.join(format!("{}.json", id)))
+                    && !self.fs.exists(&self.dir.join(id))
+            })
+            .unwrap_or(base);
+
+        InstallReceipt {
+            backup_dir: self.dir.join(&id),
+            id,
+            modpack_name: modpack_name.to_string(),
+            save_path: save_path.to_string_lossy().to_string(),
+            hytale_root: hytale_root.to_string_lossy().to_string(),
+            installed_at,
+            global_files: Vec::new(),
+            save_files: Vec::new(),
+            config_entries: Vec::new(),
+            metadata_entries: Vec::new(),
+            source_sha256: None,
+        }
+    }
+
+    /// Every readable receipt, oldest first, and a warning for each unreadable one
+    pub fn read_all(&self) -> Result<(Vec<InstallReceipt>, Vec<String>), String> {
+        let entries = self
+            .fs
+            .read_dir(&self.dir)
+            .map_err(|e| format!("Failed to read receipts directory: {}", e))?;
+
+        let mut receipts = Vec::new();
+        let mut warnings = Vec::new();
+        for path in entries {
+            if path.extension().and_then(|s| s.to_str()) != Some("json") {
+                continue;
+            }
+
+            match self
+                .fs
+                .read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|c| serde_json::from_str::<InstallReceipt>(&c).map_err(|e| e.to_string()))
+            {
+                Ok(mut receipt) => {
+                    receipt.backup_dir = self.dir.join(&receipt.id);
+                    receipts.push(receipt);
+                }
+                Err(e) => {
+                    warnings.push(format!("Failed to read install receipt {:?}: {}", path, e))
+                }
+            }
+        }
+
+        receipts.sort_by_key(|r| r.installed_at);
+        Ok((receipts, warnings))
+    }
+
+    pub fn read(&self, receipt_id: &str) -> Result<InstallReceipt, String> {
+        self.read_all()?
+            .0
+            .into_iter()
+            .find(|r| r.id == receipt_id)
+            .ok_or_else(|| format!("Install receipt '{}' not found", receipt_id))
+    }
+
+    pub fn save(&self, receipt: &InstallReceipt) -> Result<(), String> {
+        let contents = serde_json::to_string_pretty(receipt)
+            .map_err(|e| format!("Failed to serialize install receipt: {}", e))?;
+        self.fs
+            .create_dir_all(&self.dir)
+            .and_then(|()| {
+                self.fs.write(
+                    &self.dir.join(format!("{}.json", receipt.id)),
+                    contents.as_bytes(),
+                )
+            })
+            .map_err(|e| format!("Failed to write install receipt: {}", e))
+    }
+
+    fn delete(&self, receipt: &InstallReceipt) -> Result<(), String> {
+        if self.fs.exists(&receipt.backup_dir) {
+            self.fs
+                .remove_dir_all(&receipt.backup_dir)
+                .map_err(|e| format!("Failed to remove receipt backups: {}", e))?;
+        }
+        self.fs
+            .remove_file(&self.dir.join(format!("{}.json", receipt.id)))
+            .map_err(|e| format!("Failed to remove install receipt: {}", e))
+    }
+
+    /// Files recorded by every install receipt except `receipt_id`
+    pub fn paths_owned_by_others(&self, receipt_id: &str) -> Result<HashSet<String>, String> {
+        Ok(self
+            .read_all()?
+            .0
+            .into_iter()
+            .filter(|r| r.id != receipt_id)
+            .flat_map(|r| r.global_files.into_iter().chain(r.save_files))
+            .filter(|f| !f.removed)
+            .map(|f| f.path)
+            .collect())
+    }
+
+    /// Revert everything an install recorded in its receipt, then delete it
+    /// - Restores or removes config files it wrote in the save's mods/ dir
+    /// - Restores or removes archives in UserData/Mods unless another save
+    ///   enables them or another install receipt also owns them
+    /// - Puts config.json and orbis-metadata.json entries back to their previous values
+    pub fn uninstall(&self, receipt: &InstallReceipt) -> Result<UninstallSummary, String> {
+        let fs = self.fs;
+        let save_path = PathBuf::from(&receipt.save_path);
+        let hytale_root = PathBuf::from(&receipt.hytale_root);
+        let global_mods_dir = hytale_root.join("UserData").join("Mods");
+
+        let mut summary = UninstallSummary::default();
+
+        // Paths owned by other installs must survive this one
+        let other_owned = self.paths_owned_by_others(&receipt.id)?;
+        let owned_by_another = "owned by another modpack install".to_string();
+
+        for file in &receipt.save_files {
+            if other_owned.contains(&file.path) {
+                summary.keep(&file.path, owned_by_another.clone());
+                continue;
+            }
+            let outcome = revert_file(fs, file, &receipt.backup_dir)?;
+            summary.record(&file.path, outcome);
+        }
+
+        let mut reverted_global = HashSet::new();
+        for file in &receipt.global_files {
+            if other_owned.contains(&file.path) {
+                summary.keep(&file.path, owned_by_another.clone());
+                continue;
+            }
+
+            if let Some(mod_key) = &file.mod_key {
+                let users = saves_enabling(fs, &hytale_root, &save_path, mod_key);
+                if !users.is_empty() {
+                    summary.keep(&file.path, format!("enabled in {}", users.join(", ")));
+                    continue;
+                }
+            }
+
+            let outcome = revert_file(fs, file, &receipt.backup_dir)?;
+            if !matches!(outcome, RevertOutcome::Kept(_)) {
+                reverted_global.insert(file.path.clone());
+            }
+            summary.record(&file.path, outcome);
+        }
+
+        if !receipt.config_entries.is_empty() {
+            let mut config = SaveConfig::load(fs, &save_path)?;
+            for entry in &receipt.config_entries {
+                match entry.previous {
+                    Some(enabled) => {
+                        config.set_enabled(&entry.mod_key, enabled);
+                    }
+                    None => {
+                        config.mods.remove(&entry.mod_key);
+                    }
+                }
+                summary.reverted_config_keys.push(entry.mod_key.clone());
+            }
+            config.save(fs, &save_path)?;
+        }
+
+        // Only touch metadata for archives that were actually reverted
+        let metadata_to_revert: Vec<&ReceiptMetadataEntry> = receipt
+            .metadata_entries
+            .iter()
+            .filter(|e| {
+                let path = global_mods_dir.join(&e.file_name);
+                reverted_global.contains(path.to_string_lossy().as_ref())
+            })
+            .collect();
+
+        if !metadata_to_revert.is_empty() {
+            let mut metadata = read_orbis_metadata(fs, &global_mods_dir).unwrap_or_default();
+            for entry in metadata_to_revert {
+                match &entry.previous {
+                    Some(previous) => {
+                        metadata.insert(entry.file_name.clone(), previous.clone());
+                    }
+                    None => {
+                        metadata.remove(&entry.file_name);
+                    }
+                }
+            }
+            write_orbis_metadata(fs, &global_mods_dir, &metadata)?;
+        }
+
+        self.delete(receipt)?;
+        Ok(summary)
+    }
+}
@@ -0,0 +1,547 @@
+use super::fs::FileSystem;
+use super::manifest::{
+    discover_manifests, entry_names, has_dir, has_manifest_candidate, DiscoveredManifest,
+    ModManifest, ResourceType, MANIFEST_FILE,
+};
+use super::mod_index::ModIndex;
+use super::modpack_installer::{
+    alternate_file_name, resolve_archive_action, ArchiveAction, ArchiveStatus, ConflictPolicy,
+};
+use super::orbis_metadata::{
+    read_orbis_metadata, write_orbis_metadata, OrbisMetadata, OrbisMetadataEntry,
+};
+use super::save_config::SaveConfig;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use zip::ZipArchive;
+
+/// Prefab structures and the schematics Orbis serves for `PREFAB` resources
+const PREFAB_EXTENSIONS: &[&str] = &[".prefab.json", ".schematic"];
+
+/// Group given to the manifest generated for asset and data packs that ship without one
+const GENERATED_MANIFEST_GROUP: &str = "Orbis";
+
+pub fn is_prefab_file(name: &str) -> bool {
+    let name = name.to_lowercase();
+    PREFAB_EXTENSIONS.iter().any(|ext| name.ends_with(ext))
+}
+
+/// Classify content without a manifest from its entries alone
+fn classify_entries(names: &[String]) -> Option<ResourceType> {
+    if has_dir(names, "universe/") {
+        Some(ResourceType::World)
+    } else if has_dir(names, "Common/") {
+        Some(ResourceType::AssetPack)
+    } else if has_dir(names, "Server/") {
+        Some(ResourceType::DataPack)
+    } else if names.iter().any(|name| is_prefab_file(name)) {
+        Some(ResourceType::Prefab)
+    } else {
+        None
+    }
+}
+
+/// The single top-level folder every entry is under, if any (e.g. "MyPack/")
+fn wrapper_folder(names: &[String]) -> Option<String> {
+    let first = names.first()?;
+    let folder = format!("{}/", first.split('/').next()?);
+
+    let wrapped = names.iter().all(|name| name.starts_with(&folder));
+    (wrapped && names.iter().any(|name| name.len() > folder.len())).then_some(folder)
+}
+
+pub fn strip_folder(names: &[String], folder: Option<&str>) -> Vec<String> {
+    names
+        .iter()
+        .filter_map(|name| match folder {
+            Some(folder) => name.strip_prefix(folder),
+            None => Some(name.as_str()),
+        })
+        .filter(|name| !name.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// What an archive or directory contains
+#[derive(Debug, Serialize, Clone)]
+pub struct ResourceInspection {
+    pub resource_type: ResourceType,
+    /// Empty for content without a manifest
+    pub manifests: Vec<DiscoveredManifest>,
+    /// Top-level folder manifest-less content is wrapped in, dropped when installing
+    pub wrapper_folder: Option<String>,
+}
+
+/// Classify an archive or directory by its manifest and entries
+pub fn inspect(fs: &dyn FileSystem, path: &Path) -> Result<ResourceInspection, String> {
+    let names = entry_names(fs, path)?;
+
+    // A manifest that exists but can't be read is an error, not a hint to guess
+    if has_manifest_candidate(&names) {
+        let manifests = discover_manifests(fs, path)?;
+        let resource_type = manifests
+            .first()
+            .map(|m| m.resource_type)
+            .unwrap_or_default();
+
+        return Ok(ResourceInspection {
+            resource_type,
+            manifests,
+            wrapper_folder: None,
+        });
+    }
+
+    // Content zipped together with its folder is classified from inside that folder
+    let (wrapper, resource_type) = match classify_entries(&names) {
+        Some(resource_type) => (None, Some(resource_type)),
+        None => {
+            let wrapper = wrapper_folder(&names);
+            let resource_type = classify_entries(&strip_folder(&names, wrapper.as_deref()));
+            (wrapper, resource_type)
+        }
+    };
+    let resource_type = resource_type.ok_or_else(|| {
+        format!(
+            "{:?} has no manifest.json, and no assets, server data, prefabs or world to install",
+            path
+        )
+    })?;
+
+    Ok(ResourceInspection {
+        resource_type,
+        manifests: Vec::new(),
+        wrapper_folder: wrapper,
+    })
+}
+
+/// Extract an archive into `dest`, dropping `wrapper` from every path and
+/// skipping entries that would escape `dest` or that `keep` rejects (it gets
+/// the path relative to `dest`). Returns the files written.
+pub fn extract_archive(
+    fs: &dyn FileSystem,
+    archive_path: &Path,
+    dest: &Path,
+    wrapper: Option<&str>,
+    keep: impl Fn(&Path) -> bool,
+) -> Result<Vec<PathBuf>, String> {
+    let file = fs
+        .open(archive_path)
+        .map_err(|e| format!("Failed to open archive file: {}", e))?;
+    let mut archive =
+        ZipArchive::new(file).map_err(|e| format!("Failed to read archive: {}", e))?;
+
+    let mut written = Vec::new();
+    for i in 0..archive.len() {
+        let mut entry = archive
+            .by_index(i)
+            .map_err(|e| format!("Failed to read entry at index {}: {}", i, e))?;
+
+        if entry.is_dir() {
+            continue;
+        }
+
+        let enclosed = match entry.enclosed_name() {
+            Some(p) => p.to_path_buf(),
+            None => continue,
+        };
+        let relative = match wrapper {
+            Some(folder) => match enclosed.strip_prefix(folder.trim_end_matches('/')) {
+                Ok(p) => p.to_path_buf(),
+                Err(_) => continue,
+            },
+            None => enclosed,
+        };
+        if !keep(&relative) {
+            continue;
+        }
+
+        let outpath = dest.join(&relative);
+        if let Some(parent) = outpath.parent() {
+            fs.create_dir_all(parent)
+                .map_err(|e| format!("Failed to create directory {:?}: {}", parent, e))?;
+        }
+
+        fs.write_from(&outpath, &mut entry)
+            .map_err(|e| format!("Failed to extract {:?}: {}", relative, e))?;
+        written.push(outpath);
+    }
+
+    Ok(written)
+}
+
+/// Manifest for an asset or data pack that shipped without one, so the game
+/// and the loader can address it as "Orbis:<name>"
+fn generated_manifest(
+    name: &str,
+    version: Option<&str>,
+    resource_type: ResourceType,
+) -> ModManifest {
+    ModManifest {
+        group: GENERATED_MANIFEST_GROUP.to_string(),
+        name: name.to_string(),
+        version: version.unwrap_or("1.0.0").to_string(),
+        description: "Generated by the Orbis mod loader; the pack shipped without a manifest"
+            .to_string(),
+        authors: Vec::new(),
+        website: None,
+        server_version: String::new(),
+        dependencies: Default::default(),
+        optional_dependencies: Default::default(),
+        disabled_by_default: false,
+        main: String::new(),
+        includes_asset_pack: resource_type == ResourceType::AssetPack,
+    }
+}
+
+/// Directory (and manifest) name for a pack without a manifest: its Orbis
+/// slug or name when the download was recorded, otherwise the archive's stem.
+/// Stems usually carry the version, which would give every update a new key.
+fn generated_pack_name(orbis_entry: Option<&OrbisMetadataEntry>, stem: &str) -> String {
+    let resource_name = orbis_entry
+        .and_then(|e| e.slug.as_deref().or(Some(e.name.as_str())))
+        .map(|name| {
+            name.trim()
+                .chars()
+                .map(|c| {
+                    if c.is_alphanumeric() || c == '-' || c == '_' {
+                        c
+                    } else {
+                        '-'
+                    }
+                })
+                .collect::<String>()
+        })
+        .filter(|name| !name.trim_matches('-').is_empty());
+
+    resource_name.unwrap_or_else(|| stem.to_string())
+}
+
+/// The first mod manifest of an archive or unpacked mod
+fn first_manifest(fs: &dyn FileSystem, path: &Path) -> Option<ModManifest> {
+    discover_manifests(fs, path)
+        .ok()
+        .and_then(|found| found.into_iter().next())
+        .map(|d| d.manifest)
+}
+
+/// Whether a directory in UserData/Mods was unpacked by `unpack_pack`
+fn is_generated_pack(fs: &dyn FileSystem, path: &Path) -> bool {
+    fs.is_dir(path) && first_manifest(fs, path).is_some_and(|m| m.group == GENERATED_MANIFEST_GROUP)
+}
+
+/// Mods or packs a resource install put in UserData/Mods
+#[derive(Debug, Clone)]
+pub struct InstalledMods {
+    /// Archive or directory the content was installed as
+    pub destination: PathBuf,
+    /// "Group:Name" of each mod or pack installed
+    pub mod_keys: Vec<String>,
+    /// Saves whose config.json was changed to point at the new version
+    pub updated_saves: Vec<PathBuf>,
+}
+
+/// Installs single Orbis resources (plugins, mods, asset and data packs) into
+/// UserData/Mods of one Hytale data directory
+pub struct ResourceInstaller<'a> {
+    fs: &'a dyn FileSystem,
+    hytale_root: PathBuf,
+}
+
+impl<'a> ResourceInstaller<'a> {
+    pub fn new(fs: &'a dyn FileSystem, hytale_root: impl Into<PathBuf>) -> Self {
+        ResourceInstaller {
+            fs,
+            hytale_root: hytale_root.into(),
+        }
+    }
+
+    pub fn global_mods_dir(&self) -> PathBuf {
+        self.hytale_root.join("UserData").join("Mods")
+    }
+
+    /// Install a plugin, mod or pack archive:
+    /// - with a manifest, the archive is copied into UserData/Mods; a mod
+    ///   already there under the same Group:Name is resolved by `policy`, and
+    ///   a file name taken by something else gets a free one
+    /// - without one, it is unpacked into UserData/Mods/<name>/ with a
+    ///   generated manifest, named after the Orbis resource when the download
+    ///   was recorded; reinstalls and updates replace it. An archive that was
+    ///   downloaded straight into UserData/Mods is removed, and its Orbis
+    ///   metadata moves to the unpacked directory.
+    pub fn install_mods(
+        &self,
+        archive: &Path,
+        inspection: &ResourceInspection,
+        policy: ConflictPolicy,
+    ) -> Result<InstalledMods, String> {
+        match inspection.resource_type {
+            ResourceType::Plugin
+            | ResourceType::Mod
+            | ResourceType::AssetPack
+            | ResourceType::DataPack => {}
+            other => return Err(format!("{:?} is a {:?}, not a mod or pack", archive, other)),
+        }
+
+        self.fs
+            .create_dir_all(&self.global_mods_dir())
+            .map_err(|e| format!("Failed to create mods directory: {}", e))?;
+
+        if inspection.manifests.is_empty() {
+            self.unpack_pack(archive, inspection)
+        } else {
+            self.copy_archive(archive, inspection, policy)
+        }
+    }
+
+    fn copy_archive(
+        &self,
+        archive: &Path,
+        inspection: &ResourceInspection,
+        policy: ConflictPolicy,
+    ) -> Result<InstalledMods, String> {
+        let global_mods_dir = self.global_mods_dir();
+        let file_name = archive
+            .file_name()
+            .and_then(|s| s.to_str())
+            .ok_or("Invalid archive file name")?;
+        let mod_keys: Vec<String> = inspection
+            .manifests
+            .iter()
+            .map(|m| m.manifest.key())
+            .collect();
+
+        // Already in place, e.g. downloaded straight into UserData/Mods
+        if archive.parent() == Some(global_mods_dir.as_path()) {
+            return Ok(InstalledMods {
+                destination: archive.to_path_buf(),
+                mod_keys,
+                updated_saves: Vec::new(),
+            });
+        }
+
+        // The first of its mods that is already installed decides, like a
+        // modpack archive
+        let index = ModIndex::global(self.fs, &global_mods_dir);
+        let version = inspection.manifests[0].manifest.version.clone();
+        let existing = inspection
+            .manifests
+            .iter()
+            .find_map(|m| index.get(&m.manifest.key()).map(|e| (&m.manifest, e)));
+
+        let action = match existing {
+            None => ArchiveAction::Write,
+            Some((manifest, existing)) => {
+                let status = if manifest.version == existing.manifest.version {
+                    ArchiveStatus::SameVersionPresent
+                } else {
+                    ArchiveStatus::DifferentVersionPresent
+                };
+                resolve_archive_action(
+                    status,
+                    policy,
+                    Some(&manifest.version),
+                    Some(&existing.manifest.version),
+                )
+            }
+        };
+        let existing_path = existing.map(|(_, e)| e.path.clone());
+
+        if let (ArchiveAction::Skip, Some(existing_path)) = (action, &existing_path) {
+            return Ok(InstalledMods {
+                destination: existing_path.clone(),
+                mod_keys,
+                updated_saves: Vec::new(),
+            });
+        }
+
+        // Never write over a file that isn't the archive being replaced
+        let dest = global_mods_dir.join(file_name);
+        let replacing_it =
+            action == ArchiveAction::Replace && existing_path.as_ref() == Some(&dest);
+        let dest = if self.fs.exists(&dest) && !replacing_it {
+            global_mods_dir.join(alternate_file_name(
+                self.fs,
+                &global_mods_dir,
+                file_name,
+                Some(&version),
+            ))
+        } else {
+            dest
+        };
+
+        let mut source = self
+            .fs
+            .open(archive)
+            .map_err(|e| format!("Failed to copy archive to mods: {}", e))?;
+        self.fs
+            .write_from(&dest, &mut source)
+            .map_err(|e| format!("Failed to copy archive to mods: {}", e))?;
+
+        if action == ArchiveAction::Replace {
+            if let Some(existing_path) = existing_path {
+                if existing_path != dest && self.fs.is_file(&existing_path) {
+                    self.fs
+                        .remove_file(&existing_path)
+                        .map_err(|e| format!("Failed to remove replaced archive: {}", e))?;
+                }
+            }
+        }
+
+        Ok(InstalledMods {
+            destination: dest,
+            mod_keys,
+            updated_saves: Vec::new(),
+        })
+    }
+
+    fn unpack_pack(
+        &self,
+        archive: &Path,
+        inspection: &ResourceInspection,
+    ) -> Result<InstalledMods, String> {
+        let global_mods_dir = self.global_mods_dir();
+        let file_name = archive
+            .file_name()
+            .and_then(|s| s.to_str())
+            .ok_or("Invalid archive file name")?;
+        let stem = archive
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .ok_or("Invalid archive file name")?;
+
+        // Metadata the frontend recorded for the download names the pack
+        // after its Orbis resource, so updates land in the same directory
+        let mut metadata = read_orbis_metadata(self.fs, &global_mods_dir)?;
+        let orbis_entry = metadata.get(file_name).cloned();
+        let pack_name = generated_pack_name(orbis_entry.as_ref(), stem);
+
+        let dest = global_mods_dir.join(&pack_name);
+        if self.fs.exists(&dest) {
+            if !is_generated_pack(self.fs, &dest) {
+                return Err(format!("{:?} already exists in UserData/Mods", pack_name));
+            }
+            self.fs
+                .remove_dir_all(&dest)
+                .map_err(|e| format!("Failed to remove {:?}: {}", dest, e))?;
+        }
+
+        extract_archive(
+            self.fs,
+            archive,
+            &dest,
+            inspection.wrapper_folder.as_deref(),
+            |_| true,
+        )?;
+
+        let version = orbis_entry.as_ref().map(|e| e.version.as_str());
+        let manifest = generated_manifest(&pack_name, version, inspection.resource_type);
+        let contents = serde_json::to_string_pretty(&manifest)
+            .map_err(|e| format!("Failed to serialize manifest.json: {}", e))?;
+        self.fs
+            .write(&dest.join(MANIFEST_FILE), contents.as_bytes())
+            .map_err(|e| format!("Failed to write manifest.json: {}", e))?;
+        let mod_key = manifest.key();
+
+        let updated_saves = match &orbis_entry {
+            Some(entry) => self.replace_older_generated_packs(
+                &mut metadata,
+                &entry.id,
+                &pack_name,
+                file_name,
+                &mod_key,
+            )?,
+            None => Vec::new(),
+        };
+
+        if archive.parent() == Some(global_mods_dir.as_path()) {
+            self.fs
+                .remove_file(archive)
+                .map_err(|e| format!("Failed to remove {:?}: {}", archive, e))?;
+
+            if let Some(entry) = metadata.remove(file_name) {
+                metadata.insert(pack_name.clone(), entry);
+            }
+        }
+        write_orbis_metadata(self.fs, &global_mods_dir, &metadata)?;
+
+        Ok(InstalledMods {
+            destination: dest,
+            mod_keys: vec![mod_key],
+            updated_saves,
+        })
+    }
+
+    /// Remove packs generated for the same Orbis resource under another name
+    /// (e.g. named after an older archive), moving their config.json entries in
+    /// every save over to `mod_key` so the old version doesn't stay enabled.
+    /// Returns the saves whose config.json changed.
+    fn replace_older_generated_packs(
+        &self,
+        metadata: &mut OrbisMetadata,
+        resource_id: &str,
+        pack_name: &str,
+        file_name: &str,
+        mod_key: &str,
+    ) -> Result<Vec<PathBuf>, String> {
+        let global_mods_dir = self.global_mods_dir();
+        let older: Vec<String> = metadata
+            .iter()
+            .filter(|(name, entry)| {
+                entry.id == resource_id
+                    && name.as_str() != pack_name
+                    && name.as_str() != file_name
+                    && is_generated_pack(self.fs, &global_mods_dir.join(name))
+            })
+            .map(|(name, _)| name.clone())
+            .collect();
+        if older.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let saves_dir = self.hytale_root.join("UserData").join("Saves");
+        let saves: Vec<PathBuf> = self
+            .fs
+            .read_dir(&saves_dir)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|path| self.fs.is_dir(path))
+            .collect();
+
+        let mut updated_saves = Vec::new();
+        for name in older {
+            let path = global_mods_dir.join(&name);
+            let old_key = first_manifest(self.fs, &path).map(|m| m.key());
+
+            self.fs
+                .remove_dir_all(&path)
+                .map_err(|e| format!("Failed to remove {:?}: {}", path, e))?;
+            metadata.remove(&name);
+
+            let old_key = match old_key {
+                Some(key) => key,
+                None => continue,
+            };
+            for save_path in &saves {
+                let mut config = SaveConfig::load(self.fs, save_path)?;
+                if let Some(entry) = config.mods.remove(&old_key) {
+                    config.mods.entry(mod_key.to_string()).or_insert(entry);
+                    config.save(self.fs, save_path)?;
+                    if !updated_saves.contains(save_path) {
+                        updated_saves.push(save_path.clone());
+                    }
+                }
+            }
+        }
+
+        Ok(updated_saves)
+    }
+
+    /// Enable each "Group:Name" in a save's config.json
+    pub fn enable_in_save(&self, save_path: &Path, keys: &[String]) -> Result<(), String> {
+        let mut config = SaveConfig::load(self.fs, save_path)?;
+        for key in keys {
+            config.set_enabled(key, true);
+        }
+        config.save(self.fs, save_path)
+    }
+}
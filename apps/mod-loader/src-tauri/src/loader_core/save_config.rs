@@ -0,0 +1,72 @@
+use super::fs::FileSystem;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+pub const SAVE_CONFIG_FILE: &str = "config.json";
+
+/// A save's config.json: which mods it enables, by "Group:Name"
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct SaveConfig {
+    #[serde(rename = "Mods", default)]
+    pub mods: HashMap<String, ModConfigEntry>,
+    /// The rest of the save's config.json, kept as-is when writing it back
+    #[serde(flatten)]
+    pub other: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ModConfigEntry {
+    #[serde(rename = "Enabled")]
+    pub enabled: bool,
+}
+
+impl SaveConfig {
+    /// Read config.json from a save directory; empty if the save has none yet
+    pub fn load(fs: &dyn FileSystem, save_path: &Path) -> Result<Self, String> {
+        let config_path = save_path.join(SAVE_CONFIG_FILE);
+
+        if !fs.exists(&config_path) {
+            return Ok(SaveConfig::default());
+        }
+
+        let contents = fs
+            .read_to_string(&config_path)
+            .map_err(|e| format!("Failed to read config.json: {}", e))?;
+
+        serde_json::from_str(&contents).map_err(|e| format!("Failed to parse config.json: {}", e))
+    }
+
+    /// Write config.json to a save directory
+    pub fn save(&self, fs: &dyn FileSystem, save_path: &Path) -> Result<(), String> {
+        let contents = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize config: {}", e))?;
+
+        fs.write(&save_path.join(SAVE_CONFIG_FILE), contents.as_bytes())
+            .map_err(|e| format!("Failed to write config.json: {}", e))
+    }
+
+    pub fn is_enabled(&self, mod_key: &str) -> bool {
+        self.mods.get(mod_key).is_some_and(|e| e.enabled)
+    }
+
+    /// Enable or disable a mod, adding its key if needed. Returns the
+    /// previous state, None if the key was not in config.json.
+    pub fn set_enabled(&mut self, mod_key: &str, enabled: bool) -> Option<bool> {
+        self.mods
+            .insert(mod_key.to_string(), ModConfigEntry { enabled })
+            .map(|previous| previous.enabled)
+    }
+
+    /// Keys of the enabled mods, sorted
+    pub fn enabled_keys(&self) -> Vec<String> {
+        let mut keys: Vec<String> = self
+            .mods
+            .iter()
+            .filter(|(_, entry)| entry.enabled)
+            .map(|(key, _)| key.clone())
+            .collect();
+        keys.sort();
+        keys
+    }
+}
@@ -0,0 +1,108 @@
+use super::fs::FileSystem;
+use std::path::{Path, PathBuf};
+use zip::ZipArchive;
+
+/// Reject names that would escape the saves directory
+pub fn validate_save_name(name: &str) -> Result<(), String> {
+    let trimmed = name.trim();
+
+    if trimmed.is_empty() || trimmed == "." || trimmed == ".." {
+        return Err(format!("Invalid save name '{}'", name));
+    }
+
+    if trimmed.contains(['/', '\\', ':']) {
+        return Err(format!(
+            "Save name '{}' must not contain path separators",
+            name
+        ));
+    }
+
+    Ok(())
+}
+
+/// Imports save zips into UserData/Saves of one Hytale data directory
+pub struct SaveImporter<'a> {
+    fs: &'a dyn FileSystem,
+    saves_dir: PathBuf,
+}
+
+impl<'a> SaveImporter<'a> {
+    pub fn new(fs: &'a dyn FileSystem, hytale_root: impl AsRef<Path>) -> Self {
+        SaveImporter {
+            fs,
+            saves_dir: hytale_root.as_ref().join("UserData").join("Saves"),
+        }
+    }
+
+    pub fn saves_dir(&self) -> &Path {
+        &self.saves_dir
+    }
+
+    /// Import a zip as a save named after the zip file. Returns the save name.
+    pub fn import(&self, zip_path: &Path) -> Result<String, String> {
+        let save_name = zip_path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .ok_or("Invalid ZIP filename")?;
+
+        self.import_as(zip_path, save_name)?;
+        Ok(save_name.to_string())
+    }
+
+    /// Extract a zip into a new save directory. Entries whose path would
+    /// escape the save are skipped. Returns the save directory.
+    pub fn import_as(&self, zip_path: &Path, save_name: &str) -> Result<PathBuf, String> {
+        validate_save_name(save_name)?;
+
+        let target_dir = self.saves_dir.join(save_name.trim());
+        if self.fs.exists(&target_dir) {
+            return Err(format!("Save '{}' already exists", save_name));
+        }
+
+        let file = self
+            .fs
+            .open(zip_path)
+            .map_err(|e| format!("Failed to open ZIP file: {}", e))?;
+        let mut archive =
+            ZipArchive::new(file).map_err(|e| format!("Failed to read ZIP archive: {}", e))?;
+
+        self.fs
+            .create_dir_all(&target_dir)
+            .map_err(|e| format!("Failed to create save directory: {}", e))?;
+
+        for i in 0..archive.len() {
+            let mut file = archive
+                .by_index(i)
+                .map_err(|e| format!("Failed to read file at index {}: {}", i, e))?;
+
+            let outpath = match file.enclosed_name() {
+                Some(path) => target_dir.join(path),
+                None => continue,
+            };
+
+            if file.is_dir() {
+                self.fs
+                    .create_dir_all(&outpath)
+                    .map_err(|e| format!("Failed to create directory: {}", e))?;
+                continue;
+            }
+
+            if let Some(parent) = outpath.parent() {
+                self.fs
+                    .create_dir_all(parent)
+                    .map_err(|e| format!("Failed to create parent directory: {}", e))?;
+            }
+
+            // An entry named like a directory the archive already created
+            if self.fs.is_dir(&outpath) {
+                continue;
+            }
+
+            self.fs
+                .write_from(&outpath, &mut file)
+                .map_err(|e| format!("Failed to extract file {:?}: {}", outpath, e))?;
+        }
+
+        Ok(target_dir)
+    }
+}
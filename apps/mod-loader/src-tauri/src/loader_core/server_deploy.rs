@@ -0,0 +1,498 @@
+use super::fs::FileSystem;
+use super::lockfile::{sha256_bytes, sha256_reader};
+use super::mod_index::ModIndex;
+use super::modpack_installer::{
+    resolve_archive_action, ArchiveAction, ArchiveStatus, ConfigKeyAction, ConflictPolicy,
+    FileScope, InstallJournal, PackContents, PlannedConfigEntry,
+};
+use super::receipts::current_sha;
+use super::save_config::SaveConfig;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::io::{Seek, SeekFrom};
+use std::path::{Component, Path, PathBuf};
+
+/// Contents of a file to deploy: already in memory (from a modpack) or read
+/// when it is written (from a save, whose world can be large)
+pub enum FileSource {
+    Bytes(Vec<u8>),
+    Disk(PathBuf),
+}
+
+impl FileSource {
+    pub fn sha256(&self, fs: &dyn FileSystem) -> Result<String, String> {
+        match self {
+            FileSource::Bytes(data) => Ok(sha256_bytes(data)),
+            FileSource::Disk(path) => {
+                let mut file = fs
+                    .open(path)
+                    .map_err(|e| format!("Failed to open {:?}: {}", path, e))?;
+                sha256_reader(&mut file).map_err(|e| format!("Failed to hash {:?}: {}", path, e))
+            }
+        }
+    }
+
+    pub fn size(&self, fs: &dyn FileSystem) -> Result<u64, String> {
+        match self {
+            FileSource::Bytes(data) => Ok(data.len() as u64),
+            FileSource::Disk(path) => fs
+                .open(path)
+                .and_then(|mut file| file.seek(SeekFrom::End(0)))
+                .map_err(|e| format!("Failed to read {:?}: {}", path, e)),
+        }
+    }
+
+    pub fn read(&self, fs: &dyn FileSystem) -> Result<Vec<u8>, String> {
+        match self {
+            FileSource::Bytes(data) => Ok(data.clone()),
+            FileSource::Disk(path) => fs
+                .read(path)
+                .map_err(|e| format!("Failed to read {:?}: {}", path, e)),
+        }
+    }
+}
+
+pub struct DeployFile {
+    /// Relative to the server directory
+    pub relative_path: PathBuf,
+    pub source: FileSource,
+}
+
+/// A mod archive, or every file of an unpacked mod, for the server's mods/ dir
+pub struct DeployMod {
+    pub file_name: String,
+    pub mod_key: Option<String>,
+    pub version: Option<String>,
+    pub files: Vec<DeployFile>,
+}
+
+/// A modpack or save, read into the shape of a server directory
+pub struct DeploySource {
+    pub name: String,
+    pub mods: Vec<DeployMod>,
+    /// Per-mod config files under mods/
+    pub configs: Vec<DeployFile>,
+    pub world: Vec<DeployFile>,
+    /// Enabled in the source but without an archive to deploy
+    pub missing: Vec<String>,
+    pub source_sha256: Option<String>,
+}
+
+impl DeploySource {
+    /// A modpack's archives, configs and (optionally) bundled world
+    pub fn from_pack(pack: PackContents, include_world: bool) -> Self {
+        let mods = pack
+            .mods
+            .into_iter()
+            .map(|pack_mod| DeployMod {
+                mod_key: pack_mod.manifest.as_ref().map(|m| m.key()),
+                version: pack_mod.manifest.as_ref().map(|m| m.version.clone()),
+                files: vec![DeployFile {
+                    relative_path: Path::new("mods").join(&pack_mod.file_name),
+                    source: FileSource::Bytes(pack_mod.data),
+                }],
+                file_name: pack_mod.file_name,
+            })
+            .collect();
+
+        let configs = pack
+            .configs
+            .into_iter()
+            .map(|file| DeployFile {
+                relative_path: Path::new("mods").join(file.relative_path),
+                source: FileSource::Bytes(file.data),
+            })
+            .collect();
+
+        // The bundled world's config.json is a client save config; the server keeps
+        // its own and only gets its Mods entries updated
+        let world = pack
+            .world
+            .into_iter()
+            .filter(|_| include_world)
+            .filter(|file| file.relative_path != Path::new("config.json"))
+            .map(|file| DeployFile {
+                relative_path: file.relative_path,
+                source: FileSource::Bytes(file.data),
+            })
+            .collect();
+
+        DeploySource {
+            name: pack.name,
+            mods,
+            configs,
+            world,
+            missing: Vec::new(),
+            source_sha256: None,
+        }
+    }
+}
+
+/// Options for deploying to a server directory
+#[derive(Debug, Deserialize, Clone)]
+pub struct DeployOptions {
+    /// Only compute the plan
+    #[serde(default)]
+    pub dry_run: bool,
+    /// How to resolve a mod the server already has under another archive.
+    /// Defaults to replacing it, so the server runs exactly the client's archives.
+    #[serde(default = "default_conflict_policy")]
+    pub conflict_policy: ConflictPolicy,
+    /// Per-mod policy by "Group:Name", overriding `conflict_policy`
+    #[serde(default)]
+    pub conflict_overrides: HashMap<String, ConflictPolicy>,
+    /// Deploy the world (universe/) too
+    #[serde(default = "default_true")]
+    pub include_world: bool,
+    /// Overwrite world files the server already has with different contents.
+    /// Without it such files are conflicts and the deploy is refused.
+    #[serde(default)]
+    pub replace_world: bool,
+    /// Disable mods enabled in the server's config.json that the source doesn't enable
+    #[serde(default = "default_true")]
+    pub disable_other_mods: bool,
+}
+
+fn default_conflict_policy() -> ConflictPolicy {
+    ConflictPolicy::Replace
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for DeployOptions {
+    fn default() -> Self {
+        DeployOptions {
+            dry_run: false,
+            conflict_policy: default_conflict_policy(),
+            conflict_overrides: HashMap::new(),
+            include_world: true,
+            replace_world: false,
+            disable_other_mods: true,
+        }
+    }
+}
+
+/// A mod the deploy would put in the server's mods/ dir
+#[derive(Debug, Serialize, Clone)]
+pub struct PlannedServerMod {
+    pub file_name: String,
+    pub mod_key: Option<String>,
+    pub version: Option<String>,
+    pub status: ArchiveStatus,
+    /// Archive or directory in the server's mods/ dir with the same Group:Name
+    pub existing_file: Option<String>,
+    pub existing_version: Option<String>,
+    pub action: ArchiveAction,
+}
+
+/// A config or world file the deploy would write, relative to the server directory
+#[derive(Debug, Serialize, Clone)]
+pub struct PlannedServerFile {
+    pub path: String,
+    /// The server has a file here with different contents
+    pub overwrites: bool,
+    /// The server already has this exact file, so it is left alone
+    pub unchanged: bool,
+}
+
+/// Everything a deploy will do, computed before touching any file
+#[derive(Debug, Serialize, Clone)]
+pub struct DeployPlan {
+    pub source_name: String,
+    pub server_dir: String,
+    pub mods: Vec<PlannedServerMod>,
+    pub config_files: Vec<PlannedServerFile>,
+    pub world_files: Vec<PlannedServerFile>,
+    pub config_entries: Vec<PlannedConfigEntry>,
+    /// Enabled on the server but not in the source, disabled by the deploy
+    pub disabled_mods: Vec<String>,
+    /// Enabled in the source but without an archive to deploy
+    pub missing_mods: Vec<String>,
+    /// World files that differ from the server's; the deploy is refused
+    /// unless `replace_world` is set
+    pub world_conflicts: Vec<String>,
+}
+
+/// Only plain components, so the file can't land outside the server directory
+pub fn is_enclosed(relative: &Path) -> bool {
+    relative
+        .components()
+        .all(|c| matches!(c, Component::Normal(_)))
+}
+
+pub fn relative_string(relative: &Path) -> String {
+    relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().to_string())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Deploys modpacks and saves into a dedicated server directory: archives to
+/// mods/, per-mod configs under mods/, the world next to them, and the mods
+/// enabled in the server's config.json
+pub struct ServerDeployer<'a> {
+    fs: &'a dyn FileSystem,
+    server_dir: PathBuf,
+}
+
+impl<'a> ServerDeployer<'a> {
+    pub fn new(fs: &'a dyn FileSystem, server_dir: impl Into<PathBuf>) -> Self {
+        ServerDeployer {
+            fs,
+            server_dir: server_dir.into(),
+        }
+    }
+
+    fn plan_file(&self, file: &DeployFile) -> Result<PlannedServerFile, String> {
+        let dest = self.server_dir.join(&file.relative_path);
+        let unchanged = match current_sha(self.fs, &dest) {
+            Some(existing) => existing == file.source.sha256(self.fs)?,
+            None => false,
+        };
+
+        Ok(PlannedServerFile {
+            path: relative_string(&file.relative_path),
+            overwrites: self.fs.exists(&dest) && !unchanged,
+            unchanged,
+        })
+    }
+
+    /// Work out what deploying `source` would change on the server
+    pub fn plan(
+        &self,
+        source: &DeploySource,
+        options: &DeployOptions,
+    ) -> Result<DeployPlan, String> {
+        let unsafe_path = source
+            .mods
+            .iter()
+            .flat_map(|m| &m.files)
+            .chain(&source.configs)
+            .chain(&source.world)
+            .find(|f| !is_enclosed(&f.relative_path));
+        if let Some(file) = unsafe_path {
+            return Err(format!(
+                "Refusing to deploy unsafe path {:?}",
+                file.relative_path
+            ));
+        }
+
+        let server_mods = ModIndex::global(self.fs, &self.server_dir.join("mods"));
+        let config = SaveConfig::load(self.fs, &self.server_dir)?;
+
+        let mut plan = DeployPlan {
+            source_name: source.name.clone(),
+            server_dir: self.server_dir.to_string_lossy().to_string(),
+            mods: Vec::new(),
+            config_files: Vec::new(),
+            world_files: Vec::new(),
+            config_entries: Vec::new(),
+            disabled_mods: Vec::new(),
+            missing_mods: source.missing.clone(),
+            world_conflicts: Vec::new(),
+        };
+
+        for deploy_mod in &source.mods {
+            let existing = deploy_mod
+                .mod_key
+                .as_ref()
+                .and_then(|key| server_mods.get(key));
+            let existing_file = existing.and_then(|existing| {
+                existing
+                    .path
+                    .file_name()
+                    .and_then(|s| s.to_str())
+                    .map(|s| s.to_string())
+            });
+            let existing_version = existing.map(|existing| existing.manifest.version.clone());
+
+            let status = match &existing_version {
+                None => ArchiveStatus::New,
+                Some(version) if Some(version) == deploy_mod.version.as_ref() => {
+                    ArchiveStatus::SameVersionPresent
+                }
+                Some(_) => ArchiveStatus::DifferentVersionPresent,
+            };
+
+            let policy = deploy_mod
+                .mod_key
+                .as_ref()
+                .and_then(|key| options.conflict_overrides.get(key))
+                .copied()
+                .unwrap_or(options.conflict_policy);
+            let mut action = resolve_archive_action(
+                status,
+                policy,
+                deploy_mod.version.as_deref(),
+                existing_version.as_deref(),
+            );
+
+            // Keeping both under one file name would just overwrite the existing one
+            if action == ArchiveAction::Write
+                && existing_file.as_ref() == Some(&deploy_mod.file_name)
+            {
+                action = ArchiveAction::Replace;
+            }
+
+            // Nothing to do when the server already has these exact files
+            if existing_file.as_ref() == Some(&deploy_mod.file_name) {
+                let identical = deploy_mod
+                    .files
+                    .iter()
+                    .map(|f| self.plan_file(f).map(|p| p.unchanged))
+                    .collect::<Result<Vec<bool>, String>>()?
+                    .into_iter()
+                    .all(|unchanged| unchanged);
+                if identical {
+                    action = ArchiveAction::Skip;
+                }
+            }
+
+            plan.mods.push(PlannedServerMod {
+                file_name: deploy_mod.file_name.clone(),
+                mod_key: deploy_mod.mod_key.clone(),
+                version: deploy_mod.version.clone(),
+                status,
+                existing_file,
+                existing_version,
+                action,
+            });
+
+            // A skipped mod is still provided by the server's own archive
+            if let Some(key) = &deploy_mod.mod_key {
+                let action = match config.mods.get(key) {
+                    None => ConfigKeyAction::Add,
+                    Some(entry) if !entry.enabled => ConfigKeyAction::Enable,
+                    Some(_) => ConfigKeyAction::AlreadyEnabled,
+                };
+                if plan.config_entries.iter().all(|e| &e.mod_key != key) {
+                    plan.config_entries.push(PlannedConfigEntry {
+                        mod_key: key.clone(),
+                        action,
+                    });
+                }
+            }
+        }
+
+        for file in &source.configs {
+            plan.config_files.push(self.plan_file(file)?);
+        }
+
+        for file in &source.world {
+            let planned = self.plan_file(file)?;
+            if planned.overwrites {
+                plan.world_conflicts.push(planned.path.clone());
+            }
+            plan.world_files.push(planned);
+        }
+
+        if options.disable_other_mods {
+            let deployed: HashSet<&String> =
+                plan.config_entries.iter().map(|e| &e.mod_key).collect();
+            plan.disabled_mods = config
+                .mods
+                .iter()
+                .filter(|(key, entry)| entry.enabled && !deployed.contains(key))
+                .map(|(key, _)| key.clone())
+                .collect();
+            plan.disabled_mods.sort();
+        }
+
+        Ok(plan)
+    }
+
+    fn write_planned(
+        &self,
+        journal: &mut dyn InstallJournal,
+        file: &DeployFile,
+        mod_key: Option<String>,
+    ) -> Result<(), String> {
+        let dest = self.server_dir.join(&file.relative_path);
+        journal.write_file(
+            self.fs,
+            FileScope::Save,
+            &dest,
+            &file.source.read(self.fs)?,
+            mod_key,
+        )
+    }
+
+    /// Carry out a plan, making every change through the journal. Refuses to
+    /// overwrite the server's world files unless `replace_world` is set.
+    pub fn deploy(
+        &self,
+        plan: &DeployPlan,
+        source: &DeploySource,
+        options: &DeployOptions,
+        journal: &mut dyn InstallJournal,
+    ) -> Result<(), String> {
+        if !plan.world_conflicts.is_empty() && !options.replace_world {
+            return Err(format!(
+                "The server already has different world files ({}); set replace_world to overwrite them",
+                plan.world_conflicts.len()
+            ));
+        }
+
+        let server_mods_dir = self.server_dir.join("mods");
+        self.fs
+            .create_dir_all(&server_mods_dir)
+            .map_err(|e| format!("Failed to create server mods directory: {}", e))?;
+
+        for (deploy_mod, planned) in source.mods.iter().zip(&plan.mods) {
+            if planned.action == ArchiveAction::Skip {
+                continue;
+            }
+
+            for file in &deploy_mod.files {
+                self.write_planned(journal, file, deploy_mod.mod_key.clone())?;
+            }
+
+            if planned.action == ArchiveAction::Replace {
+                if let Some(existing_file) = &planned.existing_file {
+                    let existing_path = server_mods_dir.join(existing_file);
+                    // Overwritten in place above, or an unpacked mod that stays put
+                    if existing_file != &planned.file_name && !self.fs.is_dir(&existing_path) {
+                        journal.remove_file(
+                            self.fs,
+                            FileScope::Save,
+                            &existing_path,
+                            deploy_mod.mod_key.clone(),
+                        )?;
+                    }
+                }
+            }
+        }
+
+        let files = source
+            .configs
+            .iter()
+            .zip(&plan.config_files)
+            .chain(source.world.iter().zip(&plan.world_files));
+        for (file, planned) in files {
+            if !planned.unchanged {
+                self.write_planned(journal, file, None)?;
+            }
+        }
+
+        if !plan.config_entries.is_empty() || !plan.disabled_mods.is_empty() {
+            let mut config = SaveConfig::load(self.fs, &self.server_dir)?;
+
+            for entry in &plan.config_entries {
+                let previous = config.set_enabled(&entry.mod_key, true);
+                journal.record_config_entry(&entry.mod_key, previous);
+            }
+
+            for key in &plan.disabled_mods {
+                let previous = config.set_enabled(key, false);
+                journal.record_config_entry(key, previous);
+            }
+
+            config.save(self.fs, &self.server_dir)?;
+        }
+
+        Ok(())
+    }
+}
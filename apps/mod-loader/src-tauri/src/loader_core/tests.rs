@@ -0,0 +1,1155 @@
+//! Tests for the core against `MemoryFs`. The archives under tests/fixtures
+//! are built by tests/fixtures/generate.py.
+
+use super::fs::{FileSystem, MemoryFs};
+use super::manifest::{
+    compare_versions, discover_manifests, discover_manifests_in_archive, entry_names,
+    ManifestFallback, ModManifest, ResourceType,
+};
+use super::mod_index::{ModIndex, ModLocation};
+use super::modpack_installer::{
+    ArchiveAction, ArchiveStatus, ConfigKeyAction, ConflictPolicy, FileScope, InstallJournal,
+    InstallOptions, ModpackInstaller, PackContents, PackFile, PackMod,
+};
+use super::modpack_upgrade::{
+    apply_upgrade, plan_configs, plan_mods, ChangeKind, UpgradeOptions, UpgradePlan,
+};
+use super::orbis_metadata::{read_orbis_metadata, OrbisMetadataEntry};
+use super::receipts::{InstallReceipt, ReceiptStore};
+use super::resource_installer::{inspect, ResourceInstaller};
+use super::save_config::SaveConfig;
+use super::save_importer::{validate_save_name, SaveImporter};
+use super::server_deploy::{DeployOptions, DeploySource, FileSource, ServerDeployer};
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+
+const HAMMERS_1: &[u8] = include_bytes!("../../tests/fixtures/hammers-1.0.0.jar");
+const HAMMERS_2: &[u8] = include_bytes!("../../tests/fixtures/hammers-2.0.0.jar");
+const QUIRKY: &[u8] = include_bytes!("../../tests/fixtures/quirky-textures.zip");
+const BUNDLE: &[u8] = include_bytes!("../../tests/fixtures/bundle.zip");
+const MODPACK: &[u8] = include_bytes!("../../tests/fixtures/modpack.zip");
+const MODPACK_TAMPERED: &[u8] = include_bytes!("../../tests/fixtures/modpack-tampered.zip");
+const MODPACK_UNLISTED: &[u8] = include_bytes!("../../tests/fixtures/modpack-unlisted.zip");
+const MODPACK_MISSING: &[u8] = include_bytes!("../../tests/fixtures/modpack-missing.zip");
+const SAVE_ZIP: &[u8] = include_bytes!("../../tests/fixtures/Imported World.zip");
+const STONE_PACK: &[u8] = include_bytes!("../../tests/fixtures/stone-pack-1.2.zip");
+
+const ROOT: &str = "/hytale";
+const GLOBAL_MODS: &str = "/hytale/UserData/Mods";
+const SAVE: &str = "/hytale/UserData/Saves/World";
+const OTHER_SAVE: &str = "/hytale/UserData/Saves/Other";
+const RECEIPTS: &str = "/loader/receipts";
+const SERVER: &str = "/server";
+
+/// A Hytale data directory with an empty global mods dir and one empty save
+fn hytale_fs() -> MemoryFs {
+    let fs = MemoryFs::new();
+    fs.create_dir_all(Path::new(GLOBAL_MODS)).unwrap();
+    fs.create_dir_all(&Path::new(SAVE).join("mods")).unwrap();
+    fs
+}
+
+/// Writes straight through the filesystem and remembers every call
+#[derive(Default)]
+struct RecordingJournal {
+    written: Vec<(FileScope, PathBuf)>,
+    removed: Vec<PathBuf>,
+    config_entries: Vec<(String, Option<bool>)>,
+    metadata_entries: Vec<(String, Option<OrbisMetadataEntry>)>,
+}
+
+impl InstallJournal for RecordingJournal {
+    fn write_file(
+        &mut self,
+        fs: &dyn FileSystem,
+        scope: FileScope,
+        path: &Path,
+        data: &[u8],
+        _mod_key: Option<String>,
+    ) -> Result<(), String> {
+        fs.create_dir_all(path.parent().unwrap())
+            .map_err(|e| e.to_string())?;
+        fs.write(path, data).map_err(|e| e.to_string())?;
+        self.written.push((scope, path.to_path_buf()));
+        Ok(())
+    }
+
+    fn remove_file(
+        &mut self,
+        fs: &dyn FileSystem,
+        _scope: FileScope,
+        path: &Path,
+        _mod_key: Option<String>,
+    ) -> Result<(), String> {
+        fs.remove_file(path).map_err(|e| e.to_string())?;
+        self.removed.push(path.to_path_buf());
+        Ok(())
+    }
+
+    fn record_config_entry(&mut self, mod_key: &str, previous: Option<bool>) {
+        self.config_entries.push((mod_key.to_string(), previous));
+    }
+
+    fn record_metadata_entry(&mut self, file_name: &str, previous: Option<OrbisMetadataEntry>) {
+        self.metadata_entries
+            .push((file_name.to_string(), previous));
+    }
+}
+
+/// Install the test modpack into `save`, recording it in a receipt from `store`
+fn install_with_receipt(
+    fs: &MemoryFs,
+    store: &ReceiptStore,
+    save: &str,
+    policy: ConflictPolicy,
+) -> InstallReceipt {
+    fs.insert_file("/downloads/pack.zip", MODPACK);
+    let installer = ModpackInstaller::new(fs, ROOT);
+    let pack = installer
+        .read_pack(Path::new("/downloads/pack.zip"))
+        .unwrap();
+    let options = InstallOptions {
+        conflict_policy: policy,
+        ..Default::default()
+    };
+    let plan = installer.plan(&pack, Path::new(save), &options).unwrap();
+
+    let mut receipt = store.create(&pack.name, Path::new(save), Path::new(ROOT));
+    installer.install(&plan, &pack, &mut receipt).unwrap();
+    store.save(&receipt).unwrap();
+    receipt
+}
+
+fn manifest_version(fs: &MemoryFs, path: &str) -> String {
+    let found = discover_manifests(fs, Path::new(path)).unwrap();
+    found[0].manifest.version.clone()
+}
+
+/// The test modpack's next version: it swaps the hammers archive, drops
+/// Quirky, adds the bundle and ships new settings plus one more config file
+fn next_pack_version(fs: &MemoryFs) -> PackContents {
+    let mut next = ModpackInstaller::new(fs, ROOT)
+        .read_pack(Path::new("/downloads/pack.zip"))
+        .unwrap();
+    let first_manifest = |data: &[u8]| {
+        discover_manifests_in_archive(Cursor::new(data)).unwrap()[0]
+            .manifest
+            .clone()
+    };
+    next.mods = vec![
+        PackMod {
+            file_name: "hammers-1.0.0.jar".to_string(),
+            data: HAMMERS_1.to_vec(),
+            manifest: Some(first_manifest(HAMMERS_1)),
+        },
+        PackMod {
+            file_name: "bundle.zip".to_string(),
+            data: BUNDLE.to_vec(),
+            manifest: Some(first_manifest(BUNDLE)),
+        },
+    ];
+    next.configs = vec![
+        PackFile {
+            relative_path: PathBuf::from("Acme_Hammers/settings.json"),
+            data: br#"{"damage": 15}"#.to_vec(),
+        },
+        PackFile {
+            relative_path: PathBuf::from("Bundle_Alpha/alpha.json"),
+            data: b"{}".to_vec(),
+        },
+    ];
+    next
+}
+
+#[test]
+fn memory_fs_behaves_like_a_disk() {
+    let fs = MemoryFs::new();
+
+    assert!(fs.write(Path::new("/a/b.txt"), b"x").is_err());
+    fs.create_dir_all(Path::new("/a/c")).unwrap();
+    fs.write(Path::new("/a/b.txt"), b"x").unwrap();
+    assert!(fs.is_file(Path::new("/a/b.txt")));
+    assert!(fs.is_dir(Path::new("/a")));
+    assert!(fs.create_dir_all(Path::new("/a/b.txt")).is_err());
+
+    let entries = fs.read_dir(Path::new("/a")).unwrap();
+    assert_eq!(
+        entries,
+        vec![PathBuf::from("/a/b.txt"), PathBuf::from("/a/c")]
+    );
+
+    fs.remove_dir_all(Path::new("/a")).unwrap();
+    assert!(!fs.exists(Path::new("/a/b.txt")));
+    assert!(fs.read(Path::new("/a/b.txt")).is_err());
+}
+
+#[test]
+fn tolerates_nested_misnamed_manifest_with_bom_and_trailing_commas() {
+    let found = discover_manifests_in_archive(Cursor::new(QUIRKY)).unwrap();
+
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].entry_path, "Quirky/Manifest.json");
+    assert_eq!(found[0].manifest.key(), "Acme:Quirky");
+    assert_eq!(
+        found[0].fallbacks,
+        vec![
+            ManifestFallback::NestedFolder,
+            ManifestFallback::FileNameCasing,
+            ManifestFallback::ByteOrderMark,
+            ManifestFallback::TrailingCommas,
+        ]
+    );
+    assert_eq!(found[0].resource_type, ResourceType::AssetPack);
+}
+
+#[test]
+fn finds_every_mod_in_a_bundle() {
+    let found = discover_manifests_in_archive(Cursor::new(BUNDLE)).unwrap();
+    let summary: Vec<(String, ResourceType)> = found
+        .iter()
+        .map(|d| (d.manifest.key(), d.resource_type))
+        .collect();
+
+    assert_eq!(
+        summary,
+        vec![
+            ("Bundle:Alpha".to_string(), ResourceType::Plugin),
+            ("Bundle:Beta".to_string(), ResourceType::AssetPack),
+        ]
+    );
+}
+
+#[test]
+fn reads_unpacked_mod_directories() {
+    let fs = MemoryFs::new();
+    fs.insert_file(
+        "/mods/Tools/manifest.json",
+        r#"{"Group": "Acme", "Name": "Tools", "Version": "1.0", "Main": "acme.Tools"}"#,
+    );
+    fs.insert_file("/mods/Tools/Common/Items/wrench.json", "{}");
+
+    let found = discover_manifests(&fs, Path::new("/mods/Tools")).unwrap();
+    assert_eq!(found[0].manifest.key(), "Acme:Tools");
+    assert_eq!(found[0].resource_type, ResourceType::Mod);
+
+    let names = entry_names(&fs, Path::new("/mods/Tools")).unwrap();
+    assert_eq!(
+        names,
+        vec![
+            "Common/",
+            "Common/Items/",
+            "Common/Items/wrench.json",
+            "manifest.json",
+        ]
+    );
+}
+
+#[test]
+fn compares_versions_numerically() {
+    assert_eq!(compare_versions("1.10.0", "1.9.2"), Ordering::Greater);
+    assert_eq!(compare_versions("2.0", "2.0.0"), Ordering::Equal);
+    assert_eq!(compare_versions("2.0", "2.0.1"), Ordering::Less);
+    assert_eq!(compare_versions("v1.0", "V1.0"), Ordering::Equal);
+}
+
+#[test]
+fn save_local_mods_shadow_global_ones() {
+    let fs = hytale_fs();
+    fs.insert_file(Path::new(GLOBAL_MODS).join("hammers-1.0.0.jar"), HAMMERS_1);
+    fs.insert_file(Path::new(GLOBAL_MODS).join("bundle.zip"), BUNDLE);
+    fs.insert_file(Path::new(GLOBAL_MODS).join("readme.txt"), "not a mod");
+    fs.insert_file(Path::new(GLOBAL_MODS).join("broken.jar"), "not a zip");
+    fs.insert_file(Path::new(SAVE).join("mods/hammers-2.0.0.jar"), HAMMERS_2);
+
+    let global = ModIndex::global(&fs, Path::new(GLOBAL_MODS));
+    assert_eq!(global.len(), 3);
+    assert_eq!(
+        global.get("Acme:Hammers").unwrap().manifest.version,
+        "1.0.0"
+    );
+    assert!(global.contains("Bundle:Alpha") && global.contains("Bundle:Beta"));
+
+    let index = ModIndex::for_save(&fs, Path::new(SAVE), Path::new(GLOBAL_MODS));
+    let hammers = index.get("Acme:Hammers").unwrap();
+    assert_eq!(hammers.location, ModLocation::SaveLocal);
+    assert_eq!(hammers.manifest.version, "2.0.0");
+    assert_eq!(
+        hammers.shadowed_global.as_deref(),
+        Some(Path::new("/hytale/UserData/Mods/hammers-1.0.0.jar"))
+    );
+    assert_eq!(
+        index.get("Bundle:Beta").unwrap().location,
+        ModLocation::Global
+    );
+}
+
+#[test]
+fn save_config_keeps_unknown_keys() {
+    let fs = hytale_fs();
+    let save = Path::new(SAVE);
+
+    let mut config = SaveConfig::load(&fs, save).unwrap();
+    assert!(config.mods.is_empty());
+
+    fs.insert_file(
+        save.join("config.json"),
+        r#"{"Mods": {"Acme:Hammers": {"Enabled": false}}, "Seed": 42}"#,
+    );
+    config = SaveConfig::load(&fs, save).unwrap();
+    assert!(!config.is_enabled("Acme:Hammers"));
+    assert_eq!(config.set_enabled("Acme:Hammers", true), Some(false));
+    assert_eq!(config.set_enabled("Acme:Quirky", true), None);
+    config.save(&fs, save).unwrap();
+
+    let reloaded = SaveConfig::load(&fs, save).unwrap();
+    assert_eq!(reloaded.enabled_keys(), vec!["Acme:Hammers", "Acme:Quirky"]);
+    assert_eq!(reloaded.other.get("Seed"), Some(&serde_json::json!(42)));
+
+    fs.insert_file(save.join("config.json"), "{ not json");
+    assert!(SaveConfig::load(&fs, save).is_err());
+}
+
+#[test]
+fn installs_a_modpack_into_a_save() {
+    let fs = hytale_fs();
+    fs.insert_file("/downloads/Starter Pack.zip", MODPACK);
+    let installer = ModpackInstaller::new(&fs, ROOT);
+
+    let pack = installer
+        .read_pack(Path::new("/downloads/Starter Pack.zip"))
+        .unwrap();
+    assert_eq!(pack.name, "Starter Pack");
+    assert_eq!(pack.mods.len(), 2);
+    assert_eq!(pack.world.len(), 1);
+    assert_eq!(pack.warnings.len(), 1);
+    assert!(pack.warnings[0].contains("../escape.txt"));
+
+    let plan = installer
+        .plan(&pack, Path::new(SAVE), &InstallOptions::default())
+        .unwrap();
+    assert!(plan
+        .archives
+        .iter()
+        .all(|a| a.status == ArchiveStatus::New && a.action == ArchiveAction::Write));
+    assert!(plan
+        .config_entries
+        .iter()
+        .all(|e| e.action == ConfigKeyAction::Add));
+    assert_eq!(plan.config_files.len(), 1);
+
+    let before = fs.files();
+    let mut journal = RecordingJournal::default();
+    let warnings = installer.install(&plan, &pack, &mut journal).unwrap();
+    assert!(warnings.is_empty());
+
+    let global = Path::new(GLOBAL_MODS);
+    let save = Path::new(SAVE);
+    assert_eq!(
+        fs.read(&global.join("hammers-2.0.0.jar")).unwrap(),
+        HAMMERS_2
+    );
+    assert!(fs.is_file(&global.join("quirky-textures.zip")));
+    assert_eq!(
+        fs.read_to_string(&save.join("mods/Acme_Hammers/settings.json"))
+            .unwrap(),
+        r#"{"damage": 12}"#
+    );
+
+    // The config zip's "../escape.txt" entry was dropped, not written elsewhere
+    let added: Vec<PathBuf> = fs
+        .files()
+        .into_iter()
+        .filter(|p| !before.contains(p))
+        .collect();
+    assert!(added.iter().all(|p| !p.ends_with("escape.txt")));
+    assert_eq!(journal.written.len(), 3);
+    assert_eq!(journal.written[2].0, FileScope::Save);
+
+    let config = SaveConfig::load(&fs, save).unwrap();
+    assert_eq!(config.enabled_keys(), vec!["Acme:Hammers", "Acme:Quirky"]);
+    assert_eq!(
+        journal.config_entries,
+        vec![
+            ("Acme:Hammers".to_string(), None),
+            ("Acme:Quirky".to_string(), None),
+        ]
+    );
+
+    let metadata = read_orbis_metadata(&fs, global).unwrap();
+    let hammers = &metadata["hammers-2.0.0.jar"];
+    assert_eq!(hammers.id, "res-hammers");
+    assert_eq!(hammers.version_id.as_deref(), Some("ver-2"));
+    assert_eq!(hammers.author, "Acme");
+    assert!(hammers.sha256.is_some());
+    assert!(!metadata.contains_key("quirky-textures.zip"));
+    assert_eq!(journal.metadata_entries.len(), 1);
+}
+
+#[test]
+fn planning_writes_nothing() {
+    let fs = hytale_fs();
+    fs.insert_file("/downloads/pack.zip", MODPACK);
+    let installer = ModpackInstaller::new(&fs, ROOT);
+    let before = fs.files();
+
+    let pack = installer
+        .read_pack(Path::new("/downloads/pack.zip"))
+        .unwrap();
+    installer
+        .plan(&pack, Path::new(SAVE), &InstallOptions::default())
+        .unwrap();
+
+    assert_eq!(fs.files(), before);
+}
+
+#[test]
+fn resolves_conflicts_with_installed_versions() {
+    let fs = hytale_fs();
+    fs.insert_file("/downloads/pack.zip", MODPACK);
+    fs.insert_file(Path::new(GLOBAL_MODS).join("hammers-1.0.0.jar"), HAMMERS_1);
+    fs.insert_file(
+        Path::new(SAVE).join("config.json"),
+        r#"{"Mods": {"Acme:Hammers": {"Enabled": false}}}"#,
+    );
+    let installer = ModpackInstaller::new(&fs, ROOT);
+    let pack = installer
+        .read_pack(Path::new("/downloads/pack.zip"))
+        .unwrap();
+
+    let plan_with = |policy: ConflictPolicy| {
+        let options = InstallOptions {
+            conflict_policy: policy,
+            ..Default::default()
+        };
+        installer.plan(&pack, Path::new(SAVE), &options).unwrap()
+    };
+
+    let keep_existing = plan_with(ConflictPolicy::KeepExisting);
+    let hammers = &keep_existing.archives[0];
+    assert_eq!(hammers.status, ArchiveStatus::DifferentVersionPresent);
+    assert_eq!(hammers.existing_version.as_deref(), Some("1.0.0"));
+    assert_eq!(hammers.action, ArchiveAction::Skip);
+    assert_eq!(
+        keep_existing.config_entries[0].action,
+        ConfigKeyAction::Enable
+    );
+
+    // 2.0.0 is newer, so the default policy replaces the installed archive
+    let plan = plan_with(ConflictPolicy::KeepNewer);
+    assert_eq!(plan.archives[0].action, ArchiveAction::Replace);
+
+    let mut journal = RecordingJournal::default();
+    installer.install(&plan, &pack, &mut journal).unwrap();
+    assert_eq!(
+        journal.removed,
+        vec![PathBuf::from("/hytale/UserData/Mods/hammers-1.0.0.jar")]
+    );
+    assert_eq!(
+        manifest_version(&fs, "/hytale/UserData/Mods/hammers-2.0.0.jar"),
+        "2.0.0"
+    );
+    assert_eq!(
+        journal.config_entries[0],
+        ("Acme:Hammers".to_string(), Some(false))
+    );
+
+    // Now the same version is installed: nothing to write unless replacing
+    let again = plan_with(ConflictPolicy::KeepNewer);
+    assert_eq!(again.archives[0].status, ArchiveStatus::SameVersionPresent);
+    assert_eq!(again.archives[0].action, ArchiveAction::Skip);
+    assert!(again.config_files[0].overwrites);
+}
+
+#[test]
+fn keeping_both_picks_a_free_file_name() {
+    let fs = hytale_fs();
+    fs.insert_file("/downloads/pack.zip", MODPACK);
+    // An older version installed under the pack's file name
+    fs.insert_file(Path::new(GLOBAL_MODS).join("hammers-2.0.0.jar"), HAMMERS_1);
+    let installer = ModpackInstaller::new(&fs, ROOT);
+    let pack = installer
+        .read_pack(Path::new("/downloads/pack.zip"))
+        .unwrap();
+
+    let options = InstallOptions {
+        conflict_overrides: [("Acme:Hammers".to_string(), ConflictPolicy::KeepBoth)]
+            .into_iter()
+            .collect(),
+        ..Default::default()
+    };
+    let plan = installer.plan(&pack, Path::new(SAVE), &options).unwrap();
+
+    assert_eq!(plan.archives[0].action, ArchiveAction::Write);
+    assert_eq!(
+        plan.archives[0].dest_path,
+        "/hytale/UserData/Mods/hammers-2.0.0-2.0.0.jar"
+    );
+
+    installer
+        .install(&plan, &pack, &mut RecordingJournal::default())
+        .unwrap();
+    assert_eq!(
+        manifest_version(&fs, "/hytale/UserData/Mods/hammers-2.0.0.jar"),
+        "1.0.0"
+    );
+    assert_eq!(
+        manifest_version(&fs, "/hytale/UserData/Mods/hammers-2.0.0-2.0.0.jar"),
+        "2.0.0"
+    );
+
+    // The lock's Orbis ids describe 2.0.0, so they belong to the new file
+    let metadata = read_orbis_metadata(&fs, Path::new(GLOBAL_MODS)).unwrap();
+    assert_eq!(metadata["hammers-2.0.0-2.0.0.jar"].id, "res-hammers");
+    assert!(!metadata.contains_key("hammers-2.0.0.jar"));
+}
+
+#[test]
+fn never_writes_over_an_unrelated_file_with_the_same_name() {
+    let fs = hytale_fs();
+    fs.insert_file("/downloads/pack.zip", MODPACK);
+    let global = Path::new(GLOBAL_MODS);
+    // A different mod, and a file with no manifest, under the pack's file names
+    fs.insert_file(global.join("hammers-2.0.0.jar"), BUNDLE);
+    fs.insert_file(global.join("quirky-textures.zip"), "not a zip");
+    let installer = ModpackInstaller::new(&fs, ROOT);
+    let pack = installer
+        .read_pack(Path::new("/downloads/pack.zip"))
+        .unwrap();
+
+    let plan = installer
+        .plan(&pack, Path::new(SAVE), &InstallOptions::default())
+        .unwrap();
+    assert!(plan
+        .archives
+        .iter()
+        .all(|a| a.status == ArchiveStatus::New && a.action == ArchiveAction::Write));
+    assert_eq!(
+        plan.archives[0].dest_path,
+        "/hytale/UserData/Mods/hammers-2.0.0-2.0.0.jar"
+    );
+    assert_eq!(
+        plan.archives[1].dest_path,
+        "/hytale/UserData/Mods/quirky-textures-0.3.zip"
+    );
+
+    installer
+        .install(&plan, &pack, &mut RecordingJournal::default())
+        .unwrap();
+    assert_eq!(fs.read(&global.join("hammers-2.0.0.jar")).unwrap(), BUNDLE);
+    assert_eq!(
+        fs.read_to_string(&global.join("quirky-textures.zip"))
+            .unwrap(),
+        "not a zip"
+    );
+    assert_eq!(
+        manifest_version(&fs, "/hytale/UserData/Mods/hammers-2.0.0-2.0.0.jar"),
+        "2.0.0"
+    );
+}
+
+#[test]
+fn rejects_packs_that_do_not_match_their_lock() {
+    let fs = hytale_fs();
+    fs.insert_file("/downloads/pack.zip", MODPACK_TAMPERED);
+    let installer = ModpackInstaller::new(&fs, ROOT);
+
+    let err = installer
+        .read_pack(Path::new("/downloads/pack.zip"))
+        .err()
+        .unwrap();
+    assert!(
+        err.contains("Hash mismatch for Mods/hammers-2.0.0.jar"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn rejects_pack_entries_the_lock_does_not_list() {
+    let fs = hytale_fs();
+    fs.insert_file("/downloads/pack.zip", MODPACK_UNLISTED);
+    let installer = ModpackInstaller::new(&fs, ROOT);
+
+    let err = installer
+        .read_pack(Path::new("/downloads/pack.zip"))
+        .err()
+        .unwrap();
+    assert!(
+        err.contains("Mods/stowaway.jar is not listed in orbis-pack.json"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn rejects_lock_entries_missing_from_the_pack() {
+    let fs = hytale_fs();
+    fs.insert_file("/downloads/pack.zip", MODPACK_MISSING);
+    let installer = ModpackInstaller::new(&fs, ROOT);
+
+    let err = installer
+        .read_pack(Path::new("/downloads/pack.zip"))
+        .err()
+        .unwrap();
+    assert!(
+        err.contains("lists Mods/anvils-1.0.0.jar but the modpack does not contain it"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn plans_an_upgrade_against_the_receipt() {
+    let fs = hytale_fs();
+    let store = ReceiptStore::new(&fs, RECEIPTS);
+    let receipt = install_with_receipt(&fs, &store, SAVE, ConflictPolicy::KeepNewer);
+    let save_mods = Path::new(SAVE).join("mods");
+    let settings = save_mods.join("Acme_Hammers/settings.json");
+
+    let installer = ModpackInstaller::new(&fs, ROOT);
+    let same = installer
+        .read_pack(Path::new("/downloads/pack.zip"))
+        .unwrap();
+    assert!(plan_mods(&fs, &receipt, &same)
+        .iter()
+        .all(|c| c.kind == ChangeKind::Unchanged));
+    assert!(plan_configs(&fs, &receipt, &same, &save_mods)
+        .iter()
+        .all(|c| c.kind == ChangeKind::Unchanged));
+
+    let next = next_pack_version(&fs);
+    let mods = plan_mods(&fs, &receipt, &next);
+    let summary: Vec<(&str, ChangeKind)> =
+        mods.iter().map(|c| (c.mod_key.as_str(), c.kind)).collect();
+    assert_eq!(
+        summary,
+        vec![
+            ("Acme:Hammers", ChangeKind::Updated),
+            ("Acme:Quirky", ChangeKind::Removed),
+            ("Bundle:Alpha", ChangeKind::Added),
+        ]
+    );
+    assert_eq!(mods[0].old_file.as_deref(), Some("hammers-2.0.0.jar"));
+    assert_eq!(mods[0].new_file.as_deref(), Some("hammers-1.0.0.jar"));
+    assert_eq!(mods[0].old_version.as_deref(), Some("2.0.0"));
+    assert_eq!(mods[0].new_version.as_deref(), Some("1.0.0"));
+
+    let configs = plan_configs(&fs, &receipt, &next, &save_mods);
+    let kinds: Vec<ChangeKind> = configs.iter().map(|c| c.kind).collect();
+    assert_eq!(kinds, vec![ChangeKind::Updated, ChangeKind::Added]);
+
+    // Once the player edits the settings, the update is a conflict
+    fs.write(&settings, br#"{"damage": 99}"#).unwrap();
+    let configs = plan_configs(&fs, &receipt, &next, &save_mods);
+    assert_eq!(configs[0].kind, ChangeKind::Conflict);
+    assert_eq!(configs[0].reason.as_deref(), Some("edited since install"));
+}
+
+#[test]
+fn applies_an_upgrade_through_the_receipt() {
+    let fs = hytale_fs();
+    let store = ReceiptStore::new(&fs, RECEIPTS);
+    let mut receipt = install_with_receipt(&fs, &store, SAVE, ConflictPolicy::KeepNewer);
+    let global = Path::new(GLOBAL_MODS);
+    let save_mods = Path::new(SAVE).join("mods");
+    let settings = save_mods.join("Acme_Hammers/settings.json");
+    fs.write(&settings, br#"{"damage": 99}"#).unwrap();
+
+    let next = next_pack_version(&fs);
+    let plan = UpgradePlan::new(&fs, &receipt, &next);
+    let notes = apply_upgrade(
+        &fs,
+        &mut receipt,
+        &next,
+        &plan,
+        &HashSet::new(),
+        &UpgradeOptions::default(),
+    )
+    .unwrap();
+    store.save(&receipt).unwrap();
+
+    assert_eq!(
+        fs.read(&global.join("hammers-1.0.0.jar")).unwrap(),
+        HAMMERS_1
+    );
+    assert!(fs.is_file(&global.join("bundle.zip")));
+    assert!(!fs.exists(&global.join("hammers-2.0.0.jar")));
+    assert!(!fs.exists(&global.join("quirky-textures.zip")));
+    assert!(fs.is_file(&save_mods.join("Bundle_Alpha/alpha.json")));
+
+    // The edited settings are left alone
+    assert_eq!(fs.read_to_string(&settings).unwrap(), r#"{"damage": 99}"#);
+    assert!(notes
+        .iter()
+        .any(|n| n.contains("Acme_Hammers/settings.json")));
+
+    let config = SaveConfig::load(&fs, Path::new(SAVE)).unwrap();
+    assert_eq!(config.enabled_keys(), vec!["Acme:Hammers", "Bundle:Alpha"]);
+    assert!(!config.mods.contains_key("Acme:Quirky"));
+
+    // The receipt follows the upgrade, so uninstalling reverts the new files
+    let summary = store.uninstall(&receipt).unwrap();
+    assert!(!fs.exists(&global.join("hammers-1.0.0.jar")));
+    assert!(!fs.exists(&global.join("bundle.zip")));
+    assert!(fs.is_file(&settings));
+    assert!(summary
+        .kept_files
+        .iter()
+        .all(|k| k.path == settings.to_str().unwrap()));
+    assert!(SaveConfig::load(&fs, Path::new(SAVE))
+        .unwrap()
+        .enabled_keys()
+        .is_empty());
+}
+
+#[test]
+fn upgrade_keeps_an_archive_installed_from_elsewhere() {
+    let fs = hytale_fs();
+    let store = ReceiptStore::new(&fs, RECEIPTS);
+    let mut receipt = install_with_receipt(&fs, &store, SAVE, ConflictPolicy::KeepNewer);
+    let global = Path::new(GLOBAL_MODS);
+    fs.insert_file(global.join("alpha-bundle.zip"), BUNDLE);
+
+    let next = next_pack_version(&fs);
+    let plan = UpgradePlan::new(&fs, &receipt, &next);
+    let notes = apply_upgrade(
+        &fs,
+        &mut receipt,
+        &next,
+        &plan,
+        &HashSet::new(),
+        &UpgradeOptions::default(),
+    )
+    .unwrap();
+    store.save(&receipt).unwrap();
+
+    // Same mods at the same version: the player's copy is used
+    assert!(!fs.exists(&global.join("bundle.zip")));
+    assert!(notes
+        .iter()
+        .any(|n| n.contains("Keeping installed alpha-bundle.zip")));
+    let config = SaveConfig::load(&fs, Path::new(SAVE)).unwrap();
+    assert!(config.enabled_keys().contains(&"Bundle:Alpha".to_string()));
+
+    store.uninstall(&receipt).unwrap();
+    assert_eq!(fs.read(&global.join("alpha-bundle.zip")).unwrap(), BUNDLE);
+}
+
+#[test]
+fn upgrade_writes_beside_a_file_that_takes_the_archive_name() {
+    let fs = hytale_fs();
+    let store = ReceiptStore::new(&fs, RECEIPTS);
+    let mut receipt = install_with_receipt(&fs, &store, SAVE, ConflictPolicy::KeepNewer);
+    let global = Path::new(GLOBAL_MODS);
+    fs.insert_file(global.join("bundle.zip"), b"not a mod");
+
+    let next = next_pack_version(&fs);
+    let plan = UpgradePlan::new(&fs, &receipt, &next);
+    apply_upgrade(
+        &fs,
+        &mut receipt,
+        &next,
+        &plan,
+        &HashSet::new(),
+        &UpgradeOptions::default(),
+    )
+    .unwrap();
+    store.save(&receipt).unwrap();
+
+    assert_eq!(fs.read(&global.join("bundle.zip")).unwrap(), b"not a mod");
+    fs.insert_file("/downloads/bundle.zip", BUNDLE);
+    let version = manifest_version(&fs, "/downloads/bundle.zip");
+    let renamed = global.join(format!("bundle-{}.zip", version));
+    assert_eq!(fs.read(&renamed).unwrap(), BUNDLE);
+
+    store.uninstall(&receipt).unwrap();
+    assert!(!fs.exists(&renamed));
+    assert_eq!(fs.read(&global.join("bundle.zip")).unwrap(), b"not a mod");
+}
+
+#[test]
+fn uninstall_keeps_files_other_saves_still_use() {
+    let fs = hytale_fs();
+    fs.insert_file(
+        Path::new(OTHER_SAVE).join("config.json"),
+        r#"{"Mods": {"Acme:Hammers": {"Enabled": true}}}"#,
+    );
+    let store = ReceiptStore::new(&fs, RECEIPTS);
+    let receipt = install_with_receipt(&fs, &store, SAVE, ConflictPolicy::KeepNewer);
+
+    let global = Path::new(GLOBAL_MODS);
+    let settings = Path::new(SAVE).join("mods/Acme_Hammers/settings.json");
+    fs.write(&settings, br#"{"damage": 99}"#).unwrap();
+
+    let summary = store.uninstall(&receipt).unwrap();
+
+    let hammers = global.join("hammers-2.0.0.jar");
+    let kept: Vec<(&str, &str)> = summary
+        .kept_files
+        .iter()
+        .map(|k| (k.path.as_str(), k.reason.as_str()))
+        .collect();
+    assert_eq!(
+        kept,
+        vec![
+            (settings.to_str().unwrap(), "modified since install"),
+            (
+                hammers.to_str().unwrap(),
+                "enabled in /hytale/UserData/Saves/Other"
+            ),
+        ]
+    );
+    assert!(fs.is_file(&hammers));
+    assert!(!fs.exists(&global.join("quirky-textures.zip")));
+    assert_eq!(summary.removed_files.len(), 1);
+
+    // The kept archive keeps its metadata; the save's config.json is reverted
+    let metadata = read_orbis_metadata(&fs, global).unwrap();
+    assert!(metadata.contains_key("hammers-2.0.0.jar"));
+    assert!(SaveConfig::load(&fs, Path::new(SAVE))
+        .unwrap()
+        .enabled_keys()
+        .is_empty());
+
+    assert!(store.read(&receipt.id).is_err());
+    assert!(!fs.exists(receipt.backup_dir()));
+}
+
+#[test]
+fn uninstall_keeps_files_another_receipt_owns() {
+    let fs = hytale_fs();
+    fs.create_dir_all(&Path::new(OTHER_SAVE).join("mods"))
+        .unwrap();
+    let store = ReceiptStore::new(&fs, RECEIPTS);
+    let first = install_with_receipt(&fs, &store, SAVE, ConflictPolicy::KeepNewer);
+    // Replacing rewrites the same archives, so the second install owns them too
+    let second = install_with_receipt(&fs, &store, OTHER_SAVE, ConflictPolicy::Replace);
+    assert_ne!(first.id, second.id);
+
+    let hammers = Path::new(GLOBAL_MODS).join("hammers-2.0.0.jar");
+    let hammers = hammers.to_str().unwrap();
+    assert!(store
+        .paths_owned_by_others(&first.id)
+        .unwrap()
+        .contains(hammers));
+
+    let summary = store.uninstall(&first).unwrap();
+    assert!(summary
+        .kept_files
+        .iter()
+        .any(|k| k.path == hammers && k.reason == "owned by another modpack install"));
+    assert_eq!(
+        summary.removed_files,
+        vec!["/hytale/UserData/Saves/World/mods/Acme_Hammers/settings.json"]
+    );
+    assert!(fs.is_file(Path::new(hammers)));
+
+    // The second install backed up the first one's archive and puts it back
+    let summary = store.uninstall(&second).unwrap();
+    assert!(summary.restored_files.iter().any(|p| p == hammers));
+    assert_eq!(fs.read(Path::new(hammers)).unwrap(), HAMMERS_2);
+    assert!(store.read_all().unwrap().0.is_empty());
+}
+
+#[test]
+fn imports_a_save_zip() {
+    let fs = hytale_fs();
+    fs.insert_file("/downloads/Imported World.zip", SAVE_ZIP);
+    let importer = SaveImporter::new(&fs, ROOT);
+
+    let name = importer
+        .import(Path::new("/downloads/Imported World.zip"))
+        .unwrap();
+    assert_eq!(name, "Imported World");
+
+    let save = importer.saves_dir().join("Imported World");
+    assert!(fs.is_dir(&save.join("mods")));
+    assert_eq!(
+        fs.read(&save.join("universe/worlds/default/chunks.bin"))
+            .unwrap(),
+        vec![0, 1, 2, 3]
+    );
+    let config = SaveConfig::load(&fs, &save).unwrap();
+    assert!(config.is_enabled("Acme:Hammers"));
+
+    // "../evil.txt" would have landed in UserData/Saves
+    assert!(fs.files().iter().all(|p| !p.ends_with("evil.txt")));
+
+    let err = importer
+        .import(Path::new("/downloads/Imported World.zip"))
+        .unwrap_err();
+    assert!(err.contains("already exists"));
+}
+
+#[test]
+fn rejects_save_names_that_escape_the_saves_dir() {
+    let fs = hytale_fs();
+    fs.insert_file("/downloads/save.zip", SAVE_ZIP);
+    let importer = SaveImporter::new(&fs, ROOT);
+
+    for name in ["", " ", "..", "../Other", "a/b", "C:evil"] {
+        assert!(validate_save_name(name).is_err(), "{:?}", name);
+        assert!(importer
+            .import_as(Path::new("/downloads/save.zip"), name)
+            .is_err());
+    }
+    assert!(validate_save_name("My World").is_ok());
+
+    let missing = importer.import_as(Path::new("/downloads/missing.zip"), "Missing");
+    assert!(missing.is_err());
+    assert!(!fs.exists(&importer.saves_dir().join("Missing")));
+}
+
+#[test]
+fn manifest_key_matches_config_keys() {
+    let manifest: ModManifest =
+        serde_json::from_str(r#"{"Group": "Acme", "Name": "Hammers", "Version": "1"}"#).unwrap();
+    assert_eq!(manifest.key(), "Acme:Hammers");
+}
+
+#[test]
+fn deploys_a_modpack_to_a_server() {
+    let fs = hytale_fs();
+    let server = Path::new(SERVER);
+    fs.insert_file(server.join("mods/hammers-1.0.0.jar"), HAMMERS_1);
+    fs.insert_file(
+        server.join("config.json"),
+        r#"{"Mods": {"Other:Mod": {"Enabled": true}}, "MaxPlayers": 20}"#,
+    );
+    fs.insert_file("/downloads/pack.zip", MODPACK);
+    let pack = ModpackInstaller::new(&fs, ROOT)
+        .read_pack(Path::new("/downloads/pack.zip"))
+        .unwrap();
+    let source = DeploySource::from_pack(pack, true);
+
+    let deployer = ServerDeployer::new(&fs, server);
+    let options = DeployOptions::default();
+    let plan = deployer.plan(&source, &options).unwrap();
+    let actions: Vec<(&str, ArchiveAction)> = plan
+        .mods
+        .iter()
+        .map(|m| (m.file_name.as_str(), m.action))
+        .collect();
+    assert_eq!(
+        actions,
+        vec![
+            ("hammers-2.0.0.jar", ArchiveAction::Replace),
+            ("quirky-textures.zip", ArchiveAction::Write),
+        ]
+    );
+    assert_eq!(plan.disabled_mods, vec!["Other:Mod"]);
+    assert!(plan.world_conflicts.is_empty());
+
+    let mut journal = RecordingJournal::default();
+    deployer
+        .deploy(&plan, &source, &options, &mut journal)
+        .unwrap();
+
+    assert_eq!(
+        fs.read(&server.join("mods/hammers-2.0.0.jar")).unwrap(),
+        HAMMERS_2
+    );
+    assert_eq!(journal.removed, vec![server.join("mods/hammers-1.0.0.jar")]);
+    assert!(fs.is_file(&server.join("mods/Acme_Hammers/settings.json")));
+    assert!(fs.is_file(&server.join("universe/worlds/default/config.json")));
+
+    let config = SaveConfig::load(&fs, server).unwrap();
+    assert_eq!(config.enabled_keys(), vec!["Acme:Hammers", "Acme:Quirky"]);
+    assert!(!config.is_enabled("Other:Mod"));
+    assert_eq!(config.other["MaxPlayers"], 20);
+
+    // Deploying again finds everything in place
+    let again = deployer.plan(&source, &options).unwrap();
+    assert!(again.mods.iter().all(|m| m.action == ArchiveAction::Skip));
+    assert!(again.config_files.iter().all(|f| f.unchanged));
+    assert!(again.world_files.iter().all(|f| f.unchanged));
+}
+
+#[test]
+fn refuses_to_overwrite_a_servers_world() {
+    let fs = hytale_fs();
+    let server = Path::new(SERVER);
+    let world_config = server.join("universe/worlds/default/config.json");
+    fs.insert_file(&world_config, r#"{"Seed": 1}"#);
+    fs.insert_file("/downloads/pack.zip", MODPACK);
+    let pack = ModpackInstaller::new(&fs, ROOT)
+        .read_pack(Path::new("/downloads/pack.zip"))
+        .unwrap();
+    let source = DeploySource::from_pack(pack, true);
+
+    let deployer = ServerDeployer::new(&fs, server);
+    let mut options = DeployOptions::default();
+    let plan = deployer.plan(&source, &options).unwrap();
+    assert_eq!(
+        plan.world_conflicts,
+        vec!["universe/worlds/default/config.json"]
+    );
+
+    let before = fs.files();
+    let mut journal = RecordingJournal::default();
+    assert!(deployer
+        .deploy(&plan, &source, &options, &mut journal)
+        .is_err());
+    assert_eq!(fs.files(), before);
+
+    options.replace_world = true;
+    deployer
+        .deploy(&plan, &source, &options, &mut journal)
+        .unwrap();
+    assert_eq!(fs.read_to_string(&world_config).unwrap(), r#"{"Seed": 7}"#);
+}
+
+#[test]
+fn deploy_sources_read_files_through_the_filesystem() {
+    let fs = MemoryFs::new();
+    fs.insert_file("/saves/World/mods/hammers.jar", HAMMERS_1);
+    let source = FileSource::Disk(PathBuf::from("/saves/World/mods/hammers.jar"));
+
+    assert_eq!(source.size(&fs).unwrap(), HAMMERS_1.len() as u64);
+    assert_eq!(source.read(&fs).unwrap(), HAMMERS_1);
+    assert_eq!(
+        source.sha256(&fs).unwrap(),
+        FileSource::Bytes(HAMMERS_1.to_vec()).sha256(&fs).unwrap()
+    );
+}
+
+#[test]
+fn installs_a_mod_archive_as_a_resource() {
+    let fs = hytale_fs();
+    fs.insert_file("/downloads/hammers-2.0.0.jar", HAMMERS_2);
+    let archive = Path::new("/downloads/hammers-2.0.0.jar");
+
+    let inspection = inspect(&fs, archive).unwrap();
+    assert_eq!(inspection.resource_type, ResourceType::Plugin);
+
+    let installer = ResourceInstaller::new(&fs, ROOT);
+    let installed = installer
+        .install_mods(archive, &inspection, ConflictPolicy::KeepNewer)
+        .unwrap();
+    assert_eq!(
+        installed.destination,
+        Path::new(GLOBAL_MODS).join("hammers-2.0.0.jar")
+    );
+    assert_eq!(installed.mod_keys, vec!["Acme:Hammers"]);
+    assert_eq!(fs.read(&installed.destination).unwrap(), HAMMERS_2);
+    assert!(fs.is_file(archive));
+
+    installer
+        .enable_in_save(Path::new(SAVE), &installed.mod_keys)
+        .unwrap();
+    assert!(SaveConfig::load(&fs, Path::new(SAVE))
+        .unwrap()
+        .is_enabled("Acme:Hammers"));
+}
+
+#[test]
+fn resource_installs_resolve_mods_already_installed() {
+    let fs = hytale_fs();
+    let global = Path::new(GLOBAL_MODS);
+    fs.insert_file(global.join("hammers.jar"), HAMMERS_1);
+    fs.insert_file("/downloads/hammers-1.0.0.jar", HAMMERS_1);
+    fs.insert_file("/downloads/hammers-2.0.0.jar", HAMMERS_2);
+    let installer = ResourceInstaller::new(&fs, ROOT);
+    let install = |path: &str, policy| {
+        let archive = Path::new(path);
+        let inspection = inspect(&fs, archive).unwrap();
+        installer
+            .install_mods(archive, &inspection, policy)
+            .unwrap()
+    };
+
+    // The same version is already there under another name
+    let installed = install("/downloads/hammers-1.0.0.jar", ConflictPolicy::KeepNewer);
+    assert_eq!(installed.destination, global.join("hammers.jar"));
+    assert!(!fs.exists(&global.join("hammers-1.0.0.jar")));
+
+    // A newer version replaces it, unless the existing one is kept
+    let installed = install("/downloads/hammers-2.0.0.jar", ConflictPolicy::KeepExisting);
+    assert_eq!(installed.destination, global.join("hammers.jar"));
+    let installed = install("/downloads/hammers-2.0.0.jar", ConflictPolicy::KeepNewer);
+    assert_eq!(installed.destination, global.join("hammers-2.0.0.jar"));
+    assert_eq!(fs.read(&installed.destination).unwrap(), HAMMERS_2);
+    assert!(!fs.exists(&global.join("hammers.jar")));
+}
+
+#[test]
+fn resource_installs_never_overwrite_an_unrelated_file() {
+    let fs = hytale_fs();
+    let global = Path::new(GLOBAL_MODS);
+    fs.insert_file(global.join("hammers-2.0.0.jar"), b"not a mod");
+    fs.insert_file("/downloads/hammers-2.0.0.jar", HAMMERS_2);
+    let archive = Path::new("/downloads/hammers-2.0.0.jar");
+
+    let inspection = inspect(&fs, archive).unwrap();
+    let installed = ResourceInstaller::new(&fs, ROOT)
+        .install_mods(archive, &inspection, ConflictPolicy::KeepNewer)
+        .unwrap();
+
+    assert_eq!(
+        installed.destination,
+        global.join("hammers-2.0.0-2.0.0.jar")
+    );
+    assert_eq!(fs.read(&installed.destination).unwrap(), HAMMERS_2);
+    assert_eq!(
+        fs.read(&global.join("hammers-2.0.0.jar")).unwrap(),
+        b"not a mod"
+    );
+}
+
+#[test]
+fn unpacks_a_pack_without_a_manifest() {
+    let fs = hytale_fs();
+    let global = Path::new(GLOBAL_MODS);
+    let archive = global.join("stone-pack-1.2.zip");
+    fs.insert_file(&archive, STONE_PACK);
+
+    // An older version unpacked under the archive's name, enabled in the save
+    fs.insert_file(
+        global.join("StonePack-1.1/manifest.json"),
+        r#"{"Group": "Orbis", "Name": "StonePack-1.1", "Version": "1.1"}"#,
+    );
+    fs.insert_file(
+        Path::new(SAVE).join("config.json"),
+        r#"{"Mods": {"Orbis:StonePack-1.1": {"Enabled": true}}}"#,
+    );
+    let entry = |version: &str| {
+        format!(
+            r#"{{"id": "res-stone", "slug": "stone-pack", "name": "Stone Pack", "author": "Mason", "iconUrl": null, "version": "{}", "installedAt": "2024-01-01T00:00:00Z"}}"#,
+            version
+        )
+    };
+    fs.insert_file(
+        global.join("orbis-metadata.json"),
+        format!(
+            r#"{{"StonePack-1.1": {}, "stone-pack-1.2.zip": {}}}"#,
+            entry("1.1"),
+            entry("1.2")
+        ),
+    );
+
+    let inspection = inspect(&fs, &archive).unwrap();
+    assert_eq!(inspection.resource_type, ResourceType::AssetPack);
+    assert_eq!(inspection.wrapper_folder.as_deref(), Some("StonePack/"));
+
+    let installed = ResourceInstaller::new(&fs, ROOT)
+        .install_mods(&archive, &inspection, ConflictPolicy::KeepNewer)
+        .unwrap();
+    let dest = global.join("stone-pack");
+    assert_eq!(installed.destination, dest);
+    assert_eq!(installed.mod_keys, vec!["Orbis:stone-pack"]);
+    assert_eq!(installed.updated_saves, vec![PathBuf::from(SAVE)]);
+
+    assert!(fs.is_file(&dest.join("Common/Blocks/stone.png")));
+    assert_eq!(manifest_version(&fs, dest.to_str().unwrap()), "1.2");
+    assert!(!fs.exists(&archive));
+    assert!(!fs.exists(&global.join("StonePack-1.1")));
+
+    let metadata = read_orbis_metadata(&fs, global).unwrap();
+    let keys: HashSet<&String> = metadata.keys().collect();
+    assert_eq!(keys, HashSet::from([&"stone-pack".to_string()]));
+
+    let config = SaveConfig::load(&fs, Path::new(SAVE)).unwrap();
+    assert_eq!(config.enabled_keys(), vec!["Orbis:stone-pack"]);
+}
@@ -0,0 +1,37 @@
+use crate::loader_core::fs::OsFs;
+use crate::loader_core::manifest as core_manifest;
+use std::path::Path;
+
+pub use crate::loader_core::manifest::{DiscoveredManifest, ManifestFallback};
+
+impl DiscoveredManifest {
+    pub(crate) fn log_fallbacks(&self, source: &Path) {
+        for fallback in &self.fallbacks {
+            eprintln!(
+                "Warning: {:?} ({}): {}",
+                source,
+                self.entry_path,
+                fallback.describe()
+            );
+        }
+    }
+}
+
+/// Find every mod manifest in a mod archive or unpacked mod directory on disk
+pub(crate) fn discover_manifests(path: &Path) -> Result<Vec<DiscoveredManifest>, String> {
+    core_manifest::discover_manifests(&OsFs, path)
+}
+
+/// List every manifest in a mod archive or directory, with the fallbacks
+/// needed to read each one
+#[tauri::command]
+pub fn inspect_mod(path: String) -> Result<Vec<DiscoveredManifest>, String> {
+    let path = Path::new(&path);
+    let manifests = discover_manifests(path)?;
+
+    for manifest in &manifests {
+        manifest.log_fallbacks(path);
+    }
+
+    Ok(manifests)
+}
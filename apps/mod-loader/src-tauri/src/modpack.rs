@@ -0,0 +1,575 @@
+use crate::loader_core::fs::OsFs;
+use crate::loader_core::lockfile::{
+    sha256_bytes, PackLock, PackLockConfig, PackLockMod, PACK_LOCK_FILE, PACK_LOCK_FORMAT_VERSION,
+};
+use crate::loader_core::modpack_installer::{self, ModpackInstaller};
+use crate::loader_core::receipts::InstallReceipt;
+use crate::loader_core::save_config::SaveConfig;
+use crate::mods::{
+    build_mods_index, is_mod_archive, is_mod_directory, read_mod_config, read_orbis_metadata,
+    write_mod_config, ModLocation, ModManifest,
+};
+use crate::pack_cache::{cache_downloaded_modpack, cached_modpack_path, hash_file};
+use crate::receipts::{new_receipt, save_receipt, uninstall_modpack};
+use crate::saves::{invalidate_save_stats, validate_save_name};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{Cursor, Write};
+use std::path::{Path, PathBuf};
+use zip::write::FileOptions;
+use zip::ZipWriter;
+
+pub(crate) use crate::loader_core::modpack_installer::{is_safe_file_name, PackContents, PackFile};
+pub use crate::loader_core::modpack_installer::{
+    ArchiveAction, ArchiveStatus, ConfigKeyAction, ConflictPolicy, InstallOptions, InstallPlan,
+    PlannedConfigEntry,
+};
+
+/// Print the warnings the core collected while reading or installing a pack
+fn log_warnings(warnings: &[String]) {
+    for warning in warnings {
+        log!("Warning: {}", warning);
+    }
+}
+
+/// Read a modpack zip from disk, verifying it against orbis-pack.json
+pub(crate) fn read_pack(modpack_path: &Path) -> Result<PackContents, String> {
+    let pack = modpack_installer::read_pack(&OsFs, modpack_path)?;
+    log_warnings(&pack.warnings);
+    Ok(pack)
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct InstallOutcome {
+    pub plan: InstallPlan,
+    /// None for a dry run
+    pub receipt: Option<InstallReceipt>,
+}
+
+/// Install a modpack from a downloaded zip file
+/// - Verifies archives against orbis-pack.json when the pack has one
+/// - Extracts Mods/ contents to UserData/Mods
+/// - Extracts and unpacks Configs/*.zip to save_path/mods
+/// - Updates config.json to enable installed mods
+/// - Records Orbis metadata for mods listed in orbis-pack.json
+/// - Saves an install receipt so `uninstall_modpack` can revert it
+///
+/// Archives whose Group:Name is already installed are resolved by the options'
+/// conflict policy. With `dry_run`, nothing is written and only the plan is returned.
+/// The zip itself is left alone unless it was `downloaded`, in which case it moves
+/// into the modpack cache.
+#[tauri::command]
+pub fn install_modpack(
+    modpack_zip_path: String,
+    save_path: String,
+    hytale_root: String,
+    options: Option<InstallOptions>,
+) -> Result<InstallOutcome, String> {
+    install_modpack_recorded(modpack_zip_path, save_path, hytale_root, options, &mut None)
+}
+
+/// `install_modpack`, also reporting the id of the receipt it saved, which
+/// is set even when the install failed part-way
+fn install_modpack_recorded(
+    modpack_zip_path: String,
+    save_path: String,
+    hytale_root: String,
+    options: Option<InstallOptions>,
+    saved_receipt: &mut Option<String>,
+) -> Result<InstallOutcome, String> {
+    let options = options.unwrap_or_default();
+    let modpack_path = Path::new(&modpack_zip_path);
+    let save_path = Path::new(&save_path);
+    let hytale_path = Path::new(&hytale_root);
+
+    log!("Installing modpack from {:?}", modpack_path);
+
+    let installer = ModpackInstaller::new(&OsFs, hytale_path);
+    let pack = read_pack(modpack_path)?;
+    let plan = installer.plan(&pack, save_path, &options)?;
+
+    for archive in &plan.archives {
+        log!(
+            "Planned {} ({:?}) -> {:?}",
+            archive.file_name,
+            archive.status,
+            archive.action
+        );
+    }
+
+    if options.dry_run {
+        return Ok(InstallOutcome {
+            plan,
+            receipt: None,
+        });
+    }
+
+    let mut receipt = new_receipt(&pack.name, save_path, hytale_path)?;
+
+    // The pack is fully in memory now, so the zip can move into the cache before
+    // installing. Its hash ties the receipt to the pack for reinstalls and rollbacks.
+    if options.downloaded {
+        match cache_downloaded_modpack(modpack_path, &pack.name) {
+            Ok(cached) => receipt.source_sha256 = Some(cached.sha256),
+            Err(e) => eprintln!("Warning: Failed to cache modpack zip: {}", e),
+        }
+    } else {
+        receipt.source_sha256 = hash_file(modpack_path).ok();
+    }
+
+    let result = installer
+        .install(&plan, &pack, &mut receipt)
+        .map(|warnings| log_warnings(&warnings));
+
+    // Keep the footprint even if the install failed part-way, so it can be reverted
+    if !receipt.is_empty() {
+        match save_receipt(&receipt) {
+            Ok(()) => *saved_receipt = Some(receipt.id.clone()),
+            Err(e) => eprintln!("Warning: Failed to save install receipt: {}", e),
+        }
+    }
+    result?;
+
+    log!("Modpack installation complete");
+    Ok(InstallOutcome {
+        plan,
+        receipt: Some(receipt),
+    })
+}
+
+/// Install a modpack from the loader's cache, e.g. into a second save or again
+/// after an uninstall, without downloading it again
+#[tauri::command]
+pub fn install_cached_modpack(
+    sha256: String,
+    save_path: String,
+    hytale_root: String,
+    options: Option<InstallOptions>,
+) -> Result<InstallOutcome, String> {
+    let path = cached_modpack_path(&sha256)
+        .ok_or_else(|| format!("Modpack {} is not in the cache", sha256))?;
+
+    // The cached copy is owned by the cache, never move or delete it
+    let options = InstallOptions {
+        downloaded: false,
+        ..options.unwrap_or_default()
+    };
+
+    install_modpack(
+        path.to_string_lossy().to_string(),
+        save_path,
+        hytale_root,
+        Some(options),
+    )
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct CreatedSave {
+    pub save_path: String,
+    /// Whether the modpack bundled a world that was copied into the save
+    pub world_seeded: bool,
+    pub install: InstallOutcome,
+}
+
+/// Write the modpack's bundled world into a fresh save directory
+fn seed_world(save_path: &Path, world: &[PackFile]) -> Result<(), String> {
+    for file in world {
+        let dest = save_path.join(&file.relative_path);
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create directory {:?}: {}", parent, e))?;
+        }
+
+        log!("Seeding world file: {:?}", dest);
+        fs::write(&dest, &file.data)
+            .map_err(|e| format!("Failed to write world file {:?}: {}", dest, e))?;
+    }
+
+    Ok(())
+}
+
+/// Create a new save for a modpack: scaffold the save directory with a minimal
+/// config.json, seed it with the pack's World/ data if it bundles one, then
+/// install the pack into it. If the install fails, whatever it wrote is
+/// reverted through its receipt and the save is removed again.
+#[tauri::command]
+pub fn create_save_from_modpack(
+    hytale_root: String,
+    modpack_zip: String,
+    save_name: String,
+    options: Option<InstallOptions>,
+) -> Result<CreatedSave, String> {
+    validate_save_name(&save_name)?;
+
+    let options = options.unwrap_or_default();
+    if options.dry_run {
+        return Err("Dry runs are not supported when creating a save".to_string());
+    }
+
+    let saves_dir = Path::new(&hytale_root).join("UserData").join("Saves");
+    let save_path = saves_dir.join(save_name.trim());
+    if save_path.exists() {
+        return Err(format!("Save '{}' already exists", save_name));
+    }
+
+    // Read the pack before touching anything so a broken zip leaves no empty save behind
+    let pack = read_pack(Path::new(&modpack_zip))?;
+
+    log!("Creating save {:?} from modpack {}", save_path, pack.name);
+    fs::create_dir_all(&save_path)
+        .map_err(|e| format!("Failed to create save directory: {}", e))?;
+
+    let mut saved_receipt = None;
+    let result = seed_world(&save_path, &pack.world)
+        .and_then(|_| {
+            if save_path.join("config.json").exists() {
+                return Ok(());
+            }
+            write_mod_config(&save_path, &SaveConfig::default())
+        })
+        .and_then(|_| {
+            install_modpack_recorded(
+                modpack_zip.clone(),
+                save_path.to_string_lossy().to_string(),
+                hytale_root.clone(),
+                Some(options),
+                &mut saved_receipt,
+            )
+        });
+
+    let install = match result {
+        Ok(install) => install,
+        Err(e) => {
+            // Global mods the install already wrote outlive the save, so revert
+            // them through the receipt before removing the save itself
+            if let Some(receipt_id) = saved_receipt {
+                log!("Rolling back partial install {}", receipt_id);
+                if let Err(rollback) = uninstall_modpack(receipt_id) {
+                    eprintln!("Warning: Failed to roll back partial install: {}", rollback);
+                }
+            }
+            if let Err(cleanup) = fs::remove_dir_all(&save_path) {
+                eprintln!("Warning: Failed to remove partial save: {}", cleanup);
+            }
+            return Err(e);
+        }
+    };
+
+    invalidate_save_stats(&save_path);
+
+    Ok(CreatedSave {
+        save_path: save_path.to_string_lossy().to_string(),
+        world_seeded: !pack.world.is_empty(),
+        install,
+    })
+}
+
+/// Options for `export_modpack`
+#[derive(Debug, Deserialize, Clone)]
+pub struct ExportOptions {
+    /// Where to write the modpack zip
+    pub output_path: String,
+    /// Pack each mod's files from the save's mods/ dir into Configs/
+    #[serde(default = "default_true")]
+    pub include_configs: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ExportedMod {
+    pub mod_key: String,
+    pub file_name: String,
+    /// Name of the config archive under Configs/, if the mod has config files
+    pub config_archive: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ExportSummary {
+    pub output_path: String,
+    pub mods: Vec<ExportedMod>,
+    /// Enabled mods whose archive could not be found
+    pub missing: Vec<String>,
+}
+
+/// Config directory/file prefix the game uses for a mod inside a save's mods/ dir
+pub(crate) fn config_prefix(manifest: &ModManifest) -> String {
+    format!("{}_{}", manifest.group, manifest.name)
+}
+
+/// Config prefix for a "Group:Name" key
+pub(crate) fn config_prefix_for_key(mod_key: &str) -> String {
+    mod_key.replacen(':', "_", 1)
+}
+
+/// Recursively collect files under `dir` as (absolute path, '/'-separated relative path)
+pub(crate) fn collect_files(dir: &Path, prefix: &str, out: &mut Vec<(PathBuf, String)>) -> Result<(), String> {
+    let entries =
+        fs::read_dir(dir).map_err(|e| format!("Failed to read directory {:?}: {}", dir, e))?;
+
+    for entry in entries {
+        let entry = entry.map_err(|e| format!("Failed to read directory entry: {}", e))?;
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+        let relative = format!("{}/{}", prefix, name);
+
+        if path.is_dir() {
+            collect_files(&path, &relative, out)?;
+        } else {
+            out.push((path, relative));
+        }
+    }
+
+    Ok(())
+}
+
+/// Files in a save's mods/ dir that belong to a mod: the `<Group>_<Name>/` directory
+/// plus any loose `<Group>_<Name>.*` files that aren't archives
+pub(crate) fn collect_mod_config_files(
+    save_mods_dir: &Path,
+    manifest: &ModManifest,
+) -> Result<Vec<(PathBuf, String)>, String> {
+    collect_config_files(save_mods_dir, &config_prefix(manifest))
+}
+
+/// Same as `collect_mod_config_files`, for a "Group_Name" config prefix
+pub(crate) fn collect_config_files(
+    save_mods_dir: &Path,
+    prefix: &str,
+) -> Result<Vec<(PathBuf, String)>, String> {
+    let mut files = Vec::new();
+
+    let config_dir = save_mods_dir.join(prefix);
+    if config_dir.is_dir() && !is_mod_directory(&config_dir) {
+        collect_files(&config_dir, prefix, &mut files)?;
+    }
+
+    if let Ok(entries) = fs::read_dir(save_mods_dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if !path.is_file() || is_mod_archive(&path) {
+                continue;
+            }
+            if path.file_stem().and_then(|s| s.to_str()) == Some(prefix) {
+                files.push((path, entry.file_name().to_string_lossy().to_string()));
+            }
+        }
+    }
+
+    files.sort_by(|a, b| a.1.cmp(&b.1));
+    Ok(files)
+}
+
+/// Build an in-memory zip from (absolute path, entry name) pairs
+fn zip_files_to_bytes(files: &[(PathBuf, String)]) -> Result<Vec<u8>, String> {
+    let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = FileOptions::default();
+
+    for (path, name) in files {
+        let data = fs::read(path).map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+        writer
+            .start_file(name.as_str(), options)
+            .map_err(|e| format!("Failed to add {} to zip: {}", name, e))?;
+        writer
+            .write_all(&data)
+            .map_err(|e| format!("Failed to write {} to zip: {}", name, e))?;
+    }
+
+    let cursor = writer
+        .finish()
+        .map_err(|e| format!("Failed to finish zip: {}", e))?;
+    Ok(cursor.into_inner())
+}
+
+/// `<target>.part`, deleted when dropped unless `persist` moved it to its target
+struct PartialFile {
+    path: PathBuf,
+    persisted: bool,
+}
+
+impl PartialFile {
+    fn create(target: &Path) -> Result<(PartialFile, File), String> {
+        let path = target.with_extension("zip.part");
+        let file =
+            File::create(&path).map_err(|e| format!("Failed to create {:?}: {}", path, e))?;
+        Ok((
+            PartialFile {
+                path,
+                persisted: false,
+            },
+            file,
+        ))
+    }
+
+    fn persist(mut self, target: &Path) -> Result<(), String> {
+        fs::rename(&self.path, target)
+            .map_err(|e| format!("Failed to move modpack into place: {}", e))?;
+        self.persisted = true;
+        Ok(())
+    }
+}
+
+impl Drop for PartialFile {
+    fn drop(&mut self) {
+        if !self.persisted {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+/// Export a save's enabled mods as a modpack zip
+/// - Mods/<archive> for every enabled mod found in UserData/Mods
+/// - Configs/<Group>_<Name>.zip with the mod's files from save_path/mods
+/// - orbis-pack.json listing each mod's version, Orbis ids and hashes
+#[tauri::command]
+pub fn export_modpack(
+    save_path: String,
+    hytale_root: String,
+    options: ExportOptions,
+) -> Result<ExportSummary, String> {
+    let save_path = Path::new(&save_path);
+    let global_mods_dir = Path::new(&hytale_root).join("UserData").join("Mods");
+    let save_mods_dir = save_path.join("mods");
+    let output_path = PathBuf::from(&options.output_path);
+
+    log!(
+        "Exporting modpack from {:?} to {:?}",
+        save_path,
+        output_path
+    );
+
+    let config = read_mod_config(save_path)?;
+    let mods_index = build_mods_index(save_path, &global_mods_dir);
+    let orbis_metadata = read_orbis_metadata(&global_mods_dir);
+    let local_orbis_metadata = read_orbis_metadata(&save_mods_dir);
+
+    let mut enabled_keys: Vec<&String> = config
+        .mods
+        .iter()
+        .filter(|(_, entry)| entry.enabled)
+        .map(|(key, _)| key)
+        .collect();
+    enabled_keys.sort();
+
+    // Write to a temporary file so a failed export never leaves a truncated zip
+    let (partial, file) = PartialFile::create(&output_path)?;
+    let mut writer = ZipWriter::new(file);
+    let options_zip = FileOptions::default();
+
+    let mut pack_lock = PackLock {
+        format_version: PACK_LOCK_FORMAT_VERSION,
+        mods: Vec::new(),
+    };
+
+    let mut summary = ExportSummary {
+        output_path: output_path.to_string_lossy().to_string(),
+        mods: Vec::new(),
+        missing: Vec::new(),
+    };
+
+    for mod_key in enabled_keys {
+        let (archive_path, manifest, location) = match mods_index.get(mod_key) {
+            Some(found) => (&found.path, &found.manifest, found.location),
+            None => {
+                log!("Warning: Enabled mod {} not found, skipping", mod_key);
+                summary.missing.push(mod_key.clone());
+                continue;
+            }
+        };
+
+        let entry_name = archive_path
+            .file_name()
+            .and_then(|s| s.to_str())
+            .unwrap_or("unknown")
+            .to_string();
+
+        log!("Adding mod {} ({})", mod_key, entry_name);
+
+        // Unpacked mods go into the pack as a zip of their directory
+        let (file_name, data) = if archive_path.is_dir() {
+            let mut files = Vec::new();
+            collect_files(archive_path, "", &mut files)?;
+            for (_, name) in files.iter_mut() {
+                *name = name.trim_start_matches('/').to_string();
+            }
+            (format!("{}.zip", entry_name), zip_files_to_bytes(&files)?)
+        } else {
+            let data = fs::read(archive_path)
+                .map_err(|e| format!("Failed to read mod archive {:?}: {}", archive_path, e))?;
+            (entry_name.clone(), data)
+        };
+        writer
+            .start_file(format!("Mods/{}", file_name), options_zip)
+            .map_err(|e| format!("Failed to add {} to modpack: {}", file_name, e))?;
+        writer
+            .write_all(&data)
+            .map_err(|e| format!("Failed to write {} to modpack: {}", file_name, e))?;
+
+        let mut lock_entry = PackLockMod {
+            key: mod_key.clone(),
+            version: manifest.version.clone(),
+            resource_id: None,
+            version_id: None,
+            file_name: file_name.clone(),
+            sha256: sha256_bytes(&data),
+            config: None,
+        };
+        let metadata = match location {
+            ModLocation::SaveLocal => &local_orbis_metadata,
+            ModLocation::Global => &orbis_metadata,
+        };
+        if let Some(meta) = metadata.get(&entry_name) {
+            lock_entry.resource_id = Some(meta.id.clone());
+            lock_entry.version_id = meta.version_id.clone();
+        }
+
+        let mut config_archive = None;
+        if options.include_configs && save_mods_dir.is_dir() {
+            let files = collect_mod_config_files(&save_mods_dir, manifest)?;
+            if !files.is_empty() {
+                let archive_name = format!("{}.zip", config_prefix(manifest));
+                log!("Packing {} config files into {}", files.len(), archive_name);
+
+                let bytes = zip_files_to_bytes(&files)?;
+                writer
+                    .start_file(format!("Configs/{}", archive_name), options_zip)
+                    .map_err(|e| format!("Failed to add {} to modpack: {}", archive_name, e))?;
+                writer
+                    .write_all(&bytes)
+                    .map_err(|e| format!("Failed to write {} to modpack: {}", archive_name, e))?;
+                lock_entry.config = Some(PackLockConfig {
+                    file_name: archive_name.clone(),
+                    sha256: sha256_bytes(&bytes),
+                });
+                config_archive = Some(archive_name);
+            }
+        }
+
+        pack_lock.mods.push(lock_entry);
+        summary.mods.push(ExportedMod {
+            mod_key: mod_key.clone(),
+            file_name,
+            config_archive,
+        });
+    }
+
+    let lock_contents = serde_json::to_string_pretty(&pack_lock)
+        .map_err(|e| format!("Failed to serialize {}: {}", PACK_LOCK_FILE, e))?;
+    writer
+        .start_file(PACK_LOCK_FILE, options_zip)
+        .map_err(|e| format!("Failed to add {} to modpack: {}", PACK_LOCK_FILE, e))?;
+    writer
+        .write_all(lock_contents.as_bytes())
+        .map_err(|e| format!("Failed to write {} to modpack: {}", PACK_LOCK_FILE, e))?;
+
+    writer
+        .finish()
+        .map_err(|e| format!("Failed to finish modpack zip: {}", e))?;
+    drop(writer);
+    partial.persist(&output_path)?;
+
+    log!("Exported {} mods to {:?}", summary.mods.len(), output_path);
+    Ok(summary)
+}
@@ -1,254 +1,209 @@
+use crate::loader_core::fs::OsFs;
+use crate::loader_core::mod_index::{self, ModIndex};
+use crate::loader_core::orbis_metadata::{self, OrbisMetadata};
+use crate::loader_core::save_config::SaveConfig;
+use crate::manifest::{discover_manifests, ManifestFallback};
+use crate::modpack::is_safe_file_name;
+use crate::resources::ResourceType;
+use crate::saves::copy_dir_recursive;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::fs::{self, File};
-use std::io::Read;
+use std::collections::{HashMap, HashSet};
+use std::fs;
 use std::path::{Path, PathBuf};
-use zip::ZipArchive;
+
+pub use crate::loader_core::manifest::ModManifest;
+pub use crate::loader_core::mod_index::ModLocation;
+pub(crate) use crate::loader_core::mod_index::{is_mod_archive, ResolvedMod};
+pub use crate::loader_core::orbis_metadata::OrbisMetadataEntry;
+pub use crate::loader_core::save_config::ModConfigEntry;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct ModManifest {
-    #[serde(rename = "Group")]
-    pub group: String,
-    #[serde(rename = "Name")]
-    pub name: String,
-    #[serde(rename = "Version")]
-    pub version: String,
-    #[serde(rename = "Description")]
-    #[serde(default)]
-    pub description: String,
-    #[serde(rename = "Authors")]
-    #[serde(default)]
-    pub authors: Vec<ModAuthor>,
-    #[serde(rename = "Website")]
-    pub website: Option<String>,
-    #[serde(rename = "ServerVersion")]
-    #[serde(default)]
-    pub server_version: String,
-    #[serde(rename = "Dependencies")]
+pub struct InstalledMod {
+    pub jar_name: String, // Keep for backward compatibility (can be .jar or .zip)
+    pub manifest: ModManifest,
+    pub orbis_metadata: Option<OrbisMetadataEntry>,
     #[serde(default)]
-    pub dependencies: HashMap<String, String>,
-    #[serde(rename = "OptionalDependencies")]
+    pub location: ModLocation,
+    /// Global archive hidden by a save-local archive with the same Group:Name
     #[serde(default)]
-    pub optional_dependencies: HashMap<String, String>,
-    #[serde(rename = "DisabledByDefault")]
+    pub shadowed_global: Option<String>,
+    /// A directory with manifest.json at its root rather than an archive
     #[serde(default)]
-    pub disabled_by_default: bool,
-    #[serde(rename = "Main")]
+    pub unpacked: bool,
+    /// Workarounds needed to read the manifest; empty for a well-formed mod
     #[serde(default)]
-    pub main: String,
-    #[serde(rename = "IncludesAssetPack")]
+    pub manifest_fallbacks: Vec<ManifestFallback>,
     #[serde(default)]
-    pub includes_asset_pack: bool,
+    pub resource_type: ResourceType,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct ModAuthor {
-    #[serde(rename = "Name")]
-    pub name: String,
+/// Read orbis-metadata.json from mods directory
+pub(crate) fn read_orbis_metadata(mods_dir: &Path) -> OrbisMetadata {
+    orbis_metadata::read_orbis_metadata(&OsFs, mods_dir).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        HashMap::new()
+    })
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct OrbisMetadataEntry {
-    pub id: String,
-    pub slug: Option<String>,
-    pub name: String,
-    pub author: String,
-    #[serde(rename = "iconUrl")]
-    pub icon_url: Option<String>,
-    pub version: String,
-    #[serde(rename = "installedAt")]
-    pub installed_at: String,
+/// Write orbis-metadata.json to mods directory
+pub(crate) fn write_orbis_metadata(mods_dir: &Path, metadata: &OrbisMetadata) -> Result<(), String> {
+    orbis_metadata::write_orbis_metadata(&OsFs, mods_dir, metadata)
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct InstalledMod {
-    pub jar_name: String, // Keep for backward compatibility (can be .jar or .zip)
-    pub manifest: ModManifest,
-    pub orbis_metadata: Option<OrbisMetadataEntry>,
+/// Read config.json from save directory
+pub(crate) fn read_mod_config(save_path: &Path) -> Result<SaveConfig, String> {
+    SaveConfig::load(&OsFs, save_path)
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct ModConfig {
-    #[serde(rename = "Mods")]
-    pub mods: HashMap<String, ModConfigEntry>,
+/// Write config.json to save directory
+pub(crate) fn write_mod_config(save_path: &Path, config: &SaveConfig) -> Result<(), String> {
+    config.save(&OsFs, save_path)
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct ModConfigEntry {
-    #[serde(rename = "Enabled")]
-    pub enabled: bool,
+/// Helper to check if a path is an unpacked mod (a directory with manifest.json at its root)
+pub(crate) fn is_mod_directory(path: &Path) -> bool {
+    mod_index::is_mod_directory(&OsFs, path)
 }
 
-/// Read orbis-metadata.json from mods directory
-fn read_orbis_metadata(mods_dir: &Path) -> HashMap<String, OrbisMetadataEntry> {
-    let metadata_path = mods_dir.join("orbis-metadata.json");
-
-    if !metadata_path.exists() {
-        return HashMap::new();
-    }
-
-    match fs::read_to_string(&metadata_path) {
-        Ok(contents) => match serde_json::from_str(&contents) {
-            Ok(metadata) => metadata,
-            Err(e) => {
-                eprintln!("Failed to parse orbis-metadata.json: {}", e);
-                HashMap::new()
-            }
-        },
-        Err(e) => {
-            eprintln!("Failed to read orbis-metadata.json: {}", e);
-            HashMap::new()
-        }
-    }
+/// Helper to check if a path is a mod, packed or unpacked
+pub(crate) fn is_mod_entry(path: &Path) -> bool {
+    mod_index::is_mod_entry(&OsFs, path)
 }
 
-/// Extract manifest from a mod archive (.jar or .zip file)
-fn extract_manifest_from_archive(archive_path: &Path) -> Result<ModManifest, String> {
-    println!("extract_manifest_from_archive: Opening {:?}", archive_path);
-    let file = File::open(archive_path).map_err(|e| format!("Failed to open archive file: {}", e))?;
-
-    println!("extract_manifest_from_archive: Reading zip archive");
-    let mut archive =
-        ZipArchive::new(file).map_err(|e| format!("Failed to read archive: {}", e))?;
-
-    println!("extract_manifest_from_archive: Looking for manifest.json");
-    let mut manifest_file = archive
-        .by_name("manifest.json")
-        .map_err(|e| format!("manifest.json not found in archive: {}", e))?;
+/// Read the (first) manifest of a mod archive or unpacked mod directory
+pub(crate) fn read_mod_manifest(path: &Path) -> Result<ModManifest, String> {
+    let discovered = discover_manifests(path)?
+        .into_iter()
+        .next()
+        .ok_or("manifest.json not found")?;
 
-    let mut contents = String::new();
-    manifest_file
-        .read_to_string(&mut contents)
-        .map_err(|e| format!("Failed to read manifest.json: {}", e))?;
-
-    println!("extract_manifest_from_archive: Parsing manifest");
-    serde_json::from_str(&contents).map_err(|e| format!("Failed to parse manifest.json: {}", e))
+    discovered.log_fallbacks(path);
+    Ok(discovered.manifest)
 }
 
-/// Read config.json from save directory
-fn read_mod_config(save_path: &Path) -> Result<ModConfig, String> {
-    let config_path = save_path.join("config.json");
-
-    if !config_path.exists() {
-        // Return empty config if file doesn't exist
-        return Ok(ModConfig {
-            mods: HashMap::new(),
-        });
-    }
-
-    let contents = fs::read_to_string(&config_path)
-        .map_err(|e| format!("Failed to read config.json: {}", e))?;
-
-    serde_json::from_str(&contents).map_err(|e| format!("Failed to parse config.json: {}", e))
+/// "Group:Name" of every mod in an archive or unpacked mod directory, so a
+/// bundle is checked for each mod it provides, not just the first
+pub(crate) fn read_mod_keys(path: &Path) -> Result<Vec<String>, String> {
+    let mut keys: Vec<String> = discover_manifests(path)?
+        .into_iter()
+        .map(|d| d.manifest.key())
+        .collect();
+    keys.dedup();
+    Ok(keys)
 }
 
-/// Write config.json to save directory
-fn write_mod_config(save_path: &Path, config: &ModConfig) -> Result<(), String> {
-    let config_path = save_path.join("config.json");
-
-    let contents = serde_json::to_string_pretty(config)
-        .map_err(|e| format!("Failed to serialize config: {}", e))?;
+/// Delete a mod archive or unpacked mod directory. Anything else is refused,
+/// so a bad name can never take a whole mods directory with it.
+pub(crate) fn remove_mod_entry(path: &Path) -> Result<(), String> {
+    if !is_mod_entry(path) {
+        return Err(format!("Not a mod archive or mod directory: {:?}", path));
+    }
 
-    fs::write(&config_path, contents).map_err(|e| format!("Failed to write config.json: {}", e))
+    if path.is_dir() {
+        fs::remove_dir_all(path).map_err(|e| format!("Failed to delete mod directory: {}", e))
+    } else {
+        fs::remove_file(path).map_err(|e| format!("Failed to delete mod file: {}", e))
+    }
 }
 
-/// Helper to check if a file is a mod archive (.jar or .zip)
-fn is_mod_archive(path: &Path) -> bool {
-    match path.extension().and_then(|s| s.to_str()) {
-        Some(ext) => {
-            let ext = ext.to_lowercase();
-            ext == "jar" || ext == "zip"
+/// Report the manifest workarounds and shadowed archives found while indexing
+fn log_index(index: &ModIndex) {
+    for (key, resolved) in index.iter() {
+        for fallback in &resolved.manifest_fallbacks {
+            eprintln!("Warning: {:?} ({}): {}", resolved.path, key, fallback.describe());
+        }
+        if let Some(global) = &resolved.shadowed_global {
+            log!("Save-local {:?} takes precedence over global {:?}", resolved.path, global);
         }
-        None => false,
     }
 }
 
 /// Build a map of "Group:Name" -> (file_path, manifest) from global mods directory
-fn build_global_mods_index(global_mods_dir: &Path) -> HashMap<String, (PathBuf, ModManifest)> {
-    let mut index = HashMap::new();
-
-    if !global_mods_dir.exists() {
-        return index;
-    }
-
-    let entries = match fs::read_dir(global_mods_dir) {
-        Ok(e) => e,
-        Err(_) => return index,
-    };
-
-    for entry in entries.flatten() {
-        let path = entry.path();
-        if is_mod_archive(&path) {
-            if let Ok(manifest) = extract_manifest_from_archive(&path) {
-                let mod_key = format!("{}:{}", manifest.group, manifest.name);
-                index.insert(mod_key, (path, manifest));
-            }
-        }
-    }
+pub(crate) fn build_global_mods_index(global_mods_dir: &Path) -> HashMap<String, (PathBuf, ModManifest)> {
+    let index = ModIndex::global(&OsFs, global_mods_dir);
+    log_index(&index);
 
     index
+        .into_map()
+        .into_iter()
+        .map(|(key, resolved)| (key, (resolved.path, resolved.manifest)))
+        .collect()
+}
+
+/// Index the mods a save can see by "Group:Name". Archives in the save's own
+/// mods/ dir take precedence over global ones with the same Group:Name, the
+/// same order `register_jar_in_config` looks them up in.
+pub(crate) fn build_mods_index(save_path: &Path, global_mods_dir: &Path) -> HashMap<String, ResolvedMod> {
+    let index = ModIndex::for_save(&OsFs, save_path, global_mods_dir);
+    log_index(&index);
+    index.into_map()
 }
 
 #[tauri::command]
 pub fn get_installed_mods(save_path: String, hytale_root: String) -> Result<Vec<InstalledMod>, String> {
-    println!("get_installed_mods called with save_path: {}, hytale_root: {}", save_path, hytale_root);
+    log!("get_installed_mods called with save_path: {}, hytale_root: {}", save_path, hytale_root);
     let save_path = Path::new(&save_path);
     let hytale_path = Path::new(&hytale_root);
     let global_mods_dir = hytale_path.join("UserData").join("Mods");
 
-    println!("Looking for mods in global dir: {:?}", global_mods_dir);
+    log!("Looking for mods in global dir: {:?}", global_mods_dir);
 
     // Read config to get the list of installed mods for this save
     let config = read_mod_config(save_path)?;
-    println!("Config contains {} mod entries", config.mods.len());
+    log!("Config contains {} mod entries", config.mods.len());
 
     if config.mods.is_empty() {
-        println!("No mods configured for this save");
+        log!("No mods configured for this save");
         return Ok(Vec::new());
     }
 
-    // Read orbis metadata from global mods directory
+    // Read orbis metadata from global and save-local mods directories
     let orbis_metadata = read_orbis_metadata(&global_mods_dir);
+    let local_orbis_metadata = read_orbis_metadata(&save_path.join("mods"));
 
-    // Build index of all available mods in global directory
-    let global_mods_index = build_global_mods_index(&global_mods_dir);
-    println!("Found {} mods in global directory", global_mods_index.len());
+    // Build index of all available mods in the save's mods dir and the global directory
+    let mods_index = build_mods_index(save_path, &global_mods_dir);
+    log!("Found {} mods visible to this save", mods_index.len());
 
     let mut installed_mods = Vec::new();
 
-    // For each mod in config.json that is ENABLED, find its corresponding archive in global mods
+    // For each mod in config.json that is ENABLED, find its corresponding archive
     // (Hytale now adds all mods from UserData/Mods to config.json, so we only consider enabled ones as "installed")
     for (mod_key, config_entry) in &config.mods {
         // Only consider mods that are enabled as "installed" for this save
         if !config_entry.enabled {
-            println!("Skipping disabled mod: {}", mod_key);
+            log!("Skipping disabled mod: {}", mod_key);
             continue;
         }
 
-        println!("Looking for enabled mod: {}", mod_key);
+        log!("Looking for enabled mod: {}", mod_key);
 
-        if let Some((path, manifest)) = global_mods_index.get(mod_key) {
-            let file_name = path
-                .file_name()
-                .and_then(|s| s.to_str())
-                .unwrap_or("unknown")
-                .to_string();
+        if let Some(resolved) = mods_index.get(mod_key) {
+            let file_name = archive_file_name(&resolved.path);
 
             // Get orbis metadata if available
-            let orbis_meta = orbis_metadata.get(&file_name).cloned();
+            let orbis_meta = match resolved.location {
+                ModLocation::SaveLocal => local_orbis_metadata.get(&file_name).cloned(),
+                ModLocation::Global => orbis_metadata.get(&file_name).cloned(),
+            };
 
             installed_mods.push(InstalledMod {
                 jar_name: file_name,
-                manifest: manifest.clone(),
+                manifest: resolved.manifest.clone(),
                 orbis_metadata: orbis_meta,
+                location: resolved.location,
+                shadowed_global: resolved.shadowed_global.as_deref().map(archive_file_name),
+                unpacked: resolved.path.is_dir(),
+                manifest_fallbacks: resolved.manifest_fallbacks.clone(),
+                resource_type: resolved.resource_type,
             });
-            println!("Found mod {} -> {}", mod_key, path.display());
+            log!("Found mod {} -> {}", mod_key, resolved.path.display());
         } else {
-            println!("Warning: Mod {} not found in global mods directory", mod_key);
+            log!("Warning: Mod {} not found in save or global mods directory", mod_key);
         }
     }
 
-    println!("Found {} installed mods for this save", installed_mods.len());
+    log!("Found {} installed mods for this save", installed_mods.len());
     Ok(installed_mods)
 }
 
@@ -260,10 +215,8 @@ pub fn add_mod_to_config(save_path: String, group: String, name: String) -> Resu
     let mod_key = format!("{}:{}", group, name);
 
     // Only add if not already present
-    if !config.mods.contains_key(&mod_key) {
-        config
-            .mods
-            .insert(mod_key, ModConfigEntry { enabled: true });
+    if let std::collections::hash_map::Entry::Vacant(entry) = config.mods.entry(mod_key) {
+        entry.insert(ModConfigEntry { enabled: true });
 
         write_mod_config(save_path, &config)?;
     }
@@ -271,6 +224,39 @@ pub fn add_mod_to_config(save_path: String, group: String, name: String) -> Resu
     Ok(())
 }
 
+/// Enable or disable mods in a save's config.json by "Group:Name". Enabling a
+/// mod the config doesn't list yet adds it; disabling one it doesn't list is
+/// an error. Returns the keys whose state changed.
+#[tauri::command]
+pub fn set_mods_enabled(save_path: String, mod_keys: Vec<String>, enabled: bool) -> Result<Vec<String>, String> {
+    let save_path = Path::new(&save_path);
+    let mut config = read_mod_config(save_path)?;
+
+    if !enabled {
+        if let Some(unknown) = mod_keys.iter().find(|key| !config.mods.contains_key(*key)) {
+            return Err(format!("Mod {} is not in config.json", unknown));
+        }
+    }
+
+    let mut changed = Vec::new();
+    for key in mod_keys {
+        if !key.contains(':') {
+            return Err(format!("Invalid mod key '{}', expected Group:Name", key));
+        }
+        if config.mods.get(&key).map(|e| e.enabled) != Some(enabled) {
+            log!("{} {}", if enabled { "Enabling" } else { "Disabling" }, key);
+            config.mods.insert(key.clone(), ModConfigEntry { enabled });
+            changed.push(key);
+        }
+    }
+
+    if !changed.is_empty() {
+        write_mod_config(save_path, &config)?;
+    }
+
+    Ok(changed)
+}
+
 #[tauri::command]
 pub fn register_jar_in_config(
     save_path: String,
@@ -286,27 +272,27 @@ pub fn register_jar_in_config(
     let global_jar_path = global_mods_dir.join(&jar_filename);
 
     let jar_path = if local_jar_path.exists() {
-        println!("Found archive in local mods: {:?}", local_jar_path);
+        log!("Found archive in local mods: {:?}", local_jar_path);
         local_jar_path
     } else if global_jar_path.exists() {
-        println!("Found archive in global mods: {:?}", global_jar_path);
+        log!("Found archive in global mods: {:?}", global_jar_path);
         global_jar_path
     } else {
         let err = format!(
             "Archive file not found in local {:?} or global {:?}",
             local_jar_path, global_jar_path
         );
-        println!("{}", err);
+        log!("{}", err);
         return Err(err);
     };
 
-    println!("Registering archive: {:?}", jar_path);
+    log!("Registering archive: {:?}", jar_path);
 
-    let manifest = match extract_manifest_from_archive(&jar_path) {
+    let manifest = match read_mod_manifest(&jar_path) {
         Ok(m) => m,
         Err(e) => {
             let err = format!("Failed to extract manifest from {:?}: {}", jar_path, e);
-            println!("{}", err);
+            log!("{}", err);
             return Err(err);
         }
     };
@@ -318,14 +304,14 @@ pub fn register_jar_in_config(
 
     if let Some(entry) = config.mods.get_mut(&mod_key) {
         if !entry.enabled {
-            println!("Mod {} found but disabled, enabling it.", mod_key);
+            log!("Mod {} found but disabled, enabling it.", mod_key);
             entry.enabled = true;
             needs_save = true;
         } else {
-            println!("{} already in config and enabled", mod_key);
+            log!("{} already in config and enabled", mod_key);
         }
     } else {
-        println!("Adding {} to config", mod_key);
+        log!("Adding {} to config", mod_key);
         config
             .mods
             .insert(mod_key, ModConfigEntry { enabled: true });
@@ -340,7 +326,7 @@ pub fn register_jar_in_config(
 
         if let Err(e) = write_mod_config(save_path, &config) {
             let err = format!("Failed to write config: {}", e);
-            println!("{}", err);
+            log!("{}", err);
             return Err(err);
         }
     }
@@ -357,6 +343,17 @@ pub fn delete_mod(
 ) -> Result<(), String> {
     let save_path = Path::new(&save_path);
 
+    if !is_safe_file_name(&jar_filename) {
+        return Err(format!("Invalid archive name '{}'", jar_filename));
+    }
+
+    let mods_dir = save_path.join("mods");
+    let jar_path = mods_dir.join(&jar_filename);
+
+    if jar_path.exists() && !is_mod_entry(&jar_path) {
+        return Err(format!("Not a mod archive or mod directory: {:?}", jar_path));
+    }
+
     // 1. Remove from config
     let mut config = read_mod_config(save_path)?;
     let mod_key = format!("{}:{}", group, name);
@@ -366,22 +363,171 @@ pub fn delete_mod(
     }
 
     // 2. Delete jar file
-    let mods_dir = save_path.join("mods");
-    let jar_path = mods_dir.join(&jar_filename);
-
     if jar_path.exists() {
-        fs::remove_file(&jar_path).map_err(|e| format!("Failed to delete mod file: {}", e))?;
+        remove_mod_entry(&jar_path)?;
     }
 
     Ok(())
 }
 
+fn archive_file_name(path: &Path) -> String {
+    path.file_name()
+        .and_then(|s| s.to_str())
+        .unwrap_or("unknown")
+        .to_string()
+}
+
+/// Move a mod archive between a save's mods/ dir and UserData/Mods, taking its
+/// Orbis metadata along. Moving a global mod into one save hides it from every
+/// other save, so that is refused while other saves have it enabled unless `force` is set.
+#[tauri::command]
+pub fn move_mod(
+    save_path: String,
+    hytale_root: String,
+    jar_filename: String,
+    to: ModLocation,
+    force: Option<bool>,
+) -> Result<String, String> {
+    let save_path = Path::new(&save_path);
+    let hytale_path = Path::new(&hytale_root);
+    let local_dir = save_path.join("mods");
+    let global_dir = hytale_path.join("UserData").join("Mods");
+
+    if !is_safe_file_name(&jar_filename) {
+        return Err(format!("Invalid archive name '{}'", jar_filename));
+    }
+
+    let (from_dir, to_dir) = match to {
+        ModLocation::SaveLocal => (&global_dir, &local_dir),
+        ModLocation::Global => (&local_dir, &global_dir),
+    };
+
+    let source = from_dir.join(&jar_filename);
+    let dest = to_dir.join(&jar_filename);
+
+    if !is_mod_entry(&source) {
+        return Err(format!("Mod not found: {:?}", source));
+    }
+    if dest.exists() {
+        return Err(format!("{:?} already exists", dest));
+    }
+
+    let mod_keys = read_mod_keys(&source)?;
+    if mod_keys.is_empty() {
+        return Err(format!("manifest.json not found in {:?}", source));
+    }
+
+    if to == ModLocation::SaveLocal && !force.unwrap_or(false) {
+        let mut usage_index = build_save_usage_index(hytale_path);
+        let conflicts: Vec<String> = mod_keys
+            .iter()
+            .filter_map(|mod_key| {
+                let others: Vec<String> = usage_index
+                    .remove(mod_key)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter(|u| u.enabled && Path::new(&u.save_path) != save_path)
+                    .map(|u| u.save_name)
+                    .collect();
+                (!others.is_empty())
+                    .then(|| format!("{} is also enabled in: {}", mod_key, others.join(", ")))
+            })
+            .collect();
+
+        if !conflicts.is_empty() {
+            return Err(conflicts.join("; "));
+        }
+    }
+
+    fs::create_dir_all(to_dir).map_err(|e| format!("Failed to create mods dir: {}", e))?;
+
+    log!("Moving {:?} -> {:?}", source, dest);
+    if fs::rename(&source, &dest).is_err() {
+        if source.is_dir() {
+            copy_dir_recursive(&source, &dest)?;
+        } else {
+            fs::copy(&source, &dest).map_err(|e| format!("Failed to copy mod archive: {}", e))?;
+        }
+        remove_mod_entry(&source)?;
+    }
+
+    let mut from_metadata = read_orbis_metadata(from_dir);
+    if let Some(entry) = from_metadata.remove(&jar_filename) {
+        let mut to_metadata = read_orbis_metadata(to_dir);
+        to_metadata.insert(jar_filename.clone(), entry);
+        write_orbis_metadata(to_dir, &to_metadata)?;
+        write_orbis_metadata(from_dir, &from_metadata)?;
+    }
+
+    Ok(dest.to_string_lossy().to_string())
+}
+
 /// Represents a global mod (no config.json, so no enabled state)
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct GlobalMod {
     pub jar_name: String,
     pub manifest: ModManifest,
     pub orbis_metadata: Option<OrbisMetadataEntry>,
+    /// Saves whose config.json lists this mod, enabled or not
+    #[serde(default)]
+    pub used_by: Vec<ModSaveUsage>,
+    /// A directory with manifest.json at its root rather than an archive
+    #[serde(default)]
+    pub unpacked: bool,
+    /// Workarounds needed to read the manifest; empty for a well-formed mod
+    #[serde(default)]
+    pub manifest_fallbacks: Vec<ManifestFallback>,
+    #[serde(default)]
+    pub resource_type: ResourceType,
+}
+
+/// A save whose config.json references a mod
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ModSaveUsage {
+    pub save_name: String,
+    pub save_path: String,
+    pub enabled: bool,
+}
+
+/// For every "Group:Name" referenced by a save's config.json, the saves referencing it
+pub(crate) fn build_save_usage_index(hytale_root: &Path) -> HashMap<String, Vec<ModSaveUsage>> {
+    let mut index: HashMap<String, Vec<ModSaveUsage>> = HashMap::new();
+    let saves_dir = hytale_root.join("UserData").join("Saves");
+
+    let entries = match fs::read_dir(&saves_dir) {
+        Ok(e) => e,
+        Err(_) => return index,
+    };
+
+    for entry in entries.flatten() {
+        let save_path = entry.path();
+        if !save_path.is_dir() {
+            continue;
+        }
+
+        let config = match read_mod_config(&save_path) {
+            Ok(c) => c,
+            Err(e) => {
+                eprintln!("Skipping save {:?} in usage index: {}", save_path, e);
+                continue;
+            }
+        };
+
+        let save_name = entry.file_name().to_string_lossy().to_string();
+        for (key, mod_entry) in config.mods {
+            index.entry(key).or_default().push(ModSaveUsage {
+                save_name: save_name.clone(),
+                save_path: save_path.to_string_lossy().to_string(),
+                enabled: mod_entry.enabled,
+            });
+        }
+    }
+
+    for usages in index.values_mut() {
+        usages.sort_by(|a, b| a.save_name.cmp(&b.save_name));
+    }
+
+    index
 }
 
 #[tauri::command]
@@ -389,10 +535,10 @@ pub fn get_global_mods(hytale_root: String) -> Result<Vec<GlobalMod>, String> {
     let hytale_path = Path::new(&hytale_root);
     let global_mods_dir = hytale_path.join("UserData").join("Mods");
 
-    println!("get_global_mods: Scanning {:?}", global_mods_dir);
+    log!("get_global_mods: Scanning {:?}", global_mods_dir);
 
     if !global_mods_dir.exists() {
-        println!(
+        log!(
             "Global mods directory does not exist: {:?}",
             global_mods_dir
         );
@@ -401,6 +547,7 @@ pub fn get_global_mods(hytale_root: String) -> Result<Vec<GlobalMod>, String> {
 
     // Read orbis metadata
     let orbis_metadata = read_orbis_metadata(&global_mods_dir);
+    let save_usage = build_save_usage_index(hytale_path);
 
     let mut global_mods = Vec::new();
 
@@ -411,23 +558,30 @@ pub fn get_global_mods(hytale_root: String) -> Result<Vec<GlobalMod>, String> {
         let entry = entry.map_err(|e| format!("Failed to read directory entry: {}", e))?;
         let path = entry.path();
 
-        if is_mod_archive(&path) {
-            match extract_manifest_from_archive(&path) {
-                Ok(manifest) => {
-                    println!("Found global mod: {}", manifest.name);
-                    let file_name = path
-                        .file_name()
-                        .and_then(|s| s.to_str())
-                        .unwrap_or("unknown")
-                        .to_string();
-
-                    let orbis_meta = orbis_metadata.get(&file_name).cloned();
-
-                    global_mods.push(GlobalMod {
-                        jar_name: file_name, // Keep field name for backward compatibility
-                        manifest,
-                        orbis_metadata: orbis_meta,
-                    });
+        if is_mod_entry(&path) {
+            // Archives bundling several mods yield one GlobalMod per manifest
+            match discover_manifests(&path) {
+                Ok(manifests) => {
+                    for discovered in manifests {
+                        discovered.log_fallbacks(&path);
+                        let manifest = discovered.manifest;
+                        log!("Found global mod: {}", manifest.name);
+                        let file_name = archive_file_name(&path);
+
+                        let orbis_meta = orbis_metadata.get(&file_name).cloned();
+                        let mod_key = format!("{}:{}", manifest.group, manifest.name);
+                        let used_by = save_usage.get(&mod_key).cloned().unwrap_or_default();
+
+                        global_mods.push(GlobalMod {
+                            jar_name: file_name, // Keep field name for backward compatibility
+                            manifest,
+                            orbis_metadata: orbis_meta,
+                            used_by,
+                            unpacked: path.is_dir(),
+                            manifest_fallbacks: discovered.fallbacks,
+                            resource_type: discovered.resource_type,
+                        });
+                    }
                 }
                 Err(e) => {
                     eprintln!("Failed to extract manifest from {:?}: {}", path, e);
@@ -436,181 +590,101 @@ pub fn get_global_mods(hytale_root: String) -> Result<Vec<GlobalMod>, String> {
         }
     }
 
-    println!("Found {} global mods", global_mods.len());
+    log!("Found {} global mods", global_mods.len());
     Ok(global_mods)
 }
 
+/// Delete an archive from UserData/Mods.
+///
+/// Refuses while a save still has the mod enabled, unless `remove_from_saves`
+/// is set (drop the mod's entry from every save's config.json that lists it)
+/// or `force` is set (delete anyway and leave the configs alone). A mod that
+/// another archive in UserData/Mods also provides is never considered in use.
 #[tauri::command]
-pub fn delete_global_mod(hytale_root: String, jar_filename: String) -> Result<(), String> {
+pub fn delete_global_mod(
+    hytale_root: String,
+    jar_filename: String,
+    remove_from_saves: Option<bool>,
+    force: Option<bool>,
+) -> Result<Vec<String>, String> {
     let hytale_path = Path::new(&hytale_root);
     let global_mods_dir = hytale_path.join("UserData").join("Mods");
-    let jar_path = global_mods_dir.join(&jar_filename);
 
-    if jar_path.exists() {
-        fs::remove_file(&jar_path)
-            .map_err(|e| format!("Failed to delete global mod file: {}", e))?;
+    if !is_safe_file_name(&jar_filename) {
+        return Err(format!("Invalid archive name '{}'", jar_filename));
     }
 
-    Ok(())
-}
-
-/// Install a modpack from a downloaded zip file
-/// - Extracts Mods/ contents to UserData/Mods
-/// - Extracts and unpacks Configs/*.zip to save_path/mods
-/// - Updates config.json to enable installed mods
-#[tauri::command]
-pub fn install_modpack(
-    modpack_zip_path: String,
-    save_path: String,
-    hytale_root: String,
-) -> Result<(), String> {
-    use std::io::copy;
-
-    let modpack_path = Path::new(&modpack_zip_path);
-    let save_path = Path::new(&save_path);
-    let hytale_path = Path::new(&hytale_root);
+    let jar_path = global_mods_dir.join(&jar_filename);
 
-    let global_mods_dir = hytale_path.join("UserData").join("Mods");
-    let save_mods_dir = save_path.join("mods");
+    let mut updated_saves = Vec::new();
 
-    // Ensure directories exist
-    if !global_mods_dir.exists() {
-        fs::create_dir_all(&global_mods_dir)
-            .map_err(|e| format!("Failed to create global mods directory: {}", e))?;
+    if !jar_path.exists() {
+        return Ok(updated_saves);
     }
-    if !save_mods_dir.exists() {
-        fs::create_dir_all(&save_mods_dir)
-            .map_err(|e| format!("Failed to create save mods directory: {}", e))?;
+    if !is_mod_entry(&jar_path) {
+        return Err(format!("Not a mod archive or mod directory: {:?}", jar_path));
     }
 
-    // Open the modpack zip
-    let file = File::open(modpack_path)
-        .map_err(|e| format!("Failed to open modpack zip: {}", e))?;
-    let mut archive = ZipArchive::new(file)
-        .map_err(|e| format!("Failed to read modpack zip: {}", e))?;
-
-    println!("Installing modpack from {:?}", modpack_path);
-
-    let mut installed_manifests: Vec<ModManifest> = Vec::new();
-
-    // Process each file in the archive
-    for i in 0..archive.len() {
-        let mut zip_file = archive.by_index(i)
-            .map_err(|e| format!("Failed to read zip entry: {}", e))?;
-
-        let name = zip_file.name().to_string();
-
-        // Skip directories
-        if name.ends_with('/') {
-            continue;
-        }
-
-        // Handle Mods/ directory - copy to global mods
-        if name.starts_with("Mods/") {
-            let file_name = name.strip_prefix("Mods/").unwrap_or(&name);
-            if file_name.is_empty() {
-                continue;
-            }
-
-            let dest_path = global_mods_dir.join(file_name);
-            println!("Extracting mod: {} -> {:?}", file_name, dest_path);
-
-            // Create parent directories if needed
-            if let Some(parent) = dest_path.parent() {
-                fs::create_dir_all(parent)
-                    .map_err(|e| format!("Failed to create directory: {}", e))?;
-            }
-
-            let mut outfile = File::create(&dest_path)
-                .map_err(|e| format!("Failed to create file {:?}: {}", dest_path, e))?;
-            copy(&mut zip_file, &mut outfile)
-                .map_err(|e| format!("Failed to write file {:?}: {}", dest_path, e))?;
-
-            // Try to extract manifest from the installed mod
-            println!("Checking if extracted file is a mod archive: {:?}", dest_path);
-            if is_mod_archive(&dest_path) {
-                 println!("Attempting to extract manifest from: {:?}", dest_path);
-                 match extract_manifest_from_archive(&dest_path) {
-                    Ok(manifest) => {
-                        println!("Found manifest for mod: {}", manifest.name);
-                        installed_manifests.push(manifest);
-                    }
-                    Err(e) => {
-                        println!("Warning: Failed to extract manifest from extracted mod {:?}: {}", dest_path, e);
-                    }
-                }
-            } else {
-                println!("File is not considered a mod archive (extension check failed)");
-            }
-        }
-        // Handle Configs/ directory - extract zip files to save mods dir
-        else if name.starts_with("Configs/") && name.ends_with(".zip") {
-            let file_name = name.strip_prefix("Configs/").unwrap_or(&name);
-            if file_name.is_empty() {
-                continue;
-            }
-
-            println!("Processing config zip: {}", file_name);
-
-            // Read the config zip into memory
-            let mut config_data = Vec::new();
-            std::io::Read::read_to_end(&mut zip_file, &mut config_data)
-                .map_err(|e| format!("Failed to read config zip {}: {}", file_name, e))?;
-
-            // Open the nested config zip
-            let cursor = std::io::Cursor::new(config_data);
-            let mut config_archive = ZipArchive::new(cursor)
-                .map_err(|e| format!("Failed to read config archive {}: {}", file_name, e))?;
-
-            // Extract all files from config zip to save mods dir
-            for j in 0..config_archive.len() {
-                let mut config_file = config_archive.by_index(j)
-                    .map_err(|e| format!("Failed to read config entry: {}", e))?;
-
-                let config_name = config_file.name().to_string();
-                if config_name.ends_with('/') {
-                    continue;
-                }
-
-                let dest_path = save_mods_dir.join(&config_name);
-                println!("Extracting config: {} -> {:?}", config_name, dest_path);
+    let mod_keys = read_mod_keys(&jar_path).unwrap_or_default();
+
+    // Another archive with the same Group:Name keeps that mod available
+    let provided_elsewhere: HashSet<String> = fs::read_dir(&global_mods_dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|e| e.path())
+                .filter(|path| *path != jar_path && is_mod_entry(path))
+                .flat_map(|path| read_mod_keys(&path).unwrap_or_default())
+                .collect()
+        })
+        .unwrap_or_default();
+
+    // Every save using any mod of the archive, with the keys it uses
+    let mut usage_index = build_save_usage_index(hytale_path);
+    let mut usages: Vec<(String, ModSaveUsage)> = mod_keys
+        .iter()
+        .filter(|key| !provided_elsewhere.contains(*key))
+        .flat_map(|key| {
+            usage_index
+                .remove(key)
+                .unwrap_or_default()
+                .into_iter()
+                .map(move |usage| (key.clone(), usage))
+        })
+        .collect();
+    usages.sort_by(|a, b| a.1.save_name.cmp(&b.1.save_name));
+
+    let mut enabled_in: Vec<&str> = usages
+        .iter()
+        .filter(|(_, u)| u.enabled)
+        .map(|(_, u)| u.save_name.as_str())
+        .collect();
+    enabled_in.dedup();
+
+    let remove_from_saves = remove_from_saves.unwrap_or(false);
+    if !enabled_in.is_empty() && !remove_from_saves && !force.unwrap_or(false) {
+        return Err(format!(
+            "{} is still enabled in: {}",
+            jar_filename,
+            enabled_in.join(", ")
+        ));
+    }
 
-                // Create parent directories if needed
-                if let Some(parent) = dest_path.parent() {
-                    fs::create_dir_all(parent)
-                        .map_err(|e| format!("Failed to create directory: {}", e))?;
+    remove_mod_entry(&jar_path)?;
+
+    if remove_from_saves {
+        for (key, usage) in &usages {
+            let save_path = Path::new(&usage.save_path);
+            let mut config = read_mod_config(save_path)?;
+            if config.mods.remove(key).is_some() {
+                log!("Removed {} from {:?}", key, save_path);
+                write_mod_config(save_path, &config)?;
+                if !updated_saves.contains(&usage.save_path) {
+                    updated_saves.push(usage.save_path.clone());
                 }
-
-                let mut outfile = File::create(&dest_path)
-                    .map_err(|e| format!("Failed to create file {:?}: {}", dest_path, e))?;
-                copy(&mut config_file, &mut outfile)
-                    .map_err(|e| format!("Failed to write file {:?}: {}", dest_path, e))?;
             }
         }
     }
 
-    // Update config.json with installed mods
-    if !installed_manifests.is_empty() {
-        println!("Updating config.json with {} installed mods", installed_manifests.len());
-        let mut config = read_mod_config(save_path)?;
-        
-        for manifest in installed_manifests {
-            let mod_key = format!("{}:{}", manifest.group, manifest.name);
-            println!("Enabling mod in config: {}", mod_key);
-            config.mods.insert(mod_key, ModConfigEntry { enabled: true });
-        }
-
-        write_mod_config(save_path, &config)?;
-        println!("Successfully updated config.json");
-    } else {
-        println!("No manifests found, skipping config.json update");
-    }
-
-    // Clean up the downloaded modpack zip
-    if let Err(e) = fs::remove_file(modpack_path) {
-        eprintln!("Warning: Failed to clean up modpack zip: {}", e);
-    }
-
-    println!("Modpack installation complete");
-    Ok(())
+    Ok(updated_saves)
 }
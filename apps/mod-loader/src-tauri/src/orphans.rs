@@ -0,0 +1,348 @@
+use crate::modpack::{collect_config_files, config_prefix_for_key, is_safe_file_name};
+use crate::mods::{
+    build_global_mods_index, build_save_usage_index, is_mod_entry, read_mod_config,
+    read_mod_manifest, read_orbis_metadata, remove_mod_entry, write_mod_config,
+    write_orbis_metadata,
+};
+use crate::saves::{dir_size_and_mtime, invalidate_save_stats};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// An archive or unpacked mod in UserData/Mods that no save enables
+#[derive(Debug, Serialize, Clone)]
+pub struct OrphanArchive {
+    pub file_name: String,
+    /// None if the archive has no readable manifest
+    pub mod_key: Option<String>,
+    pub size_bytes: u64,
+    /// Saves that list the mod but have it disabled
+    pub disabled_in: Vec<String>,
+    /// A directory mod, usually a modder's working copy. Only removed when
+    /// the selection opts in with `include_unpacked`.
+    pub unpacked: bool,
+}
+
+/// A config.json entry with no archive in UserData/Mods or the save's own mods/ dir
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Hash)]
+pub struct DanglingConfigEntry {
+    pub save_path: String,
+    pub mod_key: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct DanglingConfig {
+    pub save_name: String,
+    pub entry: DanglingConfigEntry,
+    pub enabled: bool,
+    /// Size of the mod's config files in the save's mods/ dir
+    pub config_bytes: u64,
+}
+
+/// An orbis-metadata.json entry for a file that no longer exists
+#[derive(Debug, Serialize, Clone)]
+pub struct StaleMetadata {
+    pub file_name: String,
+    pub name: String,
+}
+
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct OrphanReport {
+    pub unused_archives: Vec<OrphanArchive>,
+    pub dangling_config_entries: Vec<DanglingConfig>,
+    pub stale_metadata: Vec<StaleMetadata>,
+    /// Bytes freed by cleaning everything in the report
+    pub total_bytes: u64,
+}
+
+fn scan_orphans(hytale_path: &Path) -> Result<OrphanReport, String> {
+    let global_mods_dir = hytale_path.join("UserData").join("Mods");
+    let usage = build_save_usage_index(hytale_path);
+    let mut report = OrphanReport::default();
+
+    // Every Group:Name some archive provides, including duplicates
+    let mut provided: HashSet<String> = HashSet::new();
+    let mut archive_names: HashSet<String> = HashSet::new();
+
+    if let Ok(entries) = fs::read_dir(&global_mods_dir) {
+        let mut paths: Vec<PathBuf> = entries.flatten().map(|e| e.path()).collect();
+        paths.sort();
+
+        for path in paths {
+            if !is_mod_entry(&path) {
+                continue;
+            }
+
+            let file_name = match path.file_name().and_then(|s| s.to_str()) {
+                Some(name) => name.to_string(),
+                None => continue,
+            };
+            archive_names.insert(file_name.clone());
+
+            let mod_key = read_mod_manifest(&path)
+                .ok()
+                .map(|m| format!("{}:{}", m.group, m.name));
+            if let Some(key) = &mod_key {
+                provided.insert(key.clone());
+            }
+
+            let usages = mod_key.as_ref().and_then(|key| usage.get(key));
+            if usages.is_some_and(|u| u.iter().any(|u| u.enabled)) {
+                continue;
+            }
+
+            let size_bytes = if path.is_dir() {
+                dir_size_and_mtime(&path).0
+            } else {
+                fs::metadata(&path).map(|m| m.len()).unwrap_or(0)
+            };
+            report.total_bytes += size_bytes;
+            report.unused_archives.push(OrphanArchive {
+                file_name,
+                mod_key,
+                size_bytes,
+                disabled_in: usages
+                    .map(|u| u.iter().map(|u| u.save_name.clone()).collect())
+                    .unwrap_or_default(),
+                unpacked: path.is_dir(),
+            });
+        }
+    }
+
+    let mut dangling_keys: Vec<&String> = usage
+        .keys()
+        .filter(|key| !provided.contains(*key))
+        .collect();
+    dangling_keys.sort();
+
+    // Save-local archives only satisfy the save they live in
+    let mut local_keys: HashMap<String, HashSet<String>> = HashMap::new();
+
+    for key in dangling_keys {
+        for save in &usage[key] {
+            let provided_locally = local_keys
+                .entry(save.save_path.clone())
+                .or_insert_with(|| {
+                    build_global_mods_index(&Path::new(&save.save_path).join("mods"))
+                        .into_keys()
+                        .collect()
+                })
+                .contains(key);
+            if provided_locally {
+                continue;
+            }
+
+            let config_bytes: u64 = collect_config_files(
+                &Path::new(&save.save_path).join("mods"),
+                &config_prefix_for_key(key),
+            )
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|(path, _)| fs::metadata(path).ok())
+            .map(|m| m.len())
+            .sum();
+
+            report.total_bytes += config_bytes;
+            report.dangling_config_entries.push(DanglingConfig {
+                save_name: save.save_name.clone(),
+                entry: DanglingConfigEntry {
+                    save_path: save.save_path.clone(),
+                    mod_key: key.clone(),
+                },
+                enabled: save.enabled,
+                config_bytes,
+            });
+        }
+    }
+
+    let mut stale: Vec<StaleMetadata> = read_orbis_metadata(&global_mods_dir)
+        .into_iter()
+        .filter(|(file_name, _)| !archive_names.contains(file_name))
+        .map(|(file_name, entry)| StaleMetadata {
+            file_name,
+            name: entry.name,
+        })
+        .collect();
+    stale.sort_by(|a, b| a.file_name.cmp(&b.file_name));
+    report.stale_metadata = stale;
+
+    Ok(report)
+}
+
+/// Report unused archives, config.json entries without an archive, and
+/// orbis-metadata.json entries for deleted files
+#[tauri::command]
+pub fn find_orphans(hytale_root: String) -> Result<OrphanReport, String> {
+    let report = scan_orphans(Path::new(&hytale_root))?;
+
+    log!(
+        "Found {} unused archives, {} dangling config entries, {} stale metadata entries ({} bytes)",
+        report.unused_archives.len(),
+        report.dangling_config_entries.len(),
+        report.stale_metadata.len(),
+        report.total_bytes
+    );
+
+    Ok(report)
+}
+
+/// Items from an `OrphanReport` to remove
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct OrphanSelection {
+    #[serde(default)]
+    pub archives: Vec<String>,
+    #[serde(default)]
+    pub config_entries: Vec<DanglingConfigEntry>,
+    #[serde(default)]
+    pub metadata: Vec<String>,
+    /// Also delete the dangling mods' config files from the saves' mods/ dirs
+    #[serde(default)]
+    pub remove_config_files: bool,
+    /// Also delete selected unpacked mod directories; without it they are skipped
+    #[serde(default)]
+    pub include_unpacked: bool,
+}
+
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct CleanSummary {
+    pub removed_archives: Vec<String>,
+    pub removed_config_entries: Vec<DanglingConfigEntry>,
+    pub removed_metadata: Vec<String>,
+    pub freed_bytes: u64,
+    /// Selected items that are no longer orphaned, and were left alone
+    pub skipped: Vec<String>,
+}
+
+/// Remove the selected orphans. The scan is redone first and anything that
+/// stopped being an orphan since `find_orphans` (e.g. a save enabled the mod
+/// again) is skipped, as are unpacked mod directories unless `include_unpacked` is set.
+#[tauri::command]
+pub fn clean_orphans(
+    hytale_root: String,
+    selection: OrphanSelection,
+) -> Result<CleanSummary, String> {
+    let hytale_path = Path::new(&hytale_root);
+    let global_mods_dir = hytale_path.join("UserData").join("Mods");
+    let report = scan_orphans(hytale_path)?;
+    let mut summary = CleanSummary::default();
+
+    let unused: HashMap<&str, &OrphanArchive> = report
+        .unused_archives
+        .iter()
+        .map(|a| (a.file_name.as_str(), a))
+        .collect();
+
+    for file_name in &selection.archives {
+        let archive = match unused.get(file_name.as_str()) {
+            Some(archive) if is_safe_file_name(file_name) => archive,
+            _ => {
+                summary.skipped.push(file_name.clone());
+                continue;
+            }
+        };
+
+        // Unpacked mods are usually a modder's working copy, never delete them implicitly
+        if archive.unpacked && !selection.include_unpacked {
+            log!("Keeping unpacked mod {}", file_name);
+            summary.skipped.push(file_name.clone());
+            continue;
+        }
+
+        log!("Removing unused archive {}", file_name);
+        remove_mod_entry(&global_mods_dir.join(file_name))?;
+        summary.freed_bytes += archive.size_bytes;
+        summary.removed_archives.push(file_name.clone());
+    }
+
+    let dangling: HashSet<&DanglingConfigEntry> = report
+        .dangling_config_entries
+        .iter()
+        .map(|d| &d.entry)
+        .collect();
+
+    let mut by_save: HashMap<&str, Vec<&DanglingConfigEntry>> = HashMap::new();
+    for entry in &selection.config_entries {
+        if dangling.contains(entry) {
+            by_save
+                .entry(entry.save_path.as_str())
+                .or_default()
+                .push(entry);
+        } else {
+            summary
+                .skipped
+                .push(format!("{} in {}", entry.mod_key, entry.save_path));
+        }
+    }
+
+    for (save_path, entries) in by_save {
+        let save_path = Path::new(save_path);
+        let mut config = read_mod_config(save_path)?;
+
+        for entry in entries {
+            config.mods.remove(&entry.mod_key);
+
+            if selection.remove_config_files {
+                let files = collect_config_files(
+                    &save_path.join("mods"),
+                    &config_prefix_for_key(&entry.mod_key),
+                )?;
+                for (path, _) in files {
+                    summary.freed_bytes += fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+                    fs::remove_file(&path)
+                        .map_err(|e| format!("Failed to remove {:?}: {}", path, e))?;
+                }
+
+                let config_dir = save_path
+                    .join("mods")
+                    .join(config_prefix_for_key(&entry.mod_key));
+                if config_dir.is_dir() {
+                    fs::remove_dir_all(&config_dir)
+                        .map_err(|e| format!("Failed to remove {:?}: {}", config_dir, e))?;
+                }
+            }
+
+            log!("Removing {} from {:?}", entry.mod_key, save_path);
+            summary.removed_config_entries.push(entry.clone());
+        }
+
+        write_mod_config(save_path, &config)?;
+        invalidate_save_stats(save_path);
+    }
+
+    let stale: HashSet<&str> = report
+        .stale_metadata
+        .iter()
+        .map(|m| m.file_name.as_str())
+        .collect();
+    let mut metadata = read_orbis_metadata(&global_mods_dir);
+
+    for file_name in &selection.metadata {
+        if stale.contains(file_name.as_str()) && metadata.remove(file_name).is_some() {
+            summary.removed_metadata.push(file_name.clone());
+        } else {
+            summary.skipped.push(file_name.clone());
+        }
+    }
+
+    // Metadata of the archives just removed would be stale right away
+    for file_name in &summary.removed_archives {
+        if metadata.remove(file_name).is_some() {
+            summary.removed_metadata.push(file_name.clone());
+        }
+    }
+
+    if !summary.removed_metadata.is_empty() {
+        write_orbis_metadata(&global_mods_dir, &metadata)?;
+    }
+
+    log!(
+        "Cleaned {} archives, {} config entries, {} metadata entries, freed {} bytes",
+        summary.removed_archives.len(),
+        summary.removed_config_entries.len(),
+        summary.removed_metadata.len(),
+        summary.freed_bytes
+    );
+
+    Ok(summary)
+}
@@ -0,0 +1,148 @@
+use crate::loader_core::lockfile::sha256_reader;
+use crate::state::loader_subdir;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const CACHE_DIR: &str = "modpack-cache";
+const CACHE_INDEX_FILE: &str = "index.json";
+
+/// A modpack zip kept in the loader's cache, stored as `<sha256>.zip`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CachedModpack {
+    pub sha256: String,
+    pub modpack_name: String,
+    /// File name the pack was downloaded as
+    pub source_name: String,
+    pub path: String,
+    pub size_bytes: u64,
+    /// Milliseconds since the Unix epoch
+    pub cached_at: u64,
+}
+
+fn cache_dir() -> Result<PathBuf, String> {
+    loader_subdir(CACHE_DIR)
+}
+
+fn read_index(dir: &Path) -> HashMap<String, CachedModpack> {
+    fs::read_to_string(dir.join(CACHE_INDEX_FILE))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn write_index(dir: &Path, index: &HashMap<String, CachedModpack>) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(index)
+        .map_err(|e| format!("Failed to serialize modpack cache index: {}", e))?;
+    fs::write(dir.join(CACHE_INDEX_FILE), content)
+        .map_err(|e| format!("Failed to write modpack cache index: {}", e))
+}
+
+pub(crate) fn hash_file(path: &Path) -> Result<String, String> {
+    let mut file = File::open(path).map_err(|e| format!("Failed to open {:?}: {}", path, e))?;
+    sha256_reader(&mut file).map_err(|e| format!("Failed to hash {:?}: {}", path, e))
+}
+
+/// Cache entries are addressed by hex digest only, never by arbitrary paths
+fn cache_file_path(dir: &Path, sha256: &str) -> Result<PathBuf, String> {
+    if sha256.is_empty() || !sha256.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!("Invalid modpack hash: {}", sha256));
+    }
+    Ok(dir.join(format!("{}.zip", sha256)))
+}
+
+fn move_file(src: &Path, dst: &Path) -> Result<(), String> {
+    if fs::rename(src, dst).is_ok() {
+        return Ok(());
+    }
+
+    log!("Rename failed, copying {:?} to {:?}", src, dst);
+    fs::copy(src, dst).map_err(|e| format!("Failed to copy {:?}: {}", src, e))?;
+    fs::remove_file(src).map_err(|e| format!("Failed to remove {:?}: {}", src, e))
+}
+
+/// Move a downloaded modpack zip into the cache and return its cache entry.
+/// If the same content is already cached, the download is just removed.
+pub(crate) fn cache_downloaded_modpack(
+    path: &Path,
+    modpack_name: &str,
+) -> Result<CachedModpack, String> {
+    let dir = cache_dir()?;
+    let sha256 = hash_file(path)?;
+    let dest = cache_file_path(&dir, &sha256)?;
+    let mut index = read_index(&dir);
+
+    if dest.exists() {
+        log!("Modpack already cached as {:?}", dest);
+        if let Err(e) = fs::remove_file(path) {
+            eprintln!("Warning: Failed to clean up modpack zip: {}", e);
+        }
+    } else {
+        log!("Caching modpack {:?} as {:?}", path, dest);
+        move_file(path, &dest)?;
+    }
+
+    let size_bytes = fs::metadata(&dest).map(|m| m.len()).unwrap_or(0);
+    let cached_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0);
+
+    let entry = CachedModpack {
+        sha256: sha256.clone(),
+        modpack_name: modpack_name.to_string(),
+        source_name: path
+            .file_name()
+            .and_then(|s| s.to_str())
+            .unwrap_or_default()
+            .to_string(),
+        path: dest.to_string_lossy().to_string(),
+        size_bytes,
+        cached_at,
+    };
+
+    index.insert(sha256, entry.clone());
+    write_index(&dir, &index)?;
+
+    Ok(entry)
+}
+
+/// Path of a cached modpack zip by content hash, if it is still on disk
+pub(crate) fn cached_modpack_path(sha256: &str) -> Option<PathBuf> {
+    let path = cache_file_path(&cache_dir().ok()?, sha256).ok()?;
+    path.is_file().then_some(path)
+}
+
+#[tauri::command]
+pub fn list_cached_modpacks() -> Result<Vec<CachedModpack>, String> {
+    let dir = cache_dir()?;
+    let index = read_index(&dir);
+
+    let mut packs: Vec<CachedModpack> = index
+        .into_values()
+        .filter(|pack| Path::new(&pack.path).is_file())
+        .collect();
+
+    packs.sort_by_key(|pack| std::cmp::Reverse(pack.cached_at));
+    Ok(packs)
+}
+
+#[tauri::command]
+pub fn remove_cached_modpack(sha256: String) -> Result<(), String> {
+    let dir = cache_dir()?;
+    let mut index = read_index(&dir);
+
+    let path = cache_file_path(&dir, &sha256)?;
+    if path.exists() {
+        fs::remove_file(&path).map_err(|e| format!("Failed to remove cached modpack: {}", e))?;
+    }
+
+    if index.remove(&sha256).is_some() {
+        write_index(&dir, &index)?;
+    }
+
+    log!("Removed cached modpack {}", sha256);
+    Ok(())
+}
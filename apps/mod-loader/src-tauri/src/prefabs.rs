@@ -0,0 +1,273 @@
+use crate::mods::{is_mod_archive, read_orbis_metadata, write_orbis_metadata, OrbisMetadataEntry};
+use crate::pack_cache::hash_file;
+use crate::resources::{
+    entry_names, extract_archive, inspect, is_prefab_file, strip_folder, ResourceType,
+};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// Prefabs of one save (<save>/prefabs), or the shared ones in UserData/Prefabs
+pub(crate) fn prefabs_dir(hytale_root: &str, save_path: Option<&str>) -> PathBuf {
+    match save_path {
+        Some(save) => Path::new(save).join("prefabs"),
+        None => Path::new(hytale_root).join("UserData").join("Prefabs"),
+    }
+}
+
+/// A prefab path relative to the prefabs dir: plain components only, so it
+/// can't escape the dir, ending in a prefab extension
+fn safe_prefab_path(name: &str) -> Option<PathBuf> {
+    if name.is_empty() || name.contains(['\\', ':']) || !is_prefab_file(name) {
+        return None;
+    }
+
+    let path = Path::new(name);
+    path.components()
+        .all(|c| matches!(c, Component::Normal(_)))
+        .then(|| path.to_path_buf())
+}
+
+/// `/`-separated path of a file relative to the prefabs dir, used as its metadata key
+fn relative_name(dir: &Path, path: &Path) -> Option<String> {
+    let relative = path.strip_prefix(dir).ok()?;
+    let parts: Vec<String> = relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().to_string())
+        .collect();
+    Some(parts.join("/"))
+}
+
+fn verify_hash(path: &Path, expected: &str) -> Result<(), String> {
+    let actual = hash_file(path)?;
+    if !actual.eq_ignore_ascii_case(expected.trim()) {
+        return Err(format!(
+            "Hash mismatch for {:?}: expected {}, got {}",
+            path, expected, actual
+        ));
+    }
+    Ok(())
+}
+
+/// A prefab or schematic file in a prefabs directory
+#[derive(Debug, Serialize, Clone)]
+pub struct PrefabFile {
+    /// Path relative to the prefabs directory, `/`-separated
+    pub file_name: String,
+    pub size_bytes: u64,
+    pub sha256: String,
+    pub orbis_metadata: Option<OrbisMetadataEntry>,
+    /// Whether the file still matches the hash recorded when it was installed
+    /// from Orbis; None if no hash was recorded
+    pub hash_verified: Option<bool>,
+}
+
+fn read_prefab_file(
+    dir: &Path,
+    file_name: &str,
+    metadata: &HashMap<String, OrbisMetadataEntry>,
+) -> Result<PrefabFile, String> {
+    let path = dir.join(file_name);
+    let sha256 = hash_file(&path)?;
+    let orbis_metadata = metadata.get(file_name).cloned();
+    let hash_verified = orbis_metadata
+        .as_ref()
+        .and_then(|m| m.sha256.as_ref())
+        .map(|recorded| recorded.eq_ignore_ascii_case(&sha256));
+
+    Ok(PrefabFile {
+        file_name: file_name.to_string(),
+        size_bytes: fs::metadata(&path).map(|m| m.len()).unwrap_or(0),
+        sha256,
+        orbis_metadata,
+        hash_verified,
+    })
+}
+
+fn collect_prefabs(dir: &Path, root: &Path, out: &mut Vec<String>) -> Result<(), String> {
+    let entries =
+        fs::read_dir(dir).map_err(|e| format!("Failed to read directory {:?}: {}", dir, e))?;
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_prefabs(&path, root, out)?;
+        } else if let Some(name) = relative_name(root, &path) {
+            if is_prefab_file(&name) {
+                out.push(name);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// List the prefabs of a save, or the shared ones when `save_path` is None
+#[tauri::command]
+pub fn list_prefabs(
+    hytale_root: String,
+    save_path: Option<String>,
+) -> Result<Vec<PrefabFile>, String> {
+    let dir = prefabs_dir(&hytale_root, save_path.as_deref());
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut names = Vec::new();
+    collect_prefabs(&dir, &dir, &mut names)?;
+    names.sort();
+
+    let metadata = read_orbis_metadata(&dir);
+    names
+        .iter()
+        .map(|name| read_prefab_file(&dir, name, &metadata))
+        .collect()
+}
+
+/// Extract the prefab files of a PREFAB archive into `dir`. Refuses to
+/// replace existing files unless `overwrite` is set.
+pub(crate) fn install_prefab_archive(
+    archive: &Path,
+    dir: &Path,
+    overwrite: bool,
+) -> Result<Vec<String>, String> {
+    let inspection = inspect(archive)?;
+    if inspection.resource_type != ResourceType::Prefab {
+        return Err(format!(
+            "{:?} is a {:?}, not a prefab",
+            archive, inspection.resource_type
+        ));
+    }
+
+    let wrapper = inspection.wrapper_folder.as_deref();
+    if !overwrite {
+        let existing: Vec<String> = strip_folder(&entry_names(archive)?, wrapper)
+            .into_iter()
+            .filter(|name| safe_prefab_path(name).is_some_and(|p| dir.join(p).exists()))
+            .collect();
+        if !existing.is_empty() {
+            return Err(format!("Prefabs already exist: {}", existing.join(", ")));
+        }
+    }
+
+    let written = extract_archive(archive, dir, wrapper, |relative| {
+        safe_prefab_path(&relative.to_string_lossy()).is_some()
+    })?;
+    log!("Extracted {} prefab files to {:?}", written.len(), dir);
+
+    Ok(written
+        .iter()
+        .filter_map(|path| relative_name(dir, path))
+        .collect())
+}
+
+/// Install a prefab or schematic file, or every prefab in a PREFAB archive,
+/// into a save's prefabs or the shared UserData/Prefabs.
+///
+/// With `expected_sha256` the download is checked before anything is
+/// written. With `orbis_metadata` each installed file is tracked in the
+/// prefabs dir's orbis-metadata.json along with its hash, so `list_prefabs`
+/// can tell when it was changed afterwards.
+#[tauri::command]
+pub fn install_prefab(
+    file_path: String,
+    hytale_root: String,
+    save_path: Option<String>,
+    expected_sha256: Option<String>,
+    orbis_metadata: Option<OrbisMetadataEntry>,
+    overwrite: Option<bool>,
+) -> Result<Vec<PrefabFile>, String> {
+    let source = Path::new(&file_path);
+    if !source.is_file() {
+        return Err(format!("Prefab file not found: {}", file_path));
+    }
+
+    if let Some(expected) = &expected_sha256 {
+        verify_hash(source, expected)?;
+    }
+
+    let dir = prefabs_dir(&hytale_root, save_path.as_deref());
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create prefabs directory: {}", e))?;
+    let overwrite = overwrite.unwrap_or(false);
+
+    let installed = if is_mod_archive(source) {
+        install_prefab_archive(source, &dir, overwrite)?
+    } else {
+        let file_name = source
+            .file_name()
+            .and_then(|s| s.to_str())
+            .ok_or("Invalid prefab file name")?;
+        if safe_prefab_path(file_name).is_none() {
+            return Err(format!(
+                "{} is not a prefab (.prefab.json) or schematic (.schematic) file",
+                file_name
+            ));
+        }
+
+        let dest = dir.join(file_name);
+        if dest.exists() && !overwrite {
+            return Err(format!("Prefab '{}' already exists", file_name));
+        }
+
+        log!("Copying prefab {:?} -> {:?}", source, dest);
+        fs::copy(source, &dest).map_err(|e| format!("Failed to copy prefab: {}", e))?;
+        vec![file_name.to_string()]
+    };
+
+    // Replaced files drop any metadata that described their previous contents
+    let mut metadata = read_orbis_metadata(&dir);
+    for name in &installed {
+        match &orbis_metadata {
+            Some(entry) => {
+                let mut entry = entry.clone();
+                entry.sha256 = Some(hash_file(&dir.join(name))?);
+                metadata.insert(name.clone(), entry);
+            }
+            None => {
+                metadata.remove(name);
+            }
+        }
+    }
+    write_orbis_metadata(&dir, &metadata)?;
+
+    installed
+        .iter()
+        .map(|name| read_prefab_file(&dir, name, &metadata))
+        .collect()
+}
+
+/// Delete a prefab and its Orbis metadata, and any folders it leaves empty
+#[tauri::command]
+pub fn remove_prefab(
+    hytale_root: String,
+    save_path: Option<String>,
+    file_name: String,
+) -> Result<(), String> {
+    let dir = prefabs_dir(&hytale_root, save_path.as_deref());
+    let relative = safe_prefab_path(&file_name)
+        .ok_or_else(|| format!("Invalid prefab path: {}", file_name))?;
+
+    let path = dir.join(&relative);
+    if !path.is_file() {
+        return Err(format!("Prefab '{}' not found", file_name));
+    }
+
+    log!("Removing prefab {:?}", path);
+    fs::remove_file(&path).map_err(|e| format!("Failed to remove prefab: {}", e))?;
+
+    let mut parent = path.parent();
+    while let Some(folder) = parent.filter(|p| *p != dir) {
+        if fs::remove_dir(folder).is_err() {
+            break;
+        }
+        parent = folder.parent();
+    }
+
+    let mut metadata = read_orbis_metadata(&dir);
+    if metadata.remove(&file_name).is_some() {
+        write_orbis_metadata(&dir, &metadata)?;
+    }
+
+    Ok(())
+}
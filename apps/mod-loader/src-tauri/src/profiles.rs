@@ -0,0 +1,325 @@
+use crate::mods::{build_mods_index, read_mod_config, write_mod_config, ModConfigEntry};
+use crate::saves::invalidate_save_stats;
+use crate::state::loader_data_dir;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const PROFILES_FILE: &str = "profiles.json";
+
+/// One mod in a profile
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ProfileMod {
+    /// "Group:Name"
+    pub key: String,
+    /// Version the mod was pinned at, from its manifest
+    pub version: Option<String>,
+    pub enabled: bool,
+}
+
+/// A named, reusable set of mods
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ModProfile {
+    pub name: String,
+    /// Sorted by key
+    pub mods: Vec<ProfileMod>,
+    /// Milliseconds since the Unix epoch
+    pub created_at: u64,
+    pub updated_at: u64,
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+fn profiles_path() -> Result<PathBuf, String> {
+    Ok(loader_data_dir()?.join(PROFILES_FILE))
+}
+
+fn read_profiles() -> Result<BTreeMap<String, ModProfile>, String> {
+    let path = profiles_path()?;
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+
+    let contents =
+        fs::read_to_string(&path).map_err(|e| format!("Failed to read profiles: {}", e))?;
+    serde_json::from_str(&contents).map_err(|e| format!("Failed to parse profiles: {}", e))
+}
+
+fn write_profiles(profiles: &BTreeMap<String, ModProfile>) -> Result<(), String> {
+    let contents = serde_json::to_string_pretty(profiles)
+        .map_err(|e| format!("Failed to serialize profiles: {}", e))?;
+    fs::write(profiles_path()?, contents).map_err(|e| format!("Failed to write profiles: {}", e))
+}
+
+pub(crate) fn find_profile(name: &str) -> Result<ModProfile, String> {
+    read_profiles()?
+        .remove(name)
+        .ok_or_else(|| format!("Profile '{}' not found", name))
+}
+
+fn global_mods_dir(hytale_root: &str) -> PathBuf {
+    Path::new(hytale_root).join("UserData").join("Mods")
+}
+
+/// Installed version of every mod a save can see, by "Group:Name"
+fn installed_versions(save_path: &Path, hytale_root: &str) -> HashMap<String, String> {
+    build_mods_index(save_path, &global_mods_dir(hytale_root))
+        .into_iter()
+        .map(|(key, resolved)| (key, resolved.manifest.version))
+        .collect()
+}
+
+#[tauri::command]
+pub fn list_profiles() -> Result<Vec<ModProfile>, String> {
+    Ok(read_profiles()?.into_values().collect())
+}
+
+/// Create (or, with `overwrite`, replace) a profile from a save's config.json,
+/// pinning each mod at the version of the archive the save currently resolves it to
+#[tauri::command]
+pub fn create_profile_from_save(
+    name: String,
+    save_path: String,
+    hytale_root: String,
+    overwrite: Option<bool>,
+) -> Result<ModProfile, String> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("Profile name must not be empty".to_string());
+    }
+
+    let mut profiles = read_profiles()?;
+    let existing = profiles.get(&name);
+    if existing.is_some() && !overwrite.unwrap_or(false) {
+        return Err(format!("Profile '{}' already exists", name));
+    }
+
+    let config = read_mod_config(Path::new(&save_path))?;
+    let versions = installed_versions(Path::new(&save_path), &hytale_root);
+
+    let mut mods: Vec<ProfileMod> = config
+        .mods
+        .iter()
+        .map(|(key, entry)| ProfileMod {
+            key: key.clone(),
+            version: versions.get(key).cloned(),
+            enabled: entry.enabled,
+        })
+        .collect();
+    mods.sort_by(|a, b| a.key.cmp(&b.key));
+
+    let now = now_millis();
+    let profile = ModProfile {
+        name: name.clone(),
+        mods,
+        created_at: existing.map(|p| p.created_at).unwrap_or(now),
+        updated_at: now,
+    };
+
+    log!(
+        "Saving profile '{}' with {} mods from {}",
+        name,
+        profile.mods.len(),
+        save_path
+    );
+    profiles.insert(name, profile.clone());
+    write_profiles(&profiles)?;
+
+    Ok(profile)
+}
+
+#[tauri::command]
+pub fn delete_profile(name: String) -> Result<(), String> {
+    let mut profiles = read_profiles()?;
+    if profiles.remove(&name).is_none() {
+        return Err(format!("Profile '{}' not found", name));
+    }
+
+    write_profiles(&profiles)
+}
+
+/// A pinned mod whose installed version is different or missing
+#[derive(Debug, Serialize, Clone)]
+pub struct VersionMismatch {
+    pub key: String,
+    pub pinned: Option<String>,
+    /// None when the save can't see an archive for the mod
+    pub installed: Option<String>,
+}
+
+fn version_mismatches(
+    profile: &ModProfile,
+    versions: &HashMap<String, String>,
+) -> Vec<VersionMismatch> {
+    profile
+        .mods
+        .iter()
+        .filter(|m| m.enabled)
+        .filter_map(|m| {
+            let installed = versions.get(&m.key);
+            let matches = match (&m.version, installed) {
+                (_, None) => false,
+                (Some(pinned), Some(installed)) => pinned == installed,
+                (None, Some(_)) => true,
+            };
+
+            (!matches).then(|| VersionMismatch {
+                key: m.key.clone(),
+                pinned: m.version.clone(),
+                installed: installed.cloned(),
+            })
+        })
+        .collect()
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct AppliedProfile {
+    pub save_path: String,
+    /// Keys whose entry was added or changed
+    pub changed: Vec<String>,
+    /// Keys not in the profile that were disabled
+    pub disabled: Vec<String>,
+    pub version_mismatches: Vec<VersionMismatch>,
+}
+
+/// Rewrite the Mods entries of each save to match the profile. Mods the
+/// profile doesn't list are disabled rather than removed, so their entries
+/// survive switching profiles back and forth.
+#[tauri::command]
+pub fn apply_profile(
+    name: String,
+    save_paths: Vec<String>,
+    hytale_root: String,
+) -> Result<Vec<AppliedProfile>, String> {
+    let profile = find_profile(&name)?;
+
+    let mut results = Vec::new();
+    for save_path in save_paths {
+        let path = Path::new(&save_path);
+        let mut config = read_mod_config(path)?;
+
+        let mismatches = version_mismatches(&profile, &installed_versions(path, &hytale_root));
+        for mismatch in &mismatches {
+            log!(
+                "Warning: {} is pinned at {:?} but {:?} is installed",
+                mismatch.key,
+                mismatch.pinned,
+                mismatch.installed
+            );
+        }
+
+        let mut changed = Vec::new();
+        for profile_mod in &profile.mods {
+            let current = config.mods.get(&profile_mod.key).map(|e| e.enabled);
+            if current != Some(profile_mod.enabled) {
+                changed.push(profile_mod.key.clone());
+            }
+            config.mods.insert(
+                profile_mod.key.clone(),
+                ModConfigEntry {
+                    enabled: profile_mod.enabled,
+                },
+            );
+        }
+
+        let mut disabled = Vec::new();
+        for (key, entry) in config.mods.iter_mut() {
+            if entry.enabled && !profile.mods.iter().any(|m| &m.key == key) {
+                entry.enabled = false;
+                disabled.push(key.clone());
+            }
+        }
+
+        changed.sort();
+        disabled.sort();
+
+        write_mod_config(path, &config)?;
+        invalidate_save_stats(path);
+
+        log!(
+            "Applied profile '{}' to {}: {} changed, {} disabled",
+            profile.name,
+            save_path,
+            changed.len(),
+            disabled.len()
+        );
+
+        results.push(AppliedProfile {
+            save_path,
+            changed,
+            disabled,
+            version_mismatches: mismatches,
+        });
+    }
+
+    Ok(results)
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct EnabledMismatch {
+    pub key: String,
+    pub in_profile: bool,
+    pub in_save: bool,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ProfileDiff {
+    /// Enabled in the profile, missing from the save's config.json
+    pub only_in_profile: Vec<String>,
+    /// Enabled in the save, not listed in the profile
+    pub only_in_save: Vec<String>,
+    pub enabled_mismatches: Vec<EnabledMismatch>,
+    pub version_mismatches: Vec<VersionMismatch>,
+}
+
+/// Compare a profile against a save's config.json and installed mod versions
+#[tauri::command]
+pub fn diff_profile(
+    name: String,
+    save_path: String,
+    hytale_root: String,
+) -> Result<ProfileDiff, String> {
+    let profile = find_profile(&name)?;
+    let config = read_mod_config(Path::new(&save_path))?;
+    let versions = installed_versions(Path::new(&save_path), &hytale_root);
+
+    let mut diff = ProfileDiff {
+        only_in_profile: Vec::new(),
+        only_in_save: Vec::new(),
+        enabled_mismatches: Vec::new(),
+        version_mismatches: version_mismatches(&profile, &versions),
+    };
+
+    for profile_mod in &profile.mods {
+        match config.mods.get(&profile_mod.key) {
+            None if profile_mod.enabled => diff.only_in_profile.push(profile_mod.key.clone()),
+            None => {}
+            Some(entry) if entry.enabled != profile_mod.enabled => {
+                diff.enabled_mismatches.push(EnabledMismatch {
+                    key: profile_mod.key.clone(),
+                    in_profile: profile_mod.enabled,
+                    in_save: entry.enabled,
+                })
+            }
+            Some(_) => {}
+        }
+    }
+
+    for (key, entry) in &config.mods {
+        if entry.enabled && !profile.mods.iter().any(|m| &m.key == key) {
+            diff.only_in_save.push(key.clone());
+        }
+    }
+
+    diff.only_in_save.sort();
+    diff.enabled_mismatches.sort_by(|a, b| a.key.cmp(&b.key));
+
+    Ok(diff)
+}
@@ -0,0 +1,80 @@
+use crate::loader_core::fs::OsFs;
+use crate::loader_core::receipts::{InstallReceipt, ReceiptStore, UninstallSummary};
+use crate::state::loader_subdir;
+use std::collections::HashSet;
+use std::path::Path;
+
+/// Receipts of installs on this machine, under the loader's data dir
+fn store() -> Result<ReceiptStore<'static>, String> {
+    Ok(ReceiptStore::new(&OsFs, loader_subdir("receipts")?))
+}
+
+/// An empty receipt for a new install, backed up under the loader's data dir
+pub(crate) fn new_receipt(
+    modpack_name: &str,
+    save_path: &Path,
+    hytale_root: &Path,
+) -> Result<InstallReceipt, String> {
+    Ok(store()?.create(modpack_name, save_path, hytale_root))
+}
+
+pub(crate) fn save_receipt(receipt: &InstallReceipt) -> Result<(), String> {
+    store()?.save(receipt)
+}
+
+pub(crate) fn read_receipts() -> Result<Vec<InstallReceipt>, String> {
+    let (receipts, warnings) = store()?.read_all()?;
+    for warning in warnings {
+        eprintln!("{}", warning);
+    }
+    Ok(receipts)
+}
+
+pub(crate) fn read_receipt(receipt_id: &str) -> Result<InstallReceipt, String> {
+    store()?.read(receipt_id)
+}
+
+/// Files recorded by every install receipt except `receipt_id`
+pub(crate) fn paths_owned_by_others(receipt_id: &str) -> Result<HashSet<String>, String> {
+    store()?.paths_owned_by_others(receipt_id)
+}
+
+#[tauri::command]
+pub fn list_install_receipts(save_path: Option<String>) -> Result<Vec<InstallReceipt>, String> {
+    let receipts = read_receipts()?;
+    Ok(match save_path {
+        Some(save_path) => receipts
+            .into_iter()
+            .filter(|r| r.save_path == save_path)
+            .collect(),
+        None => receipts,
+    })
+}
+
+/// Revert everything a modpack install recorded in its receipt
+/// - Restores or removes config files it wrote in the save's mods/ dir
+/// - Restores or removes archives in UserData/Mods unless another save
+///   enables them or another install receipt also owns them
+/// - Puts config.json and orbis-metadata.json entries back to their previous values
+#[tauri::command]
+pub fn uninstall_modpack(receipt_id: String) -> Result<UninstallSummary, String> {
+    let store = store()?;
+    let receipt = store.read(&receipt_id)?;
+
+    log!(
+        "Uninstalling modpack {} from {:?}",
+        receipt.modpack_name,
+        receipt.save_path
+    );
+
+    let summary = store.uninstall(&receipt)?;
+
+    log!(
+        "Uninstalled modpack {}: {} removed, {} restored, {} kept",
+        receipt.modpack_name,
+        summary.removed_files.len(),
+        summary.restored_files.len(),
+        summary.kept_files.len()
+    );
+    Ok(summary)
+}
@@ -0,0 +1,841 @@
+use crate::loader_core::fs::OsFs;
+use crate::loader_core::lockfile::sha256_bytes;
+use crate::loader_core::save_config::{ModConfigEntry, SaveConfig};
+use crate::loader_core::server_deploy::{DeployFile, DeploySource};
+use crate::modpack::{ConfigKeyAction, PlannedConfigEntry};
+use crate::mods::{build_global_mods_index, build_mods_index};
+use crate::profiles::find_profile;
+use crate::server::{is_enclosed, load_mod_set, load_save_mods, relative_string};
+use serde::{Deserialize, Serialize};
+use ssh2::{CheckResult, ErrorCode, KnownHostFileKind, RenameFlags, Session, Sftp};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::path::{Path, PathBuf};
+
+/// Hashes of the files the last sync uploaded, kept in the remote directory so
+/// unchanged files don't have to be downloaded to be compared
+const SYNC_MANIFEST_FILE: &str = ".orbis-sync.json";
+
+/// SFTP "no such file" status
+const SFTP_NO_SUCH_FILE: i32 = 2;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct SyncedFile {
+    sha256: String,
+    size: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct SyncManifest {
+    /// By '/'-separated path relative to the remote directory
+    #[serde(default)]
+    files: BTreeMap<String, SyncedFile>,
+}
+
+/// The directory a sync writes to. Paths are '/'-separated and relative to it.
+trait RemoteDir {
+    /// Size of a file, or None if it doesn't exist
+    fn file_size(&self, relative: &str) -> Result<Option<u64>, String>;
+    fn read_file(&self, relative: &str) -> Result<Vec<u8>, String>;
+    /// Write a file, creating its parent directories
+    fn write_file(&self, relative: &str, data: &[u8]) -> Result<(), String>;
+}
+
+/// A server directory reached over SFTP
+struct SftpDir {
+    // The SFTP channel only works while its session is alive
+    _session: Session,
+    sftp: Sftp,
+    root: String,
+}
+
+impl SftpDir {
+    fn path(&self, relative: &str) -> PathBuf {
+        PathBuf::from(format!("{}/{}", self.root.trim_end_matches('/'), relative))
+    }
+
+    fn create_parent_dirs(&self, relative: &str) -> Result<(), String> {
+        let dirs = match relative.rsplit_once('/') {
+            Some((dirs, _)) => dirs,
+            None => return Ok(()),
+        };
+
+        let mut parent = String::new();
+        for part in dirs.split('/') {
+            if !parent.is_empty() {
+                parent.push('/');
+            }
+            parent.push_str(part);
+
+            let path = self.path(&parent);
+            if self.sftp.stat(&path).is_err() {
+                self.sftp
+                    .mkdir(&path, 0o755)
+                    .map_err(|e| format!("Failed to create remote directory {:?}: {}", path, e))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl RemoteDir for SftpDir {
+    fn file_size(&self, relative: &str) -> Result<Option<u64>, String> {
+        let path = self.path(relative);
+        match self.sftp.stat(&path) {
+            Ok(stat) => Ok(Some(stat.size.unwrap_or(0))),
+            Err(e) if e.code() == ErrorCode::SFTP(SFTP_NO_SUCH_FILE) => Ok(None),
+            Err(e) => Err(format!("Failed to stat remote file {:?}: {}", path, e)),
+        }
+    }
+
+    fn read_file(&self, relative: &str) -> Result<Vec<u8>, String> {
+        let path = self.path(relative);
+        let mut file = self
+            .sftp
+            .open(&path)
+            .map_err(|e| format!("Failed to open remote file {:?}: {}", path, e))?;
+
+        let mut data = Vec::new();
+        file.read_to_end(&mut data)
+            .map_err(|e| format!("Failed to read remote file {:?}: {}", path, e))?;
+        Ok(data)
+    }
+
+    fn write_file(&self, relative: &str, data: &[u8]) -> Result<(), String> {
+        self.create_parent_dirs(relative)?;
+
+        // Upload next to the file and swap it in, so the server never loads a
+        // half-uploaded archive
+        let path = self.path(relative);
+        let partial = self.path(&format!("{}.part", relative));
+        let mut file = self
+            .sftp
+            .create(&partial)
+            .map_err(|e| format!("Failed to create {:?}: {}", partial, e))?;
+        file.write_all(data)
+            .map_err(|e| format!("Failed to upload {:?}: {}", path, e))?;
+        drop(file);
+
+        let flags = RenameFlags::OVERWRITE | RenameFlags::ATOMIC | RenameFlags::NATIVE;
+        let rename_error = match self.sftp.rename(&partial, &path, Some(flags)) {
+            Ok(()) => return Ok(()),
+            Err(e) => e,
+        };
+        if self.file_size(relative)?.is_none() {
+            return Err(format!(
+                "Failed to move upload into place {:?}: {}",
+                path, rename_error
+            ));
+        }
+
+        // Servers speaking SFTP v3 refuse to rename over a file: move the old
+        // one aside and put it back if the upload can't take its place
+        let previous = self.path(&format!("{}.old", relative));
+        if self.file_size(&format!("{}.old", relative))?.is_some() {
+            self.sftp
+                .unlink(&previous)
+                .map_err(|e| format!("Failed to remove {:?}: {}", previous, e))?;
+        }
+        self.sftp
+            .rename(&path, &previous, None)
+            .map_err(|e| format!("Failed to replace remote file {:?}: {}", path, e))?;
+        if let Err(e) = self.sftp.rename(&partial, &path, None) {
+            if let Err(restore) = self.sftp.rename(&previous, &path, None) {
+                eprintln!("Failed to restore {:?}: {}", path, restore);
+            }
+            return Err(format!(
+                "Failed to move upload into place {:?}: {}",
+                path, e
+            ));
+        }
+        if let Err(e) = self.sftp.unlink(&previous) {
+            eprintln!("Failed to remove {:?}: {}", previous, e);
+        }
+        Ok(())
+    }
+}
+
+/// A server directory on a mounted share, or a local stand-in for one
+struct LocalDir {
+    root: PathBuf,
+}
+
+impl RemoteDir for LocalDir {
+    fn file_size(&self, relative: &str) -> Result<Option<u64>, String> {
+        let path = self.root.join(relative);
+        if !path.exists() {
+            return Ok(None);
+        }
+        fs::metadata(&path)
+            .map(|m| Some(m.len()))
+            .map_err(|e| format!("Failed to stat {:?}: {}", path, e))
+    }
+
+    fn read_file(&self, relative: &str) -> Result<Vec<u8>, String> {
+        let path = self.root.join(relative);
+        fs::read(&path).map_err(|e| format!("Failed to read {:?}: {}", path, e))
+    }
+
+    fn write_file(&self, relative: &str, data: &[u8]) -> Result<(), String> {
+        let path = self.root.join(relative);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create directory {:?}: {}", parent, e))?;
+        }
+        fs::write(&path, data).map_err(|e| format!("Failed to write {:?}: {}", path, e))
+    }
+}
+
+/// An SSH server to sync to. Without a password or private key the SSH agent
+/// is used.
+#[derive(Deserialize, Clone)]
+pub struct SftpTarget {
+    pub host: String,
+    #[serde(default = "default_port")]
+    pub port: u16,
+    pub username: String,
+    pub password: Option<String>,
+    pub private_key_path: Option<String>,
+    pub passphrase: Option<String>,
+    /// The server directory on the remote host (the one with config.json and mods/)
+    pub remote_dir: String,
+    /// Trust and remember a host missing from ~/.ssh/known_hosts. A host whose
+    /// key changed is always refused.
+    #[serde(default)]
+    pub accept_new_host_key: bool,
+}
+
+fn default_port() -> u16 {
+    22
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RemoteTarget {
+    Sftp(SftpTarget),
+    /// A directory reachable through the filesystem: a mounted share, or a
+    /// local directory standing in for a server
+    Directory {
+        path: String,
+    },
+}
+
+impl RemoteTarget {
+    fn describe(&self) -> String {
+        match self {
+            RemoteTarget::Sftp(target) => format!(
+                "sftp://{}@{}:{}{}",
+                target.username, target.host, target.port, target.remote_dir
+            ),
+            RemoteTarget::Directory { path } => path.clone(),
+        }
+    }
+}
+
+fn known_hosts_path() -> Result<PathBuf, String> {
+    dirs::home_dir()
+        .map(|home| home.join(".ssh").join("known_hosts"))
+        .ok_or_else(|| "Failed to find home directory".to_string())
+}
+
+/// Check the server's host key against ~/.ssh/known_hosts, like ssh does
+fn verify_host_key(session: &Session, target: &SftpTarget) -> Result<(), String> {
+    let (key, key_type) = session.host_key().ok_or("Server did not send a host key")?;
+
+    let mut known_hosts = session
+        .known_hosts()
+        .map_err(|e| format!("Failed to read known hosts: {}", e))?;
+    let path = known_hosts_path()?;
+    if path.exists() {
+        known_hosts
+            .read_file(&path, KnownHostFileKind::OpenSSH)
+            .map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+    }
+
+    match known_hosts.check_port(&target.host, target.port, key) {
+        CheckResult::Match => Ok(()),
+        CheckResult::Mismatch => Err(format!(
+            "Host key for {} does not match ~/.ssh/known_hosts; refusing to connect",
+            target.host
+        )),
+        CheckResult::Failure => Err(format!("Failed to check host key for {}", target.host)),
+        CheckResult::NotFound if !target.accept_new_host_key => Err(format!(
+            "{} is not in ~/.ssh/known_hosts; set accept_new_host_key to trust it",
+            target.host
+        )),
+        CheckResult::NotFound => {
+            let host = if target.port == 22 {
+                target.host.clone()
+            } else {
+                format!("[{}]:{}", target.host, target.port)
+            };
+            log!("Adding host key for {} to {:?}", host, path);
+
+            known_hosts
+                .add(&host, key, "added by Orbis mod loader", key_type.into())
+                .map_err(|e| format!("Failed to add host key: {}", e))?;
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)
+                    .map_err(|e| format!("Failed to create {:?}: {}", parent, e))?;
+            }
+            known_hosts
+                .write_file(&path, KnownHostFileKind::OpenSSH)
+                .map_err(|e| format!("Failed to write {:?}: {}", path, e))
+        }
+    }
+}
+
+fn connect_sftp(target: &SftpTarget) -> Result<SftpDir, String> {
+    log!("Connecting to {}:{}", target.host, target.port);
+    let tcp = TcpStream::connect((target.host.as_str(), target.port)).map_err(|e| {
+        format!(
+            "Failed to connect to {}:{}: {}",
+            target.host, target.port, e
+        )
+    })?;
+
+    let mut session = Session::new().map_err(|e| format!("Failed to start SSH session: {}", e))?;
+    session.set_tcp_stream(tcp);
+    session
+        .handshake()
+        .map_err(|e| format!("SSH handshake failed: {}", e))?;
+    verify_host_key(&session, target)?;
+
+    let auth = match (&target.private_key_path, &target.password) {
+        (Some(key), _) => session.userauth_pubkey_file(
+            &target.username,
+            None,
+            Path::new(key),
+            target.passphrase.as_deref(),
+        ),
+        (None, Some(password)) => session.userauth_password(&target.username, password),
+        (None, None) => session.userauth_agent(&target.username),
+    };
+    auth.map_err(|e| format!("SSH authentication failed: {}", e))?;
+    if !session.authenticated() {
+        return Err("SSH authentication failed".to_string());
+    }
+
+    let sftp = session
+        .sftp()
+        .map_err(|e| format!("Failed to start SFTP: {}", e))?;
+    let remote_dir = Path::new(&target.remote_dir);
+    sftp.stat(remote_dir)
+        .map_err(|e| format!("Remote directory {} not found: {}", target.remote_dir, e))?;
+
+    Ok(SftpDir {
+        _session: session,
+        sftp,
+        root: target.remote_dir.clone(),
+    })
+}
+
+fn open_target(target: &RemoteTarget) -> Result<Box<dyn RemoteDir>, String> {
+    match target {
+        RemoteTarget::Sftp(sftp) => Ok(Box::new(connect_sftp(sftp)?)),
+        RemoteTarget::Directory { path } => {
+            let root = PathBuf::from(path);
+            if !root.is_dir() {
+                return Err(format!("Directory not found: {}", path));
+            }
+            Ok(Box::new(LocalDir { root }))
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RemoteFileStatus {
+    /// Not on the remote yet
+    New,
+    /// On the remote with different contents
+    Changed,
+    /// Already on the remote with the same hash; not uploaded
+    Unchanged,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct RemoteFile {
+    /// Relative to the remote directory, '/'-separated
+    pub path: String,
+    pub size_bytes: u64,
+    pub sha256: String,
+    pub status: RemoteFileStatus,
+    pub mod_key: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct RemoteSyncReport {
+    pub source_name: String,
+    pub target: String,
+    pub dry_run: bool,
+    /// Every mod archive and config file of the source
+    pub files: Vec<RemoteFile>,
+    /// Mods the sync enables (or would enable) in the remote config.json
+    pub config_entries: Vec<PlannedConfigEntry>,
+    /// Enabled in the source but without an archive to upload
+    pub missing_mods: Vec<String>,
+    /// Bytes of the New and Changed files
+    pub upload_bytes: u64,
+}
+
+/// The remote file's hash: from the sync manifest if the file still has the
+/// size recorded there, otherwise by downloading it
+fn remote_sha(
+    remote: &dyn RemoteDir,
+    manifest: &SyncManifest,
+    relative: &str,
+) -> Result<Option<String>, String> {
+    let size = match remote.file_size(relative)? {
+        Some(size) => size,
+        None => return Ok(None),
+    };
+
+    match manifest.files.get(relative) {
+        Some(synced) if synced.size == size => Ok(Some(synced.sha256.clone())),
+        _ => Ok(Some(sha256_bytes(&remote.read_file(relative)?))),
+    }
+}
+
+fn read_sync_manifest(remote: &dyn RemoteDir) -> SyncManifest {
+    let data = match remote.file_size(SYNC_MANIFEST_FILE) {
+        Ok(Some(_)) => remote.read_file(SYNC_MANIFEST_FILE),
+        _ => return SyncManifest::default(),
+    };
+
+    match data.and_then(|data| serde_json::from_slice(&data).map_err(|e| e.to_string())) {
+        Ok(manifest) => manifest,
+        Err(e) => {
+            eprintln!("Failed to read remote {}: {}", SYNC_MANIFEST_FILE, e);
+            SyncManifest::default()
+        }
+    }
+}
+
+fn write_sync_manifest(remote: &dyn RemoteDir, manifest: &SyncManifest) -> Result<(), String> {
+    let contents = serde_json::to_vec_pretty(manifest)
+        .map_err(|e| format!("Failed to serialize {}: {}", SYNC_MANIFEST_FILE, e))?;
+    remote.write_file(SYNC_MANIFEST_FILE, &contents)
+}
+
+fn read_remote_config(remote: &dyn RemoteDir) -> Result<SaveConfig, String> {
+    if remote.file_size("config.json")?.is_none() {
+        return Ok(SaveConfig::default());
+    }
+    serde_json::from_slice(&remote.read_file("config.json")?)
+        .map_err(|e| format!("Failed to parse remote config.json: {}", e))
+}
+
+fn upload_changed(
+    remote: &dyn RemoteDir,
+    source: &DeploySource,
+    report: &RemoteSyncReport,
+    manifest: &mut SyncManifest,
+) -> Result<(), String> {
+    let files = source
+        .mods
+        .iter()
+        .flat_map(|m| &m.files)
+        .chain(&source.configs);
+
+    for (file, planned) in files.zip(&report.files) {
+        if planned.status != RemoteFileStatus::Unchanged {
+            log!("Uploading {} ({} bytes)", planned.path, planned.size_bytes);
+            remote.write_file(&planned.path, &file.source.read(&OsFs)?)?;
+        }
+        manifest.files.insert(
+            planned.path.clone(),
+            SyncedFile {
+                sha256: planned.sha256.clone(),
+                size: planned.size_bytes,
+            },
+        );
+    }
+
+    Ok(())
+}
+
+fn plan_file(
+    remote: &dyn RemoteDir,
+    manifest: &SyncManifest,
+    file: &DeployFile,
+    mod_key: Option<String>,
+) -> Result<RemoteFile, String> {
+    if !is_enclosed(&file.relative_path) {
+        return Err(format!(
+            "Refusing to upload unsafe path {:?}",
+            file.relative_path
+        ));
+    }
+
+    let path = relative_string(&file.relative_path);
+    let data_sha = file.source.sha256(&OsFs)?;
+    let status = match remote_sha(remote, manifest, &path)? {
+        None => RemoteFileStatus::New,
+        Some(existing) if existing == data_sha => RemoteFileStatus::Unchanged,
+        Some(_) => RemoteFileStatus::Changed,
+    };
+
+    Ok(RemoteFile {
+        size_bytes: file.source.size(&OsFs)?,
+        path,
+        sha256: data_sha,
+        status,
+        mod_key,
+    })
+}
+
+/// Compare `source` against the remote directory and, unless `dry_run`,
+/// upload what differs. `target` names the remote in the report and the log.
+fn sync(
+    source: DeploySource,
+    remote: &dyn RemoteDir,
+    target: String,
+    dry_run: bool,
+) -> Result<RemoteSyncReport, String> {
+    let mut manifest = read_sync_manifest(remote);
+
+    let mut report = RemoteSyncReport {
+        source_name: source.name.clone(),
+        target,
+        dry_run,
+        files: Vec::new(),
+        config_entries: Vec::new(),
+        missing_mods: source.missing.clone(),
+        upload_bytes: 0,
+    };
+
+    for deploy_mod in &source.mods {
+        for file in &deploy_mod.files {
+            let planned = plan_file(remote, &manifest, file, deploy_mod.mod_key.clone())?;
+            report.files.push(planned);
+        }
+    }
+    for file in &source.configs {
+        report.files.push(plan_file(remote, &manifest, file, None)?);
+    }
+
+    report.upload_bytes = report
+        .files
+        .iter()
+        .filter(|f| f.status != RemoteFileStatus::Unchanged)
+        .map(|f| f.size_bytes)
+        .sum();
+
+    let mut config = read_remote_config(remote)?;
+    for key in source.mods.iter().filter_map(|m| m.mod_key.as_ref()) {
+        let action = match config.mods.get(key) {
+            None => ConfigKeyAction::Add,
+            Some(entry) if !entry.enabled => ConfigKeyAction::Enable,
+            Some(_) => ConfigKeyAction::AlreadyEnabled,
+        };
+        report.config_entries.push(PlannedConfigEntry {
+            mod_key: key.clone(),
+            action,
+        });
+    }
+
+    for key in &report.missing_mods {
+        log!("Warning: {} is enabled but has no archive to upload", key);
+    }
+
+    if dry_run {
+        return Ok(report);
+    }
+
+    // Record what made it across even if the sync failed part-way
+    let result = upload_changed(remote, &source, &report, &mut manifest);
+    write_sync_manifest(remote, &manifest)?;
+    result?;
+
+    let changed = report
+        .config_entries
+        .iter()
+        .filter(|e| e.action != ConfigKeyAction::AlreadyEnabled)
+        .map(|e| e.mod_key.clone())
+        .collect::<Vec<_>>();
+    if !changed.is_empty() {
+        for key in changed {
+            config.mods.insert(key, ModConfigEntry { enabled: true });
+        }
+        let contents = serde_json::to_vec_pretty(&config)
+            .map_err(|e| format!("Failed to serialize config.json: {}", e))?;
+        remote.write_file("config.json", &contents)?;
+    }
+
+    log!(
+        "Synced {} to {} ({} bytes uploaded)",
+        source.name,
+        report.target,
+        report.upload_bytes
+    );
+    Ok(report)
+}
+
+/// Upload a save's enabled mod archives and their config files to a server
+/// directory over SFTP (or to a local directory), skipping files the remote
+/// already has with the same hash, and enable the mods in the remote
+/// config.json. With `dry_run` only the listing of what would be uploaded is
+/// returned.
+#[tauri::command]
+pub fn sync_save_to_remote(
+    save_path: String,
+    hytale_root: String,
+    target: RemoteTarget,
+    dry_run: Option<bool>,
+) -> Result<RemoteSyncReport, String> {
+    log!("Syncing save {} to {}", save_path, target.describe());
+    let source = load_save_mods(Path::new(&save_path), Path::new(&hytale_root))?;
+    let remote = open_target(&target)?;
+    sync(
+        source,
+        remote.as_ref(),
+        target.describe(),
+        dry_run.unwrap_or(false),
+    )
+}
+
+/// Like `sync_save_to_remote`, for a profile's enabled mods. They are looked
+/// up in UserData/Mods, or as `save_path` resolves them if given, in which case
+/// that save's config files are uploaded too.
+#[tauri::command]
+pub fn sync_profile_to_remote(
+    profile_name: String,
+    hytale_root: String,
+    save_path: Option<String>,
+    target: RemoteTarget,
+    dry_run: Option<bool>,
+) -> Result<RemoteSyncReport, String> {
+    let profile = find_profile(&profile_name)?;
+    let global_mods_dir = Path::new(&hytale_root).join("UserData").join("Mods");
+
+    let index: HashMap<String, (PathBuf, String)> = match &save_path {
+        Some(save) => build_mods_index(Path::new(save), &global_mods_dir)
+            .into_iter()
+            .map(|(key, resolved)| (key, (resolved.path, resolved.manifest.version)))
+            .collect(),
+        None => build_global_mods_index(&global_mods_dir)
+            .into_iter()
+            .map(|(key, (path, manifest))| (key, (path, manifest.version)))
+            .collect(),
+    };
+
+    for profile_mod in profile.mods.iter().filter(|m| m.enabled) {
+        let installed = index.get(&profile_mod.key).map(|(_, version)| version);
+        if let (Some(pinned), Some(installed)) = (&profile_mod.version, installed) {
+            if pinned != installed {
+                log!(
+                    "Warning: profile pins {} at {}, uploading installed {}",
+                    profile_mod.key,
+                    pinned,
+                    installed
+                );
+            }
+        }
+    }
+
+    let enabled = profile
+        .mods
+        .iter()
+        .filter(|m| m.enabled)
+        .map(|m| m.key.clone())
+        .collect();
+    let config_mods_dir = save_path.as_ref().map(|save| Path::new(save).join("mods"));
+
+    log!("Syncing profile {} to {}", profile.name, target.describe());
+    let source = load_mod_set(&profile.name, enabled, &index, config_mods_dir.as_deref())?;
+    let remote = open_target(&target)?;
+    sync(
+        source,
+        remote.as_ref(),
+        target.describe(),
+        dry_run.unwrap_or(false),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loader_core::server_deploy::{DeployMod, FileSource};
+    use std::cell::RefCell;
+
+    /// A remote directory held in memory
+    #[derive(Default)]
+    struct FakeRemote {
+        files: RefCell<BTreeMap<String, Vec<u8>>>,
+    }
+
+    impl RemoteDir for FakeRemote {
+        fn file_size(&self, relative: &str) -> Result<Option<u64>, String> {
+            Ok(self
+                .files
+                .borrow()
+                .get(relative)
+                .map(|data| data.len() as u64))
+        }
+
+        fn read_file(&self, relative: &str) -> Result<Vec<u8>, String> {
+            self.files
+                .borrow()
+                .get(relative)
+                .cloned()
+                .ok_or_else(|| format!("{} not found", relative))
+        }
+
+        fn write_file(&self, relative: &str, data: &[u8]) -> Result<(), String> {
+            self.files
+                .borrow_mut()
+                .insert(relative.to_string(), data.to_vec());
+            Ok(())
+        }
+    }
+
+    fn archive(file_name: &str, mod_key: &str, data: &[u8]) -> DeployMod {
+        DeployMod {
+            file_name: file_name.to_string(),
+            mod_key: Some(mod_key.to_string()),
+            version: None,
+            files: vec![DeployFile {
+                relative_path: Path::new("mods").join(file_name),
+                source: FileSource::Bytes(data.to_vec()),
+            }],
+        }
+    }
+
+    /// Hammers is already on the remote, Saws has changed and Drills is new
+    fn source() -> DeploySource {
+        DeploySource {
+            name: "World".to_string(),
+            mods: vec![
+                archive("hammers.jar", "Acme:Hammers", b"hammers"),
+                archive("saws.jar", "Acme:Saws", b"saws 2"),
+                archive("drills.jar", "Acme:Drills", b"drills"),
+            ],
+            configs: Vec::new(),
+            world: Vec::new(),
+            missing: Vec::new(),
+            source_sha256: None,
+        }
+    }
+
+    fn remote() -> FakeRemote {
+        let remote = FakeRemote::default();
+        remote.write_file("mods/hammers.jar", b"hammers").unwrap();
+        remote.write_file("mods/saws.jar", b"saws 1").unwrap();
+        remote
+            .write_file(
+                "config.json",
+                br#"{"Mods": {"Acme:Hammers": {"Enabled": true}}}"#,
+            )
+            .unwrap();
+        remote
+    }
+
+    fn statuses(report: &RemoteSyncReport) -> Vec<(&str, RemoteFileStatus)> {
+        report
+            .files
+            .iter()
+            .map(|f| (f.path.as_str(), f.status))
+            .collect()
+    }
+
+    #[test]
+    fn dry_run_lists_changed_files_without_uploading() {
+        let remote = remote();
+        let before = remote.files.borrow().clone();
+
+        let report = sync(source(), &remote, "fake".to_string(), true).unwrap();
+
+        assert_eq!(
+            statuses(&report),
+            vec![
+                ("mods/hammers.jar", RemoteFileStatus::Unchanged),
+                ("mods/saws.jar", RemoteFileStatus::Changed),
+                ("mods/drills.jar", RemoteFileStatus::New),
+            ]
+        );
+        assert_eq!(
+            report.upload_bytes,
+            (b"saws 2".len() + b"drills".len()) as u64
+        );
+        assert_eq!(*remote.files.borrow(), before);
+    }
+
+    #[test]
+    fn sync_uploads_only_changed_files() {
+        let remote = remote();
+        // Hammers' hash is taken from the sync manifest instead of downloading it
+        remote
+            .write_file(
+                SYNC_MANIFEST_FILE,
+                format!(
+                    r#"{{"files": {{"mods/hammers.jar": {{"sha256": "{}", "size": 7}}}}}}"#,
+                    sha256_bytes(b"hammers")
+                )
+                .as_bytes(),
+            )
+            .unwrap();
+
+        let report = sync(source(), &remote, "fake".to_string(), false).unwrap();
+        assert_eq!(
+            report.upload_bytes,
+            (b"saws 2".len() + b"drills".len()) as u64
+        );
+
+        let files = remote.files.borrow();
+        assert_eq!(files["mods/hammers.jar"], b"hammers");
+        assert_eq!(files["mods/saws.jar"], b"saws 2");
+        assert_eq!(files["mods/drills.jar"], b"drills");
+
+        let manifest: SyncManifest = serde_json::from_slice(&files[SYNC_MANIFEST_FILE]).unwrap();
+        assert_eq!(
+            manifest.files["mods/saws.jar"].sha256,
+            sha256_bytes(b"saws 2")
+        );
+
+        let config: SaveConfig = serde_json::from_slice(&files["config.json"]).unwrap();
+        let mut enabled: Vec<&String> = config.mods.keys().collect();
+        enabled.sort();
+        assert_eq!(enabled, vec!["Acme:Drills", "Acme:Hammers", "Acme:Saws"]);
+    }
+
+    #[test]
+    fn unchanged_files_are_not_rewritten() {
+        let remote = remote();
+        sync(source(), &remote, "fake".to_string(), false).unwrap();
+
+        // Every archive now has the same hash on the remote, so none is written again
+        struct ReadOnlyHammers<'a>(&'a FakeRemote);
+        impl RemoteDir for ReadOnlyHammers<'_> {
+            fn file_size(&self, relative: &str) -> Result<Option<u64>, String> {
+                self.0.file_size(relative)
+            }
+            fn read_file(&self, relative: &str) -> Result<Vec<u8>, String> {
+                self.0.read_file(relative)
+            }
+            fn write_file(&self, relative: &str, data: &[u8]) -> Result<(), String> {
+                if relative.starts_with("mods/") {
+                    return Err(format!("{} was uploaded again", relative));
+                }
+                self.0.write_file(relative, data)
+            }
+        }
+
+        let report = sync(
+            source(),
+            &ReadOnlyHammers(&remote),
+            "fake".to_string(),
+            false,
+        )
+        .unwrap();
+        assert!(report
+            .files
+            .iter()
+            .all(|f| f.status == RemoteFileStatus::Unchanged));
+        assert_eq!(report.upload_bytes, 0);
+    }
+}
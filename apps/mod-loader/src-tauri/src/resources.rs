@@ -0,0 +1,135 @@
+use crate::loader_core::fs::OsFs;
+use crate::loader_core::manifest as core_manifest;
+use crate::loader_core::resource_installer::{self, ResourceInstaller};
+use crate::modpack::ConflictPolicy;
+use crate::mods::is_mod_archive;
+use crate::prefabs::{install_prefab_archive, prefabs_dir};
+use crate::saves::invalidate_save_stats;
+use crate::worlds::import_world_archive;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+pub use crate::loader_core::manifest::ResourceType;
+pub use crate::loader_core::resource_installer::ResourceInspection;
+pub(crate) use crate::loader_core::resource_installer::{is_prefab_file, strip_folder};
+
+/// Every entry of an archive, or every path under a directory on disk
+pub(crate) fn entry_names(path: &Path) -> Result<Vec<String>, String> {
+    core_manifest::entry_names(&OsFs, path)
+}
+
+pub(crate) fn inspect(path: &Path) -> Result<ResourceInspection, String> {
+    resource_installer::inspect(&OsFs, path)
+}
+
+/// Classify an archive or directory by its manifest and entries
+#[tauri::command]
+pub fn inspect_resource(path: String) -> Result<ResourceInspection, String> {
+    let inspection = inspect(Path::new(&path))?;
+    log!("{} is a {:?}", path, inspection.resource_type);
+    Ok(inspection)
+}
+
+/// Extract an archive on disk into `dest`, dropping `wrapper` from every path and
+/// skipping entries that would escape `dest` or that `keep` rejects (it gets
+/// the path relative to `dest`). Returns the files written.
+pub(crate) fn extract_archive(
+    archive_path: &Path,
+    dest: &Path,
+    wrapper: Option<&str>,
+    keep: impl Fn(&Path) -> bool,
+) -> Result<Vec<PathBuf>, String> {
+    resource_installer::extract_archive(&OsFs, archive_path, dest, wrapper, keep)
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct InstalledResource {
+    pub resource_type: ResourceType,
+    /// Archive, directory or save the content was installed as
+    pub destination: String,
+    /// "Group:Name" of each mod or pack installed; enabled in the save if one was given
+    pub mod_keys: Vec<String>,
+}
+
+/// Install an archive where the game expects its kind of content:
+/// - plugins, mods and packs with a manifest are copied into UserData/Mods;
+///   one already installed under the same Group:Name is resolved by
+///   `conflict_policy` (default: keep the newer one)
+/// - asset and data packs without a manifest are unpacked into
+///   UserData/Mods/<name>/ with a generated manifest, named after the Orbis
+///   resource when the download was recorded; reinstalls and updates replace it
+/// - prefabs are unpacked into <save>/prefabs, or UserData/Prefabs without a save
+/// - worlds are unpacked as a new save in UserData/Saves (use `install_world`
+///   to keep track of the Orbis resource they came from)
+///
+/// Mods and packs are enabled in `save_path` when given. An archive that was
+/// downloaded straight into UserData/Mods and had to be unpacked is removed,
+/// and its Orbis metadata moves to the unpacked directory.
+#[tauri::command]
+pub fn install_resource(
+    archive_path: String,
+    hytale_root: String,
+    save_path: Option<String>,
+    conflict_policy: Option<ConflictPolicy>,
+) -> Result<InstalledResource, String> {
+    let archive = Path::new(&archive_path);
+    if !archive.is_file() || !is_mod_archive(archive) {
+        return Err(format!("{} is not a .zip or .jar archive", archive_path));
+    }
+
+    let stem = archive
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .ok_or("Invalid archive file name")?
+        .to_string();
+
+    let installer = ResourceInstaller::new(&OsFs, &hytale_root);
+    let inspection = inspect(archive)?;
+    log!(
+        "Installing {} as {:?}",
+        archive_path,
+        inspection.resource_type
+    );
+
+    let (destination, mod_keys) = match inspection.resource_type {
+        ResourceType::Plugin
+        | ResourceType::Mod
+        | ResourceType::AssetPack
+        | ResourceType::DataPack => {
+            let installed = installer.install_mods(
+                archive,
+                &inspection,
+                conflict_policy.unwrap_or_default(),
+            )?;
+            for save in &installed.updated_saves {
+                log!("Moved {:?} to the new version", save);
+                invalidate_save_stats(save);
+            }
+            (installed.destination, installed.mod_keys)
+        }
+        ResourceType::Prefab => {
+            let dest = prefabs_dir(&hytale_root, save_path.as_deref());
+            install_prefab_archive(archive, &dest, false)?;
+            (dest, Vec::new())
+        }
+        ResourceType::World => {
+            let dest = import_world_archive(archive, Path::new(&hytale_root), &stem)?;
+            (dest, Vec::new())
+        }
+    };
+
+    if let Some(save) = &save_path {
+        if !mod_keys.is_empty() {
+            let save = Path::new(save);
+            log!("Enabling {} in {:?}", mod_keys.join(", "), save);
+            installer.enable_in_save(save, &mod_keys)?;
+            invalidate_save_stats(save);
+        }
+    }
+
+    Ok(InstalledResource {
+        resource_type: inspection.resource_type,
+        destination: destination.to_string_lossy().to_string(),
+        mod_keys,
+    })
+}
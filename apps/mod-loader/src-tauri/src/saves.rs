@@ -1,91 +1,499 @@
+use crate::loader_core::fs::OsFs;
+use crate::loader_core::save_importer::SaveImporter;
+use crate::mods::read_mod_config;
+use crate::state::{loader_subdir, relocate_save_references};
+use crate::worlds::{read_world_metadata, WorldMetadata};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
-use zip::ZipArchive;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+pub(crate) use crate::loader_core::save_importer::validate_save_name;
+
+/// How long cached size/last-modified stats are trusted even if the save's
+/// fingerprint is unchanged (region files are rewritten in place, which does
+/// not bump any directory mtime).
+const SAVE_STATS_MAX_AGE: Duration = Duration::from_secs(30);
+
+/// Deleted saves can be restored for this long before they are purged
+const TRASH_RETENTION: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigStatus {
+    Ok,
+    Missing,
+    Invalid,
+}
+
+/// A save as shown in the saves list
+#[derive(Debug, Serialize, Clone)]
+pub struct SaveInfo {
+    pub name: String,
+    pub path: String,
+    pub size_bytes: u64,
+    /// Milliseconds since the Unix epoch of the most recently written world file
+    pub last_modified: Option<u64>,
+    pub enabled_mods: usize,
+    pub disabled_mods: usize,
+    pub config_status: ConfigStatus,
+    pub config_error: Option<String>,
+    /// Set for worlds installed from Orbis with `install_world`
+    pub orbis_world: Option<WorldMetadata>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct SaveFingerprint(Vec<(PathBuf, Option<SystemTime>)>);
+
+#[derive(Debug, Clone)]
+struct SaveStats {
+    fingerprint: SaveFingerprint,
+    computed_at: Instant,
+    size_bytes: u64,
+    last_modified: Option<SystemTime>,
+}
+
+fn save_stats_cache() -> &'static Mutex<HashMap<PathBuf, SaveStats>> {
+    static CACHE: OnceLock<Mutex<HashMap<PathBuf, SaveStats>>> = OnceLock::new();
+    CACHE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Drop cached stats for a save so the next `list_saves` recomputes them
+pub(crate) fn invalidate_save_stats(save_path: &Path) {
+    if let Ok(mut cache) = save_stats_cache().lock() {
+        cache.remove(save_path);
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Cheap change detector: mtimes of the save root and its direct children
+fn save_fingerprint(save_path: &Path) -> SaveFingerprint {
+    let mut entries = vec![(save_path.to_path_buf(), modified_time(save_path))];
+
+    if let Ok(children) = fs::read_dir(save_path) {
+        for child in children.flatten() {
+            let path = child.path();
+            let modified = child.metadata().and_then(|m| m.modified()).ok();
+            entries.push((path, modified));
+        }
+    }
+
+    entries.sort();
+    SaveFingerprint(entries)
+}
+
+/// Walk a directory recursively, returning total size and newest file mtime
+pub(crate) fn dir_size_and_mtime(dir: &Path) -> (u64, Option<SystemTime>) {
+    let mut size = 0;
+    let mut newest: Option<SystemTime> = None;
+
+    let entries = match fs::read_dir(dir) {
+        Ok(e) => e,
+        Err(_) => return (size, newest),
+    };
+
+    for entry in entries.flatten() {
+        let metadata = match entry.metadata() {
+            Ok(m) => m,
+            Err(_) => continue,
+        };
+
+        if metadata.is_dir() {
+            let (child_size, child_newest) = dir_size_and_mtime(&entry.path());
+            size += child_size;
+            newest = newest.max(child_newest);
+        } else {
+            size += metadata.len();
+            newest = newest.max(metadata.modified().ok());
+        }
+    }
+
+    (size, newest)
+}
+
+/// Compute (or reuse cached) size and world last-modified time for a save
+fn save_stats(save_path: &Path) -> (u64, Option<SystemTime>) {
+    let fingerprint = save_fingerprint(save_path);
+
+    if let Ok(cache) = save_stats_cache().lock() {
+        if let Some(stats) = cache.get(save_path) {
+            if stats.fingerprint == fingerprint && stats.computed_at.elapsed() < SAVE_STATS_MAX_AGE
+            {
+                return (stats.size_bytes, stats.last_modified);
+            }
+        }
+    }
+
+    let (size_bytes, save_newest) = dir_size_and_mtime(save_path);
+
+    // World data lives under universe/; fall back to the whole save if it's missing
+    let universe_dir = save_path.join("universe");
+    let last_modified = if universe_dir.is_dir() {
+        dir_size_and_mtime(&universe_dir).1
+    } else {
+        save_newest
+    };
+
+    if let Ok(mut cache) = save_stats_cache().lock() {
+        cache.insert(
+            save_path.to_path_buf(),
+            SaveStats {
+                fingerprint,
+                computed_at: Instant::now(),
+                size_bytes,
+                last_modified,
+            },
+        );
+    }
+
+    (size_bytes, last_modified)
+}
+
+fn to_unix_millis(time: SystemTime) -> Option<u64> {
+    time.duration_since(UNIX_EPOCH)
+        .ok()
+        .map(|d| d.as_millis() as u64)
+}
+
+fn read_save_info(save_path: &Path) -> SaveInfo {
+    let name = save_path
+        .file_name()
+        .and_then(|s| s.to_str())
+        .unwrap_or("unknown")
+        .to_string();
+
+    let (config, config_status, config_error) = if !save_path.join("config.json").exists() {
+        (None, ConfigStatus::Missing, None)
+    } else {
+        match read_mod_config(save_path) {
+            Ok(config) => (Some(config), ConfigStatus::Ok, None),
+            Err(e) => (None, ConfigStatus::Invalid, Some(e)),
+        }
+    };
+
+    let (enabled_mods, disabled_mods) = config
+        .as_ref()
+        .map(|c| {
+            let enabled = c.mods.values().filter(|m| m.enabled).count();
+            (enabled, c.mods.len() - enabled)
+        })
+        .unwrap_or((0, 0));
+
+    let (size_bytes, last_modified) = save_stats(save_path);
+
+    SaveInfo {
+        name,
+        path: save_path.to_string_lossy().to_string(),
+        size_bytes,
+        last_modified: last_modified.and_then(to_unix_millis),
+        enabled_mods,
+        disabled_mods,
+        config_status,
+        config_error,
+        orbis_world: read_world_metadata(save_path),
+    }
+}
+
+#[tauri::command]
+pub fn list_saves(hytale_root: String) -> Result<Vec<SaveInfo>, String> {
+    let saves_dir = Path::new(&hytale_root).join("UserData").join("Saves");
+
+    if !saves_dir.exists() {
+        log!("Saves directory does not exist: {:?}", saves_dir);
+        return Ok(Vec::new());
+    }
+
+    let entries =
+        fs::read_dir(&saves_dir).map_err(|e| format!("Failed to read saves directory: {}", e))?;
+
+    let mut saves: Vec<SaveInfo> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .map(|path| read_save_info(&path))
+        .collect();
+
+    // Most recently played first
+    saves.sort_by_key(|s| std::cmp::Reverse(s.last_modified));
+
+    Ok(saves)
+}
 
 #[tauri::command]
 pub fn import_save(zip_path: String, hytale_path: String) -> Result<String, String> {
-    println!(
+    log!(
         "import_save called with zip_path: {} and hytale_path: {}",
-        zip_path, hytale_path
+        zip_path,
+        hytale_path
     );
 
-    // Construct the saves directory path from the provided Hytale root
-    let saves_dir = PathBuf::from(hytale_path).join("UserData").join("Saves");
+    let save_name = SaveImporter::new(&OsFs, &hytale_path).import(Path::new(&zip_path))?;
+
+    log!("Successfully imported save: {}", save_name);
+    Ok(save_name)
+}
 
-    println!("Saves directory: {:?}", saves_dir);
+/// Recursively copy a directory
+pub(crate) fn copy_dir_recursive(src: &Path, dst: &Path) -> Result<(), String> {
+    fs::create_dir_all(dst).map_err(|e| format!("Failed to create directory {:?}: {}", dst, e))?;
 
-    // Get save name from ZIP filename (without .zip extension)
-    let zip_file_path = Path::new(&zip_path);
-    let save_name = zip_file_path
-        .file_stem()
-        .and_then(|s| s.to_str())
-        .ok_or("Invalid ZIP filename")?
-        .to_string();
+    let entries =
+        fs::read_dir(src).map_err(|e| format!("Failed to read directory {:?}: {}", src, e))?;
+
+    for entry in entries {
+        let entry = entry.map_err(|e| format!("Failed to read directory entry: {}", e))?;
+        let path = entry.path();
+        let target = dst.join(entry.file_name());
+
+        if path.is_dir() {
+            copy_dir_recursive(&path, &target)?;
+        } else {
+            fs::copy(&path, &target)
+                .map_err(|e| format!("Failed to copy {:?} to {:?}: {}", path, target, e))?;
+        }
+    }
 
-    println!("Save name from ZIP: {}", save_name);
+    Ok(())
+}
+
+/// Move a directory, falling back to copy + delete across filesystems
+fn move_dir(src: &Path, dst: &Path) -> Result<(), String> {
+    if fs::rename(src, dst).is_ok() {
+        return Ok(());
+    }
+
+    log!("Rename failed, copying {:?} to {:?}", src, dst);
+    if let Err(e) = copy_dir_recursive(src, dst) {
+        // Leave the save only where it was, not half-copied at the destination
+        if let Err(cleanup) = fs::remove_dir_all(dst) {
+            eprintln!(
+                "Warning: Failed to remove partial copy {:?}: {}",
+                dst, cleanup
+            );
+        }
+        return Err(e);
+    }
+    fs::remove_dir_all(src).map_err(|e| format!("Failed to remove {:?}: {}", src, e))
+}
 
-    // Create the target directory for this save
-    let target_dir = saves_dir.join(&save_name);
+fn now_millis() -> u64 {
+    to_unix_millis(SystemTime::now()).unwrap_or(0)
+}
+
+#[tauri::command]
+pub fn duplicate_save(save_path: String, new_name: String) -> Result<String, String> {
+    validate_save_name(&new_name)?;
+
+    let save_path = Path::new(&save_path);
+    if !save_path.is_dir() {
+        return Err(format!("Save not found: {}", save_path.display()));
+    }
+
+    let target_dir = save_path
+        .parent()
+        .ok_or("Invalid save path")?
+        .join(new_name.trim());
 
-    // Check if save already exists
     if target_dir.exists() {
-        return Err(format!("Save '{}' already exists", save_name));
+        return Err(format!("Save '{}' already exists", new_name.trim()));
     }
 
-    fs::create_dir_all(&target_dir)
-        .map_err(|e| format!("Failed to create save directory: {}", e))?;
+    log!("Duplicating save {:?} -> {:?}", save_path, target_dir);
 
-    println!("Created target directory: {:?}", target_dir);
+    if let Err(e) = copy_dir_recursive(save_path, &target_dir) {
+        // Don't leave a half-copied save behind
+        let _ = fs::remove_dir_all(&target_dir);
+        return Err(e);
+    }
 
-    // Open the ZIP file
-    let file = fs::File::open(&zip_path).map_err(|e| format!("Failed to open ZIP file: {}", e))?;
+    Ok(target_dir.to_string_lossy().to_string())
+}
 
-    let mut archive =
-        ZipArchive::new(file).map_err(|e| format!("Failed to read ZIP archive: {}", e))?;
+#[tauri::command]
+pub fn rename_save(save_path: String, new_name: String) -> Result<String, String> {
+    validate_save_name(&new_name)?;
+
+    let save_path = Path::new(&save_path);
+    if !save_path.is_dir() {
+        return Err(format!("Save not found: {}", save_path.display()));
+    }
 
-    // Extract all files directly into the target directory
-    for i in 0..archive.len() {
-        let mut file = archive
-            .by_index(i)
-            .map_err(|e| format!("Failed to read file at index {}: {}", i, e))?;
+    let target_dir = save_path
+        .parent()
+        .ok_or("Invalid save path")?
+        .join(new_name.trim());
 
-        // Get the file path from the ZIP
-        let file_path = match file.enclosed_name() {
-            Some(path) => path.to_path_buf(),
-            None => continue,
-        };
+    if target_dir == save_path {
+        return Ok(target_dir.to_string_lossy().to_string());
+    }
 
-        // Target path: saves_dir/save_name/file_path
-        let outpath = target_dir.join(&file_path);
+    if target_dir.exists() {
+        return Err(format!("Save '{}' already exists", new_name.trim()));
+    }
 
-        // Check if it's a directory
-        if file.name().ends_with('/') || file.is_dir() {
-            println!("Creating directory: {:?}", outpath);
-            fs::create_dir_all(&outpath)
-                .map_err(|e| format!("Failed to create directory: {}", e))?;
-        } else {
-            // It's a file
-            if let Some(p) = outpath.parent() {
-                if !p.exists() {
-                    fs::create_dir_all(p)
-                        .map_err(|e| format!("Failed to create parent directory: {}", e))?;
-                }
-            }
+    log!("Renaming save {:?} -> {:?}", save_path, target_dir);
+
+    fs::rename(save_path, &target_dir).map_err(|e| format!("Failed to rename save: {}", e))?;
+
+    // Keep receipts, profiles etc. pointing at the save after the move
+    if let Err(e) = relocate_save_references(save_path, &target_dir) {
+        eprintln!(
+            "Warning: Failed to update loader state for renamed save: {}",
+            e
+        );
+    }
+
+    Ok(target_dir.to_string_lossy().to_string())
+}
 
-            // Skip if path is a directory
-            if outpath.exists() && outpath.is_dir() {
-                println!("Skipping directory that looks like a file: {:?}", outpath);
-                continue;
+/// A save moved to the loader's trash
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TrashedSave {
+    pub id: String,
+    pub name: String,
+    pub original_path: String,
+    /// Milliseconds since the Unix epoch
+    pub deleted_at: u64,
+    /// Milliseconds since the Unix epoch after which the save is purged
+    pub expires_at: u64,
+}
+
+fn trash_index_path(trash_dir: &Path) -> PathBuf {
+    trash_dir.join("index.json")
+}
+
+fn read_trash_index(trash_dir: &Path) -> Vec<TrashedSave> {
+    let index_path = trash_index_path(trash_dir);
+
+    if !index_path.exists() {
+        return Vec::new();
+    }
+
+    match fs::read_to_string(&index_path) {
+        Ok(contents) => match serde_json::from_str(&contents) {
+            Ok(index) => index,
+            Err(e) => {
+                eprintln!("Failed to parse trash index: {}", e);
+                Vec::new()
             }
+        },
+        Err(e) => {
+            eprintln!("Failed to read trash index: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+fn write_trash_index(trash_dir: &Path, index: &[TrashedSave]) -> Result<(), String> {
+    let contents = serde_json::to_string_pretty(index)
+        .map_err(|e| format!("Failed to serialize trash index: {}", e))?;
+
+    fs::write(trash_index_path(trash_dir), contents)
+        .map_err(|e| format!("Failed to write trash index: {}", e))
+}
+
+/// Permanently remove trashed saves whose restore window has passed
+fn purge_expired_trash(trash_dir: &Path) -> Result<Vec<TrashedSave>, String> {
+    let now = now_millis();
+    let (expired, kept): (Vec<_>, Vec<_>) = read_trash_index(trash_dir)
+        .into_iter()
+        .partition(|entry| entry.expires_at <= now);
 
-            println!("Extracting file: {:?}", outpath);
-            let mut outfile = fs::File::create(&outpath)
-                .map_err(|e| format!("Failed to create file {:?}: {}", outpath, e))?;
-            std::io::copy(&mut file, &mut outfile)
-                .map_err(|e| format!("Failed to extract file: {}", e))?;
+    for entry in &expired {
+        log!("Purging trashed save {} ({})", entry.name, entry.id);
+        let path = trash_dir.join(&entry.id);
+        if path.exists() {
+            if let Err(e) = fs::remove_dir_all(&path) {
+                eprintln!("Warning: Failed to purge trashed save {:?}: {}", path, e);
+            }
         }
     }
 
-    println!("Successfully imported save: {}", save_name);
-    Ok(save_name)
+    if !expired.is_empty() {
+        write_trash_index(trash_dir, &kept)?;
+    }
+
+    Ok(kept)
+}
+
+#[tauri::command]
+pub fn delete_save(save_path: String) -> Result<TrashedSave, String> {
+    let save_path = Path::new(&save_path);
+    if !save_path.is_dir() {
+        return Err(format!("Save not found: {}", save_path.display()));
+    }
+
+    let name = save_path
+        .file_name()
+        .and_then(|s| s.to_str())
+        .ok_or("Invalid save path")?
+        .to_string();
+
+    let trash_dir = loader_subdir("trash")?;
+    let mut index = purge_expired_trash(&trash_dir)?;
+
+    let deleted_at = now_millis();
+    let id = format!("{}-{}", deleted_at, name);
+    let trashed_path = trash_dir.join(&id);
+
+    log!("Moving save {:?} to trash {:?}", save_path, trashed_path);
+    move_dir(save_path, &trashed_path)?;
+
+    let entry = TrashedSave {
+        id,
+        name,
+        original_path: save_path.to_string_lossy().to_string(),
+        deleted_at,
+        expires_at: deleted_at + TRASH_RETENTION.as_millis() as u64,
+    };
+
+    index.push(entry.clone());
+    write_trash_index(&trash_dir, &index)?;
+
+    Ok(entry)
+}
+
+#[tauri::command]
+pub fn list_trashed_saves() -> Result<Vec<TrashedSave>, String> {
+    let trash_dir = loader_subdir("trash")?;
+    purge_expired_trash(&trash_dir)
+}
+
+#[tauri::command]
+pub fn restore_save(trash_id: String) -> Result<String, String> {
+    let trash_dir = loader_subdir("trash")?;
+    let mut index = purge_expired_trash(&trash_dir)?;
+
+    let position = index
+        .iter()
+        .position(|entry| entry.id == trash_id)
+        .ok_or_else(|| format!("Trashed save '{}' not found", trash_id))?;
+
+    let entry = &index[position];
+    let original_path = PathBuf::from(&entry.original_path);
+
+    if original_path.exists() {
+        return Err(format!(
+            "Cannot restore '{}': a save already exists at {}",
+            entry.name, entry.original_path
+        ));
+    }
+
+    if let Some(parent) = original_path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create saves directory: {}", e))?;
+    }
+
+    log!("Restoring trashed save {} -> {:?}", entry.id, original_path);
+    move_dir(&trash_dir.join(&entry.id), &original_path)?;
+
+    index.remove(position);
+    write_trash_index(&trash_dir, &index)?;
+
+    Ok(original_path.to_string_lossy().to_string())
 }
@@ -0,0 +1,239 @@
+use crate::loader_core::fs::OsFs;
+use crate::loader_core::receipts::InstallReceipt;
+use crate::loader_core::server_deploy::{
+    DeployFile, DeployMod, DeployOptions, DeployPlan, DeploySource, FileSource, ServerDeployer,
+};
+use crate::modpack::{collect_config_files, collect_files, config_prefix_for_key, read_pack};
+use crate::mods::{build_mods_index, read_mod_config};
+use crate::pack_cache::hash_file;
+use crate::receipts::{new_receipt, save_receipt};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+pub(crate) use crate::loader_core::server_deploy::{is_enclosed, relative_string};
+
+fn disk_files(dir: &Path, prefix: &str) -> Result<Vec<DeployFile>, String> {
+    let mut files = Vec::new();
+    collect_files(dir, prefix, &mut files)?;
+    Ok(files
+        .into_iter()
+        .map(|(path, relative)| DeployFile {
+            relative_path: PathBuf::from(relative),
+            source: FileSource::Disk(path),
+        })
+        .collect())
+}
+
+fn load_modpack(modpack_path: &Path, include_world: bool) -> Result<DeploySource, String> {
+    let mut source = DeploySource::from_pack(read_pack(modpack_path)?, include_world);
+    source.source_sha256 = hash_file(modpack_path).ok();
+    Ok(source)
+}
+
+/// Enabled mods resolved to an archive (or unpacked directory) and its version,
+/// with their config files taken from `config_mods_dir` if given
+pub(crate) fn load_mod_set(
+    name: &str,
+    mut enabled: Vec<String>,
+    index: &HashMap<String, (PathBuf, String)>,
+    config_mods_dir: Option<&Path>,
+) -> Result<DeploySource, String> {
+    enabled.sort();
+
+    let mut source = DeploySource {
+        name: name.to_string(),
+        mods: Vec::new(),
+        configs: Vec::new(),
+        world: Vec::new(),
+        missing: Vec::new(),
+        source_sha256: None,
+    };
+
+    // Archives bundling several mods are deployed once
+    let mut seen_files = HashSet::new();
+
+    for key in enabled {
+        let (path, version) = match index.get(&key) {
+            Some(resolved) => resolved,
+            None => {
+                source.missing.push(key);
+                continue;
+            }
+        };
+
+        let file_name = path
+            .file_name()
+            .and_then(|s| s.to_str())
+            .unwrap_or_default()
+            .to_string();
+        if seen_files.insert(file_name.clone()) {
+            let files = if path.is_dir() {
+                disk_files(path, &format!("mods/{}", file_name))?
+            } else {
+                vec![DeployFile {
+                    relative_path: Path::new("mods").join(&file_name),
+                    source: FileSource::Disk(path.clone()),
+                }]
+            };
+
+            source.mods.push(DeployMod {
+                file_name,
+                mod_key: Some(key.clone()),
+                version: Some(version.clone()),
+                files,
+            });
+        }
+
+        if let Some(mods_dir) = config_mods_dir {
+            for (path, relative) in collect_config_files(mods_dir, &config_prefix_for_key(&key))? {
+                source.configs.push(DeployFile {
+                    relative_path: Path::new("mods").join(relative),
+                    source: FileSource::Disk(path),
+                });
+            }
+        }
+    }
+
+    Ok(source)
+}
+
+/// A save's enabled mods (global or save-local) and their config files
+pub(crate) fn load_save_mods(save_path: &Path, hytale_root: &Path) -> Result<DeploySource, String> {
+    let config = read_mod_config(save_path)?;
+    let index = build_mods_index(save_path, &hytale_root.join("UserData").join("Mods"))
+        .into_iter()
+        .map(|(key, resolved)| (key, (resolved.path, resolved.manifest.version)))
+        .collect();
+
+    let enabled = config
+        .mods
+        .iter()
+        .filter(|(_, entry)| entry.enabled)
+        .map(|(key, _)| key.clone())
+        .collect();
+
+    let name = save_path
+        .file_name()
+        .and_then(|s| s.to_str())
+        .unwrap_or("save");
+    load_mod_set(name, enabled, &index, Some(&save_path.join("mods")))
+}
+
+fn load_save(
+    save_path: &Path,
+    hytale_root: &Path,
+    include_world: bool,
+) -> Result<DeploySource, String> {
+    let mut source = load_save_mods(save_path, hytale_root)?;
+
+    let universe = save_path.join("universe");
+    if include_world && universe.is_dir() {
+        source.world = disk_files(&universe, "universe")?;
+    }
+
+    Ok(source)
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct DeployOutcome {
+    pub plan: DeployPlan,
+    /// None for a dry run. Pass its id to `uninstall_modpack` to undo the deploy.
+    pub receipt: Option<InstallReceipt>,
+}
+
+fn deploy(
+    source: DeploySource,
+    server_dir: &Path,
+    options: &DeployOptions,
+) -> Result<DeployOutcome, String> {
+    let deployer = ServerDeployer::new(&OsFs, server_dir);
+    let plan = deployer.plan(&source, options)?;
+
+    for planned in &plan.mods {
+        log!(
+            "Planned server mod {} ({:?}) -> {:?}",
+            planned.file_name,
+            planned.status,
+            planned.action
+        );
+    }
+    for key in &plan.missing_mods {
+        log!("Warning: {} is enabled but has no archive to deploy", key);
+    }
+
+    if options.dry_run {
+        return Ok(DeployOutcome {
+            plan,
+            receipt: None,
+        });
+    }
+
+    let mut receipt = new_receipt(&source.name, server_dir, server_dir)?;
+    receipt.source_sha256 = source.source_sha256.clone();
+
+    let result = deployer.deploy(&plan, &source, options, &mut receipt);
+
+    // Keep the footprint even if the deploy failed part-way, so it can be reverted
+    if !receipt.is_empty() {
+        if let Err(e) = save_receipt(&receipt) {
+            eprintln!("Warning: Failed to save deploy receipt: {}", e);
+        }
+    }
+    result?;
+
+    log!("Deployed {} to {:?}", source.name, server_dir);
+    Ok(DeployOutcome {
+        plan,
+        receipt: Some(receipt),
+    })
+}
+
+fn server_path(server_dir: &str) -> Result<&Path, String> {
+    let path = Path::new(server_dir);
+    if !path.is_dir() {
+        return Err(format!("Server directory not found: {}", server_dir));
+    }
+    Ok(path)
+}
+
+/// Deploy a modpack into a dedicated server directory: archives to mods/,
+/// per-mod configs under mods/, the bundled world next to them, and the
+/// pack's mods enabled in the server's config.json (its other settings are
+/// kept). The deploy is recorded as an install receipt.
+#[tauri::command]
+pub fn deploy_modpack_to_server(
+    modpack_zip_path: String,
+    server_dir: String,
+    options: Option<DeployOptions>,
+) -> Result<DeployOutcome, String> {
+    let options = options.unwrap_or_default();
+    let server_dir = server_path(&server_dir)?;
+
+    log!("Deploying modpack {} to {:?}", modpack_zip_path, server_dir);
+    let source = load_modpack(Path::new(&modpack_zip_path), options.include_world)?;
+    deploy(source, server_dir, &options)
+}
+
+/// Deploy a save's enabled mods (global or save-local), their config files
+/// and its universe/ into a dedicated server directory, so the server runs
+/// the same mod set as the client
+#[tauri::command]
+pub fn deploy_save_to_server(
+    save_path: String,
+    hytale_root: String,
+    server_dir: String,
+    options: Option<DeployOptions>,
+) -> Result<DeployOutcome, String> {
+    let options = options.unwrap_or_default();
+    let server_dir = server_path(&server_dir)?;
+    let save = Path::new(&save_path);
+
+    if save == server_dir {
+        return Err("Save and server directory are the same".to_string());
+    }
+
+    log!("Deploying save {} to {:?}", save_path, server_dir);
+    let source = load_save(save, Path::new(&hytale_root), options.include_world)?;
+    deploy(source, server_dir, &options)
+}
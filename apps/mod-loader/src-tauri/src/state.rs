@@ -0,0 +1,128 @@
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Must match `identifier` in tauri.conf.json so the CLI and the app share state
+const APP_IDENTIFIER: &str = "com.orbisplace.mod-loader";
+
+/// Directory holding loader-owned state (trash, receipts, caches, ...)
+pub(crate) fn loader_data_dir() -> Result<PathBuf, String> {
+    let data_dir =
+        dirs::data_dir().ok_or_else(|| "Could not determine data directory".to_string())?;
+    let dir = data_dir.join(APP_IDENTIFIER);
+
+    if !dir.exists() {
+        fs::create_dir_all(&dir)
+            .map_err(|e| format!("Failed to create loader data directory: {}", e))?;
+    }
+
+    Ok(dir)
+}
+
+/// Get (and create) a subdirectory of the loader data directory
+pub(crate) fn loader_subdir(name: &str) -> Result<PathBuf, String> {
+    let dir = loader_data_dir()?.join(name);
+
+    if !dir.exists() {
+        fs::create_dir_all(&dir)
+            .map_err(|e| format!("Failed to create {} directory: {}", name, e))?;
+    }
+
+    Ok(dir)
+}
+
+/// Rewrite a path-valued string if it is `old` or lives under `old`
+fn relocate_path_string(value: &str, old: &str, new: &str) -> Option<String> {
+    if value == old {
+        return Some(new.to_string());
+    }
+
+    let rest = value.strip_prefix(old)?;
+    if rest.starts_with('/') || rest.starts_with('\\') {
+        Some(format!("{}{}", new, rest))
+    } else {
+        None
+    }
+}
+
+fn relocate_value(value: &mut Value, old: &str, new: &str) -> bool {
+    match value {
+        Value::String(s) => match relocate_path_string(s, old, new) {
+            Some(relocated) => {
+                *s = relocated;
+                true
+            }
+            None => false,
+        },
+        Value::Array(items) => {
+            let mut changed = false;
+            for item in items {
+                changed |= relocate_value(item, old, new);
+            }
+            changed
+        }
+        Value::Object(map) => {
+            let mut changed = false;
+            for item in map.values_mut() {
+                changed |= relocate_value(item, old, new);
+            }
+            changed
+        }
+        _ => false,
+    }
+}
+
+fn relocate_in_dir(dir: &Path, old: &str, new: &str) -> Result<(), String> {
+    let entries = fs::read_dir(dir)
+        .map_err(|e| format!("Failed to read loader state directory {:?}: {}", dir, e))?;
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+
+        if path.is_dir() {
+            // Trashed saves are opaque copies, not loader state
+            if path.file_name().and_then(|s| s.to_str()) == Some("trash") {
+                continue;
+            }
+            relocate_in_dir(&path, old, new)?;
+            continue;
+        }
+
+        if path.extension().and_then(|s| s.to_str()) != Some("json") {
+            continue;
+        }
+
+        let contents = match fs::read_to_string(&path) {
+            Ok(c) => c,
+            Err(e) => {
+                eprintln!("Failed to read loader state {:?}: {}", path, e);
+                continue;
+            }
+        };
+
+        let mut value: Value = match serde_json::from_str(&contents) {
+            Ok(v) => v,
+            Err(e) => {
+                eprintln!("Failed to parse loader state {:?}: {}", path, e);
+                continue;
+            }
+        };
+
+        if relocate_value(&mut value, old, new) {
+            log!("Updating save references in {:?}", path);
+            let contents = serde_json::to_string_pretty(&value)
+                .map_err(|e| format!("Failed to serialize loader state: {}", e))?;
+            fs::write(&path, contents)
+                .map_err(|e| format!("Failed to write loader state {:?}: {}", path, e))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Point every loader state file that references `old_save` at `new_save`
+pub(crate) fn relocate_save_references(old_save: &Path, new_save: &Path) -> Result<(), String> {
+    let old = old_save.to_string_lossy();
+    let new = new_save.to_string_lossy();
+    relocate_in_dir(&loader_data_dir()?, &old, &new)
+}
@@ -0,0 +1,217 @@
+use crate::modpack::{collect_config_files, config_prefix_for_key};
+use crate::mods::{build_mods_index, read_mod_config, write_mod_config, ModConfigEntry};
+use crate::saves::invalidate_save_stats;
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// UserData/Mods for the Hytale install a save belongs to (<root>/UserData/Saves/<save>)
+fn global_mods_dir_for_save(save_path: &Path) -> Option<PathBuf> {
+    save_path
+        .parent()
+        .and_then(|saves| saves.parent())
+        .map(|user_data| user_data.join("Mods"))
+}
+
+/// Installed version of every mod visible to a save, save-local or global, by "Group:Name"
+fn installed_versions(save_path: &Path) -> HashMap<String, String> {
+    match global_mods_dir_for_save(save_path) {
+        Some(dir) => build_mods_index(save_path, &dir)
+            .into_iter()
+            .map(|(key, resolved)| (key, resolved.manifest.version))
+            .collect(),
+        None => HashMap::new(),
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct SaveModState {
+    pub key: String,
+    pub enabled: bool,
+    /// Version of the archive the save resolves the mod to, if installed
+    pub version: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct EnabledDifference {
+    pub key: String,
+    pub enabled_in_a: bool,
+    pub enabled_in_b: bool,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct VersionDifference {
+    pub key: String,
+    pub version_in_a: Option<String>,
+    pub version_in_b: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct SaveDiff {
+    pub only_in_a: Vec<SaveModState>,
+    pub only_in_b: Vec<SaveModState>,
+    pub enabled_differences: Vec<EnabledDifference>,
+    /// Mods in both saves whose resolved archives have different versions,
+    /// e.g. from a save-local archive or a different Hytale install
+    pub version_differences: Vec<VersionDifference>,
+}
+
+/// Compare the mod sets in two saves' config.json
+#[tauri::command]
+pub fn diff_saves(save_a: String, save_b: String) -> Result<SaveDiff, String> {
+    let path_a = Path::new(&save_a);
+    let path_b = Path::new(&save_b);
+
+    let config_a = read_mod_config(path_a)?;
+    let config_b = read_mod_config(path_b)?;
+    let versions_a = installed_versions(path_a);
+    let versions_b = installed_versions(path_b);
+
+    let mut diff = SaveDiff {
+        only_in_a: Vec::new(),
+        only_in_b: Vec::new(),
+        enabled_differences: Vec::new(),
+        version_differences: Vec::new(),
+    };
+
+    let keys: BTreeSet<&String> = config_a.mods.keys().chain(config_b.mods.keys()).collect();
+    for key in keys {
+        match (config_a.mods.get(key), config_b.mods.get(key)) {
+            (Some(a), None) => diff.only_in_a.push(SaveModState {
+                key: key.clone(),
+                enabled: a.enabled,
+                version: versions_a.get(key).cloned(),
+            }),
+            (None, Some(b)) => diff.only_in_b.push(SaveModState {
+                key: key.clone(),
+                enabled: b.enabled,
+                version: versions_b.get(key).cloned(),
+            }),
+            (Some(a), Some(b)) => {
+                if a.enabled != b.enabled {
+                    diff.enabled_differences.push(EnabledDifference {
+                        key: key.clone(),
+                        enabled_in_a: a.enabled,
+                        enabled_in_b: b.enabled,
+                    });
+                }
+
+                let version_a = versions_a.get(key);
+                let version_b = versions_b.get(key);
+                if version_a != version_b {
+                    diff.version_differences.push(VersionDifference {
+                        key: key.clone(),
+                        version_in_a: version_a.cloned(),
+                        version_in_b: version_b.cloned(),
+                    });
+                }
+            }
+            (None, None) => {}
+        }
+    }
+
+    Ok(diff)
+}
+
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct SyncSummary {
+    /// Keys whose config.json entry was added or changed in the target
+    pub updated: Vec<String>,
+    /// Config files copied into the target's mods/ dir, relative to it
+    pub copied_files: Vec<String>,
+    /// Copied files that replaced an existing file in the target
+    pub overwritten_files: Vec<String>,
+    /// Selected keys the source save doesn't have
+    pub not_in_source: Vec<String>,
+    /// Synced mods with no archive visible to the target save
+    pub missing_archives: Vec<String>,
+}
+
+/// Copy enablement and config files for the selected mods (or every mod in
+/// the source when `selection` is None) from one save to another
+#[tauri::command]
+pub fn sync_mods(
+    from: String,
+    to: String,
+    selection: Option<Vec<String>>,
+) -> Result<SyncSummary, String> {
+    let from_path = Path::new(&from);
+    let to_path = Path::new(&to);
+    if from_path == to_path {
+        return Err("Source and target save are the same".to_string());
+    }
+
+    let source = read_mod_config(from_path)?;
+    let mut target = read_mod_config(to_path)?;
+    let target_versions = installed_versions(to_path);
+
+    let keys: Vec<String> = match selection {
+        Some(keys) => keys,
+        None => {
+            let mut keys: Vec<String> = source.mods.keys().cloned().collect();
+            keys.sort();
+            keys
+        }
+    };
+
+    let from_mods_dir = from_path.join("mods");
+    let to_mods_dir = to_path.join("mods");
+    let mut summary = SyncSummary::default();
+
+    for key in keys {
+        let entry = match source.mods.get(&key) {
+            Some(entry) => entry,
+            None => {
+                summary.not_in_source.push(key);
+                continue;
+            }
+        };
+
+        if target.mods.get(&key).map(|e| e.enabled) != Some(entry.enabled) {
+            target.mods.insert(
+                key.clone(),
+                ModConfigEntry {
+                    enabled: entry.enabled,
+                },
+            );
+            summary.updated.push(key.clone());
+        }
+
+        if entry.enabled && !target_versions.contains_key(&key) {
+            summary.missing_archives.push(key.clone());
+        }
+
+        let files = collect_config_files(&from_mods_dir, &config_prefix_for_key(&key))?;
+        for (source_file, relative) in files {
+            let dest = to_mods_dir.join(&relative);
+            if let Some(parent) = dest.parent() {
+                fs::create_dir_all(parent)
+                    .map_err(|e| format!("Failed to create directory {:?}: {}", parent, e))?;
+            }
+
+            if dest.exists() {
+                summary.overwritten_files.push(relative.clone());
+            }
+
+            log!("Copying config {:?} -> {:?}", source_file, dest);
+            fs::copy(&source_file, &dest)
+                .map_err(|e| format!("Failed to copy config file {}: {}", relative, e))?;
+            summary.copied_files.push(relative);
+        }
+    }
+
+    if !summary.updated.is_empty() {
+        write_mod_config(to_path, &target)?;
+    }
+    invalidate_save_stats(to_path);
+
+    log!(
+        "Synced {} mods from {} to {}",
+        summary.updated.len(),
+        from,
+        to
+    );
+
+    Ok(summary)
+}
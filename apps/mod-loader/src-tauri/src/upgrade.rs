@@ -0,0 +1,84 @@
+use crate::loader_core::fs::OsFs;
+use crate::loader_core::modpack_upgrade::{apply_upgrade, UpgradeOptions, UpgradePlan};
+use crate::modpack::{read_pack, ConflictPolicy};
+use crate::pack_cache::cache_downloaded_modpack;
+use crate::receipts::{paths_owned_by_others, read_receipt, save_receipt};
+use std::path::Path;
+
+/// Diff an installed modpack (by its receipt) against a newer modpack zip
+/// - With `dry_run`, only returns the plan
+/// - Otherwise applies it: adds and updates archives, reverts dropped mods
+///   and keeps configs the player edited unless `overwrite_conflicts` is set
+///
+/// Added and updated archives whose Group:Name is already installed from
+/// elsewhere are resolved by `conflict_policy` (default: keep the newer one).
+/// The receipt is updated in place so `uninstall_modpack` still reverts everything.
+/// A `downloaded` zip moves into the modpack cache, like `install_modpack`.
+#[tauri::command]
+pub fn upgrade_modpack(
+    save_path: String,
+    receipt_id: String,
+    modpack_zip_path: String,
+    dry_run: bool,
+    overwrite_conflicts: bool,
+    conflict_policy: Option<ConflictPolicy>,
+    downloaded: Option<bool>,
+) -> Result<UpgradePlan, String> {
+    let mut receipt = read_receipt(&receipt_id)?;
+
+    if Path::new(&receipt.save_path) != Path::new(&save_path) {
+        return Err(format!(
+            "Install receipt '{}' belongs to {}, not {}",
+            receipt_id, receipt.save_path, save_path
+        ));
+    }
+
+    log!(
+        "Planning upgrade of {} in {:?} from {:?}",
+        receipt.modpack_name,
+        save_path,
+        modpack_zip_path
+    );
+
+    let pack = read_pack(Path::new(&modpack_zip_path))?;
+    let mut plan = UpgradePlan::new(&OsFs, &receipt, &pack);
+
+    if dry_run {
+        return Ok(plan);
+    }
+
+    let options = UpgradeOptions {
+        overwrite_conflicts,
+        conflict_policy: conflict_policy.unwrap_or_default(),
+        ..Default::default()
+    };
+    let other_owned = paths_owned_by_others(&receipt.id)?;
+
+    // The hash ties the receipt to a cached pack for reinstalls, so it is
+    // only kept for a zip that made it into the cache
+    receipt.source_sha256 = None;
+    if downloaded.unwrap_or(false) {
+        match cache_downloaded_modpack(Path::new(&modpack_zip_path), &pack.name) {
+            Ok(cached) => receipt.source_sha256 = Some(cached.sha256),
+            Err(e) => eprintln!("Warning: Failed to cache modpack zip: {}", e),
+        }
+    }
+
+    let result =
+        apply_upgrade(&OsFs, &mut receipt, &pack, &plan, &other_owned, &options).map(|notes| {
+            for note in notes {
+                log!("{}", note);
+            }
+        });
+
+    // Persist whatever was applied, even on failure
+    receipt.modpack_name = pack.name.clone();
+    if let Err(e) = save_receipt(&receipt) {
+        eprintln!("Warning: Failed to save install receipt: {}", e);
+    }
+    result?;
+
+    plan.applied = true;
+    log!("Upgraded {} to {}", plan.from_modpack, plan.to_modpack);
+    Ok(plan)
+}
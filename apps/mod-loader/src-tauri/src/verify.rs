@@ -0,0 +1,121 @@
+use crate::mods::{build_mods_index, read_mod_config, read_orbis_metadata, ModLocation};
+use crate::pack_cache::hash_file;
+use serde::Serialize;
+use std::path::Path;
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum VerifyStatus {
+    /// Matches the hash recorded when it was installed
+    Ok,
+    /// Installed, but no hash was recorded to check it against
+    Unverified,
+    /// Changed since it was installed
+    HashMismatch,
+    /// Enabled in config.json, but no archive provides it
+    Missing,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct VerifiedMod {
+    /// "Group:Name"
+    pub mod_key: String,
+    pub file_name: Option<String>,
+    pub location: Option<ModLocation>,
+    pub status: VerifyStatus,
+    pub expected_sha256: Option<String>,
+    pub actual_sha256: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct VerifyReport {
+    /// Sorted by key
+    pub mods: Vec<VerifiedMod>,
+    /// No enabled mod is missing or changed
+    pub ok: bool,
+}
+
+/// Check every mod a save enables: that an archive provides it, and that the
+/// archive still matches the hash recorded in orbis-metadata.json when it was
+/// installed. Unpacked mods and archives installed by hand have no recorded
+/// hash and are reported as unverified.
+#[tauri::command]
+pub fn verify_mods(save_path: String, hytale_root: String) -> Result<VerifyReport, String> {
+    let save_path = Path::new(&save_path);
+    let global_mods_dir = Path::new(&hytale_root).join("UserData").join("Mods");
+
+    let config = read_mod_config(save_path)?;
+    let index = build_mods_index(save_path, &global_mods_dir);
+    let global_metadata = read_orbis_metadata(&global_mods_dir);
+    let local_metadata = read_orbis_metadata(&save_path.join("mods"));
+
+    let mut mods = Vec::new();
+    for (key, entry) in &config.mods {
+        if !entry.enabled {
+            continue;
+        }
+
+        let resolved = match index.get(key) {
+            Some(resolved) => resolved,
+            None => {
+                mods.push(VerifiedMod {
+                    mod_key: key.clone(),
+                    file_name: None,
+                    location: None,
+                    status: VerifyStatus::Missing,
+                    expected_sha256: None,
+                    actual_sha256: None,
+                });
+                continue;
+            }
+        };
+
+        let file_name = resolved
+            .path
+            .file_name()
+            .and_then(|s| s.to_str())
+            .unwrap_or_default()
+            .to_string();
+        let metadata = match resolved.location {
+            ModLocation::SaveLocal => &local_metadata,
+            ModLocation::Global => &global_metadata,
+        };
+        let expected_sha256 = metadata.get(&file_name).and_then(|m| m.sha256.clone());
+
+        let (status, actual_sha256) = match &expected_sha256 {
+            Some(expected) if resolved.path.is_file() => {
+                let actual = hash_file(&resolved.path)?;
+                let status = if actual.eq_ignore_ascii_case(expected) {
+                    VerifyStatus::Ok
+                } else {
+                    VerifyStatus::HashMismatch
+                };
+                (status, Some(actual))
+            }
+            _ => (VerifyStatus::Unverified, None),
+        };
+
+        if status == VerifyStatus::HashMismatch {
+            log!(
+                "Warning: {:?} changed since it was installed",
+                resolved.path
+            );
+        }
+
+        mods.push(VerifiedMod {
+            mod_key: key.clone(),
+            file_name: Some(file_name),
+            location: Some(resolved.location),
+            status,
+            expected_sha256,
+            actual_sha256,
+        });
+    }
+    mods.sort_by(|a, b| a.mod_key.cmp(&b.mod_key));
+
+    let ok = mods
+        .iter()
+        .all(|m| matches!(m.status, VerifyStatus::Ok | VerifyStatus::Unverified));
+
+    Ok(VerifyReport { mods, ok })
+}
@@ -0,0 +1,287 @@
+use crate::mods::{is_mod_archive, is_mod_entry};
+use crate::saves::invalidate_save_stats;
+use notify_debouncer_mini::notify::{RecommendedWatcher, RecursiveMode};
+use notify_debouncer_mini::{new_debouncer, DebounceEventResult, Debouncer};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, State};
+
+/// Changes within this window are coalesced into a single event
+const DEBOUNCE_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Payload of `mod-added` / `mod-changed` / `mod-removed`
+#[derive(Debug, Serialize, Clone)]
+pub struct ModChangedEvent {
+    pub file_name: String,
+    /// None for `UserData/Mods`, otherwise the save whose `mods/` changed
+    pub save_path: Option<String>,
+}
+
+/// Payload of `save-added` / `save-removed` / `save-config-changed`
+#[derive(Debug, Serialize, Clone)]
+pub struct SaveChangedEvent {
+    pub save_path: String,
+}
+
+type SharedDebouncer = Arc<Mutex<Option<Debouncer<RecommendedWatcher>>>>;
+
+/// Running watcher, managed by Tauri
+#[derive(Default)]
+pub struct WatcherState {
+    debouncer: SharedDebouncer,
+}
+
+struct WatchedPaths {
+    global_mods_dir: PathBuf,
+    saves_dir: PathBuf,
+    /// Mods and saves that already exist, so rewriting one is not reported
+    /// as an addition
+    known: HashSet<PathBuf>,
+}
+
+/// Mods currently in a mods directory
+fn mods_in(dir: &Path) -> Vec<PathBuf> {
+    fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| is_mod_entry(path))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn watch(debouncer: &SharedDebouncer, path: &Path) {
+    if !path.is_dir() {
+        return;
+    }
+
+    if let Ok(mut guard) = debouncer.lock() {
+        if let Some(debouncer) = guard.as_mut() {
+            if let Err(e) = debouncer.watcher().watch(path, RecursiveMode::NonRecursive) {
+                eprintln!("Failed to watch {:?}: {}", path, e);
+            }
+        }
+    }
+}
+
+/// Watch a save directory (for config.json) and its mods/ directory
+fn watch_save(debouncer: &SharedDebouncer, save_path: &Path) {
+    watch(debouncer, save_path);
+    watch(debouncer, &save_path.join("mods"));
+}
+
+fn file_name_of(path: &Path) -> String {
+    path.file_name()
+        .and_then(|s| s.to_str())
+        .unwrap_or("unknown")
+        .to_string()
+}
+
+/// Whether a changed path is (or, once deleted, was) a mod. A deleted path can
+/// no longer be inspected, so removals are classified by name: archives by
+/// their extension, unpacked mods as extension-less directory names.
+fn is_mod_change(path: &Path) -> bool {
+    if path.exists() {
+        return is_mod_entry(path);
+    }
+
+    let hidden = path
+        .file_name()
+        .and_then(|s| s.to_str())
+        .is_none_or(|name| name.starts_with('.'));
+    is_mod_archive(path) || (path.extension().is_none() && !hidden)
+}
+
+fn emit_mod_change(
+    app: &AppHandle,
+    known: &mut HashSet<PathBuf>,
+    path: &Path,
+    save_path: Option<&Path>,
+) {
+    let event = ModChangedEvent {
+        file_name: file_name_of(path),
+        save_path: save_path.map(|p| p.to_string_lossy().to_string()),
+    };
+
+    let name = if !path.exists() {
+        known.remove(path);
+        "mod-removed"
+    } else if known.insert(path.to_path_buf()) {
+        "mod-added"
+    } else {
+        "mod-changed"
+    };
+
+    log!("Emitting {}: {:?}", name, event);
+    if let Err(e) = app.emit(name, event) {
+        eprintln!("Failed to emit {}: {}", name, e);
+    }
+}
+
+fn emit_save_change(app: &AppHandle, name: &str, save_path: &Path) {
+    let event = SaveChangedEvent {
+        save_path: save_path.to_string_lossy().to_string(),
+    };
+
+    log!("Emitting {}: {:?}", name, event);
+    if let Err(e) = app.emit(name, event) {
+        eprintln!("Failed to emit {}: {}", name, e);
+    }
+}
+
+/// Translate one debounced path into loader events
+fn handle_path(
+    app: &AppHandle,
+    debouncer: &SharedDebouncer,
+    paths: &mut WatchedPaths,
+    path: &Path,
+) {
+    let parent = match path.parent() {
+        Some(p) => p,
+        None => return,
+    };
+
+    // UserData/Mods/<archive or unpacked mod>
+    if parent == paths.global_mods_dir {
+        if is_mod_change(path) {
+            emit_mod_change(app, &mut paths.known, path, None);
+        }
+        return;
+    }
+
+    // UserData/Saves/<save>
+    if parent == paths.saves_dir {
+        invalidate_save_stats(path);
+        if path.is_dir() {
+            if paths.known.insert(path.to_path_buf()) {
+                watch_save(debouncer, path);
+                paths.known.extend(mods_in(&path.join("mods")));
+                emit_save_change(app, "save-added", path);
+            }
+        } else if !path.exists() {
+            paths.known.retain(|known| !known.starts_with(path));
+            emit_save_change(app, "save-removed", path);
+        }
+        return;
+    }
+
+    let grandparent = parent.parent();
+
+    // UserData/Saves/<save>/config.json and UserData/Saves/<save>/mods
+    if grandparent == Some(paths.saves_dir.as_path()) {
+        invalidate_save_stats(parent);
+        match path.file_name().and_then(|s| s.to_str()) {
+            Some("config.json") => emit_save_change(app, "save-config-changed", parent),
+            Some("mods") => {
+                watch(debouncer, path);
+                paths.known.extend(mods_in(path));
+            }
+            _ => {}
+        }
+        return;
+    }
+
+    // UserData/Saves/<save>/mods/<archive>
+    if let Some(save_path) = grandparent {
+        if save_path.parent() == Some(paths.saves_dir.as_path())
+            && parent.file_name().and_then(|s| s.to_str()) == Some("mods")
+            && is_mod_change(path)
+        {
+            invalidate_save_stats(save_path);
+            emit_mod_change(app, &mut paths.known, path, Some(save_path));
+        }
+    }
+}
+
+fn run_event_loop(
+    app: AppHandle,
+    debouncer: SharedDebouncer,
+    mut paths: WatchedPaths,
+    rx: Receiver<DebounceEventResult>,
+) {
+    // Ends once the debouncer (and with it the sender) is dropped
+    for result in rx {
+        match result {
+            Ok(events) => {
+                for event in events {
+                    handle_path(&app, &debouncer, &mut paths, &event.path);
+                }
+            }
+            Err(e) => eprintln!("Watcher error: {}", e),
+        }
+    }
+
+    log!("Filesystem watcher stopped");
+}
+
+/// Start watching UserData/Mods and every save, replacing any previous watcher
+#[tauri::command]
+pub fn start_watching(
+    app: AppHandle,
+    state: State<'_, WatcherState>,
+    hytale_root: String,
+) -> Result<(), String> {
+    let user_data = Path::new(&hytale_root).join("UserData");
+    let mut paths = WatchedPaths {
+        global_mods_dir: user_data.join("Mods"),
+        saves_dir: user_data.join("Saves"),
+        known: HashSet::new(),
+    };
+
+    let (tx, rx) = channel();
+    let debouncer = new_debouncer(DEBOUNCE_TIMEOUT, tx)
+        .map_err(|e| format!("Failed to create filesystem watcher: {}", e))?;
+
+    {
+        let mut guard = state
+            .debouncer
+            .lock()
+            .map_err(|_| "Watcher state poisoned".to_string())?;
+        // Dropping the previous debouncer stops its event loop
+        *guard = Some(debouncer);
+    }
+
+    log!(
+        "Watching {:?} and {:?}",
+        paths.global_mods_dir,
+        paths.saves_dir
+    );
+
+    watch(&state.debouncer, &paths.global_mods_dir);
+    watch(&state.debouncer, &paths.saves_dir);
+    paths.known.extend(mods_in(&paths.global_mods_dir));
+
+    if let Ok(entries) = fs::read_dir(&paths.saves_dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                watch_save(&state.debouncer, &path);
+                paths.known.extend(mods_in(&path.join("mods")));
+                paths.known.insert(path);
+            }
+        }
+    }
+
+    let debouncer = state.debouncer.clone();
+    thread::spawn(move || run_event_loop(app, debouncer, paths, rx));
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn stop_watching(state: State<'_, WatcherState>) -> Result<(), String> {
+    let mut guard = state
+        .debouncer
+        .lock()
+        .map_err(|_| "Watcher state poisoned".to_string())?;
+    *guard = None;
+    Ok(())
+}
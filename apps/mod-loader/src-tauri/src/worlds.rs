@@ -0,0 +1,205 @@
+use crate::mods::{build_mods_index, read_mod_config, ModLocation};
+use crate::pack_cache::hash_file;
+use crate::resources::{extract_archive, inspect, ResourceType};
+use crate::saves::validate_save_name;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Where a save installed from Orbis came from, kept in the save folder
+const WORLD_METADATA_FILE: &str = "orbis-world.json";
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WorldMetadata {
+    pub resource_id: String,
+    pub version_id: String,
+    /// File name of the archive the world was installed from
+    pub source_file_name: String,
+    pub sha256: String,
+    pub installed_at: String,
+}
+
+pub(crate) fn read_world_metadata(save_path: &Path) -> Option<WorldMetadata> {
+    let contents = fs::read_to_string(save_path.join(WORLD_METADATA_FILE)).ok()?;
+    match serde_json::from_str(&contents) {
+        Ok(metadata) => Some(metadata),
+        Err(e) => {
+            eprintln!("Failed to parse {}: {}", WORLD_METADATA_FILE, e);
+            None
+        }
+    }
+}
+
+fn write_world_metadata(save_path: &Path, metadata: &WorldMetadata) -> Result<(), String> {
+    let contents = serde_json::to_string_pretty(metadata)
+        .map_err(|e| format!("Failed to serialize {}: {}", WORLD_METADATA_FILE, e))?;
+    fs::write(save_path.join(WORLD_METADATA_FILE), contents)
+        .map_err(|e| format!("Failed to write {}: {}", WORLD_METADATA_FILE, e))
+}
+
+/// A mod listed in a save's config.json
+#[derive(Debug, Serialize, Clone)]
+pub struct RequiredMod {
+    /// "Group:Name"
+    pub key: String,
+    pub enabled: bool,
+    /// Where the save finds the mod; None when it still has to be installed
+    pub location: Option<ModLocation>,
+    pub installed_version: Option<String>,
+}
+
+/// Every mod a save's config.json lists, and whether an archive for it is
+/// installed. Enabled mods without one are what a world needs downloaded.
+pub(crate) fn required_mods(
+    save_path: &Path,
+    hytale_root: &Path,
+) -> Result<Vec<RequiredMod>, String> {
+    let config = read_mod_config(save_path)?;
+    let index = build_mods_index(save_path, &hytale_root.join("UserData").join("Mods"));
+
+    let mut mods: Vec<RequiredMod> = config
+        .mods
+        .iter()
+        .map(|(key, entry)| {
+            let resolved = index.get(key);
+            RequiredMod {
+                key: key.clone(),
+                enabled: entry.enabled,
+                location: resolved.map(|r| r.location),
+                installed_version: resolved.map(|r| r.manifest.version.clone()),
+            }
+        })
+        .collect();
+    mods.sort_by(|a, b| a.key.cmp(&b.key));
+
+    Ok(mods)
+}
+
+/// Extract a WORLD archive as a new save in UserData/Saves. A half-written
+/// save is removed if extraction fails.
+pub(crate) fn import_world_archive(
+    archive: &Path,
+    hytale_root: &Path,
+    save_name: &str,
+) -> Result<PathBuf, String> {
+    validate_save_name(save_name)?;
+
+    let inspection = inspect(archive)?;
+    if inspection.resource_type != ResourceType::World {
+        return Err(format!(
+            "{:?} is a {:?}, not a world",
+            archive, inspection.resource_type
+        ));
+    }
+
+    let save_path = hytale_root
+        .join("UserData")
+        .join("Saves")
+        .join(save_name.trim());
+    if save_path.exists() {
+        return Err(format!("Save '{}' already exists", save_name));
+    }
+
+    log!("Importing world {:?} as {:?}", archive, save_path);
+    if let Err(e) = extract_archive(
+        archive,
+        &save_path,
+        inspection.wrapper_folder.as_deref(),
+        |_| true,
+    ) {
+        let _ = fs::remove_dir_all(&save_path);
+        return Err(e);
+    }
+
+    Ok(save_path)
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct InstalledWorld {
+    pub save_name: String,
+    pub save_path: String,
+    pub metadata: WorldMetadata,
+    /// Mods from the world's config.json; enabled ones with no `location`
+    /// should be offered for install
+    pub required_mods: Vec<RequiredMod>,
+}
+
+/// Install a world downloaded from Orbis as a new save, named after the
+/// archive unless `save_name` is given, and record its resource and version
+/// ids in the save's orbis-world.json
+#[tauri::command]
+pub fn install_world(
+    archive_path: String,
+    hytale_root: String,
+    resource_id: String,
+    version_id: String,
+    save_name: Option<String>,
+) -> Result<InstalledWorld, String> {
+    let archive = Path::new(&archive_path);
+    let hytale_path = Path::new(&hytale_root);
+
+    let save_name = match save_name {
+        Some(name) => name.trim().to_string(),
+        None => archive
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .ok_or("Invalid archive file name")?
+            .to_string(),
+    };
+
+    let sha256 = hash_file(archive)?;
+    let save_path = import_world_archive(archive, hytale_path, &save_name)?;
+
+    let metadata = WorldMetadata {
+        resource_id,
+        version_id,
+        source_file_name: archive
+            .file_name()
+            .and_then(|s| s.to_str())
+            .unwrap_or_default()
+            .to_string(),
+        sha256,
+        installed_at: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+    };
+    write_world_metadata(&save_path, &metadata)?;
+
+    // A world without config.json just has no mods to offer
+    let required_mods = if save_path.join("config.json").exists() {
+        required_mods(&save_path, hytale_path)?
+    } else {
+        Vec::new()
+    };
+
+    let missing = required_mods
+        .iter()
+        .filter(|m| m.enabled && m.location.is_none())
+        .count();
+    log!(
+        "Installed world {} ({} required mods, {} missing)",
+        save_name,
+        required_mods.len(),
+        missing
+    );
+
+    Ok(InstalledWorld {
+        save_name,
+        save_path: save_path.to_string_lossy().to_string(),
+        metadata,
+        required_mods,
+    })
+}
+
+#[tauri::command]
+pub fn get_world_metadata(save_path: String) -> Result<Option<WorldMetadata>, String> {
+    Ok(read_world_metadata(Path::new(&save_path)))
+}
+
+/// The mods a save's config.json lists and which of them are installed
+#[tauri::command]
+pub fn get_required_mods(
+    save_path: String,
+    hytale_root: String,
+) -> Result<Vec<RequiredMod>, String> {
+    required_mods(Path::new(&save_path), Path::new(&hytale_root))
+}
@@ -0,0 +1,176 @@
+#!/usr/bin/env python3
+"""Regenerate the archives the loader_core tests read.
+
+Run from this directory: python3 generate.py
+Entries get a fixed timestamp so the output is byte-for-byte reproducible.
+"""
+
+import hashlib
+import io
+import json
+import zipfile
+
+DATE = (2024, 1, 1, 0, 0, 0)
+
+
+def build_zip(entries):
+    buffer = io.BytesIO()
+    with zipfile.ZipFile(buffer, "w", zipfile.ZIP_DEFLATED) as archive:
+        for name, data in entries:
+            info = zipfile.ZipInfo(name, DATE)
+            info.compress_type = zipfile.ZIP_STORED if name.endswith("/") else zipfile.ZIP_DEFLATED
+            if isinstance(data, str):
+                data = data.encode()
+            archive.writestr(info, data)
+    return buffer.getvalue()
+
+
+def write(name, data):
+    with open(name, "wb") as f:
+        f.write(data)
+    return data
+
+
+def manifest(group, name, version, main="", **extra):
+    fields = {"Group": group, "Name": name, "Version": version, "Main": main}
+    fields.update(extra)
+    return json.dumps(fields, indent=2)
+
+
+def sha256(data):
+    return hashlib.sha256(data).hexdigest()
+
+
+hammers_1 = write(
+    "hammers-1.0.0.jar",
+    build_zip(
+        [
+            ("manifest.json", manifest("Acme", "Hammers", "1.0.0", "acme.Hammers", Authors=[{"Name": "Acme"}])),
+            ("acme/Hammers.class", b"\xca\xfe\xba\xbe"),
+        ]
+    ),
+)
+
+hammers_2 = write(
+    "hammers-2.0.0.jar",
+    build_zip(
+        [
+            ("manifest.json", manifest("Acme", "Hammers", "2.0.0", "acme.Hammers", Authors=[{"Name": "Acme"}])),
+            ("acme/Hammers.class", b"\xca\xfe\xba\xbe\x00\x02"),
+        ]
+    ),
+)
+
+# Zipped with its folder, a differently cased file name, a BOM and trailing commas
+quirky = write(
+    "quirky-textures.zip",
+    build_zip(
+        [
+            ("Quirky/", b""),
+            ("Quirky/Manifest.json", '\ufeff{"Group": "Acme", "Name": "Quirky", "Version": "0.3",}'),
+            ("Quirky/Common/Blocks/quirk.png", b"\x89PNG"),
+        ]
+    ),
+)
+
+write(
+    "bundle.zip",
+    build_zip(
+        [
+            ("Alpha/manifest.json", manifest("Bundle", "Alpha", "1.0", "bundle.Alpha")),
+            ("Beta/manifest.json", manifest("Bundle", "Beta", "1.0", "", IncludesAssetPack=True)),
+        ]
+    ),
+)
+
+hammers_config = build_zip(
+    [
+        ("Acme_Hammers/settings.json", '{"damage": 12}'),
+        ("../escape.txt", "outside the save"),
+    ]
+)
+
+
+def pack_lock(extra_mods=()):
+    return {
+        "formatVersion": 1,
+        "mods": [
+            {
+                "key": "Acme:Hammers",
+                "version": "2.0.0",
+                "resourceId": "res-hammers",
+                "versionId": "ver-2",
+                "fileName": "hammers-2.0.0.jar",
+                "sha256": sha256(hammers_2),
+                "config": {"fileName": "Acme_Hammers.zip", "sha256": sha256(hammers_config)},
+            },
+            {
+                "key": "Acme:Quirky",
+                "version": "0.3",
+                "fileName": "quirky-textures.zip",
+                "sha256": sha256(quirky),
+            },
+            *extra_mods,
+        ],
+    }
+
+
+def modpack(mod_data, lock=None, extra_entries=()):
+    return build_zip(
+        [
+            ("orbis-pack.json", json.dumps(lock or pack_lock(), indent=2)),
+            ("Mods/hammers-2.0.0.jar", mod_data),
+            ("Mods/quirky-textures.zip", quirky),
+            ("Configs/Acme_Hammers.zip", hammers_config),
+            ("World/universe/worlds/default/config.json", '{"Seed": 7}'),
+            *extra_entries,
+        ]
+    )
+
+
+write("modpack.zip", modpack(hammers_2))
+# The lock's hash no longer matches the archive
+write("modpack-tampered.zip", modpack(hammers_1))
+# A mod archive the lock does not list
+write("modpack-unlisted.zip", modpack(hammers_2, extra_entries=[("Mods/stowaway.jar", hammers_1)]))
+# The lock lists a mod archive the zip does not contain
+write(
+    "modpack-missing.zip",
+    modpack(
+        hammers_2,
+        lock=pack_lock(
+            [
+                {
+                    "key": "Acme:Anvils",
+                    "version": "1.0.0",
+                    "fileName": "anvils-1.0.0.jar",
+                    "sha256": sha256(hammers_1),
+                }
+            ]
+        ),
+    ),
+)
+
+write(
+    "Imported World.zip",
+    build_zip(
+        [
+            ("config.json", json.dumps({"Mods": {"Acme:Hammers": {"Enabled": True}}, "Seed": 42})),
+            ("mods/", b""),
+            ("universe/worlds/default/chunks.bin", b"\x00\x01\x02\x03"),
+            ("../evil.txt", "outside the saves dir"),
+        ]
+    ),
+)
+
+# An asset pack without a manifest, zipped with its folder
+write(
+    "stone-pack-1.2.zip",
+    build_zip(
+        [
+            ("StonePack/", b""),
+            ("StonePack/Common/Blocks/stone.png", b"\x89PNG"),
+            ("StonePack/Common/Blocks/Stone.json", '{"Texture": "stone.png"}'),
+        ]
+    ),
+)
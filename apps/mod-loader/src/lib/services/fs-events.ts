@@ -0,0 +1,36 @@
+import { listen, type UnlistenFn } from '@tauri-apps/api/event';
+
+/** Payload of `mod-added` / `mod-changed` / `mod-removed` (see src-tauri/src/watcher.rs) */
+export interface ModChangedEvent {
+    file_name: string;
+    /** null for UserData/Mods, otherwise the save whose mods/ changed */
+    save_path: string | null;
+}
+
+/** Payload of `save-added` / `save-removed` / `save-config-changed` */
+export interface SaveChangedEvent {
+    save_path: string;
+}
+
+/** Listen to several watcher events at once, returning a single unlisten */
+export async function listenAll<T>(
+    events: string[],
+    handler: (payload: T) => void
+): Promise<UnlistenFn> {
+    const unlisteners = await Promise.all(
+        events.map(event => listen<T>(event, e => handler(e.payload)))
+    );
+    return () => unlisteners.forEach(unlisten => unlisten());
+}
+
+/** Call `handler` when mods change in UserData/Mods or, if given, in a save */
+export function onModsChanged(
+    savePath: string | null,
+    handler: () => void
+): Promise<UnlistenFn> {
+    return listenAll<ModChangedEvent>(['mod-added', 'mod-changed', 'mod-removed'], payload => {
+        if (payload.save_path === null || payload.save_path === savePath) {
+            handler();
+        }
+    });
+}
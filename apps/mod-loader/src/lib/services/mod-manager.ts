@@ -271,17 +271,17 @@ export class ModManager {
             }
         }
 
-        // Register in config.json using the global JAR
+        // Put the content where the game expects its type and enable it in config.json
         try {
-            console.log(`[ModManager] Registering installed global jar: ${fileName}`);
-            await invoke('register_jar_in_config', {
-                savePath,
-                jarFilename: fileName,
-                hytaleRoot // Pass hytaleRoot so backend can find it in global mods
+            console.log(`[ModManager] Installing downloaded archive: ${fileName}`);
+            const installed = await invoke<{ resource_type: string; mod_keys: string[] }>('install_resource', {
+                archivePath: globalDestination,
+                hytaleRoot,
+                savePath
             });
-            console.log(`[ModManager] Successfully registered ${fileName} in config.json`);
+            console.log(`[ModManager] Installed ${fileName} as ${installed.resource_type}, enabled: ${installed.mod_keys.join(', ')}`);
         } catch (error) {
-            console.error('[ModManager] Failed to register mod in config.json:', error);
+            console.error('[ModManager] Failed to install downloaded archive:', error);
             // Don't fail the installation, just log the error
         }
 
@@ -360,6 +360,14 @@ export class ModManager {
             }
         }
 
+        // Asset and data packs without a manifest get unpacked next to the archive
+        try {
+            const { invoke } = await import('@tauri-apps/api/core');
+            await invoke('install_resource', { archivePath: destination, hytaleRoot });
+        } catch (error) {
+            console.warn('[ModManager] Failed to install resource:', error);
+        }
+
         console.log(`Installed ${mod.id} version ${mod.version} to global mods: ${destination}`);
     }
 
@@ -451,6 +459,8 @@ export class ModManager {
             modpackZipPath: tempFilePath,
             savePath,
             hytaleRoot,
+            // Lets the backend move the download into its modpack cache
+            options: { downloaded: true },
         });
 
         console.log(`[ModManager] Modpack ${mod.name} installed successfully`);
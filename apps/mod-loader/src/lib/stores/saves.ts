@@ -1,13 +1,30 @@
 import { writable, get } from 'svelte/store';
-import type { HytaleSave } from '$lib/types/mod';
-import { readDir, exists, readTextFile } from '@tauri-apps/plugin-fs';
-import { join } from '@tauri-apps/api/path';
+import type { HytaleSave, SaveInfo } from '$lib/types/mod';
+import { invoke } from '@tauri-apps/api/core';
+import type { UnlistenFn } from '@tauri-apps/api/event';
+import { listenAll } from '$lib/services/fs-events';
 import { settings } from './settings';
 
+function toHytaleSave(info: SaveInfo): HytaleSave {
+    return {
+        name: info.name,
+        path: info.path,
+        lastPlayed:
+            info.last_modified !== null ? new Date(info.last_modified).toISOString() : undefined,
+        installedModsCount: info.enabled_mods,
+        disabledModsCount: info.disabled_mods,
+        sizeBytes: info.size_bytes,
+        configStatus: info.config_status,
+        configError: info.config_error ?? undefined
+    };
+}
+
 function createSavesStore() {
     const { subscribe, set, update } = writable<HytaleSave[]>([]);
 
-    let pollInterval: ReturnType<typeof setInterval> | null = null;
+    let unlisten: UnlistenFn | null = null;
+    let unsubscribeSettings: (() => void) | null = null;
+    let watchedRoot = '';
 
     return {
         subscribe,
@@ -20,70 +37,40 @@ function createSavesStore() {
                     hytaleRoot = get(settings).hytaleRoot;
                 }
 
-                // Construct UserData/Saves path from root
-                const userDataDir = await join(hytaleRoot, 'UserData');
-                const saveRoot = await join(userDataDir, 'Saves');
-
-                // console.log('Scanning saves at:', saveRoot);
-
-                const existsResult = await exists(saveRoot);
-                // console.log('Save root exists:', existsResult);
-
-                if (!existsResult) {
-                    console.warn('Saves directory not found:', saveRoot);
-                    return;
-                }
-
-                const entries = await readDir(saveRoot);
-                // console.log(`Found ${entries.length} entries in saves dir`);
-
-                const saves: HytaleSave[] = [];
-
-                for (const entry of entries) {
-                    // console.log(`Entry: ${entry.name}, isDirectory: ${entry.isDirectory}`);
-                    if (entry.isDirectory) {
-                        const fullPath = await join(saveRoot, entry.name);
-
-                        // Count mods in the mods folder
-                        // Count enabled mods from config.json
-                        let modCount = 0;
-                        try {
-                            const configPath = await join(fullPath, 'config.json');
-                            if (await exists(configPath)) {
-                                const content = await readTextFile(configPath);
-                                const config = JSON.parse(content);
-                                if (config && config.Mods) {
-                                    modCount = Object.values(config.Mods).filter((m: any) => m.Enabled).length;
-                                }
-                            }
-                        } catch (e) {
-                            console.warn('Failed to read mod count from config:', e);
-                        }
-
-                        saves.push({
-                            name: entry.name,
-                            path: fullPath,
-                            installedModsCount: modCount,
-                            lastPlayed: new Date().toISOString() // Placeholder
-                        });
-                    }
-                }
-                // console.log('Saves loaded:', saves);
-                set(saves);
+                const infos = await invoke<SaveInfo[]>('list_saves', { hytaleRoot });
+                set(infos.map(toHytaleSave));
             } catch (e) {
                 console.error('Failed to load saves:', e);
             }
         },
-        startPolling: function () {
-            if (pollInterval) return;
-            this.load();
-            pollInterval = setInterval(() => this.load(), 10000);
-        },
-        stopPolling: function () {
-            if (pollInterval) {
-                clearInterval(pollInterval);
-                pollInterval = null;
+        startWatching: async function () {
+            if (unlisten) return;
+            unlisten = await listenAll(
+                ['save-added', 'save-removed', 'save-config-changed'],
+                () => this.load()
+            );
+
+            if (!get(settings).hytaleRoot) {
+                await settings.load();
             }
+
+            // (Re)start the watcher whenever the Hytale root changes
+            unsubscribeSettings = settings.subscribe(({ hytaleRoot }) => {
+                if (!hytaleRoot || hytaleRoot === watchedRoot) return;
+                watchedRoot = hytaleRoot;
+                this.load();
+                invoke('start_watching', { hytaleRoot }).catch(e =>
+                    console.error('Failed to start filesystem watcher:', e)
+                );
+            });
+        },
+        stopWatching: async function () {
+            unlisten?.();
+            unlisten = null;
+            unsubscribeSettings?.();
+            unsubscribeSettings = null;
+            watchedRoot = '';
+            await invoke('stop_watching');
         },
         add: (save: HytaleSave) => update(saves => [...saves, save]),
         remove: (path: string) => update(saves => saves.filter(s => s.path !== path)),
@@ -96,12 +83,9 @@ function createSavesStore() {
 export const saves = createSavesStore();
 export const selectedSave = writable<HytaleSave | null>(null);
 
-// Initialize saves on load
+// Load saves and refresh them on watcher events
 setTimeout(() => {
-    // Initialize saves on load and start polling
-    setTimeout(() => {
-        saves.startPolling();
-    }, 100);
+    saves.startWatching();
 }, 100);
 
 export function selectSave(save: HytaleSave) {
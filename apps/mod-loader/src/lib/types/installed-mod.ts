@@ -31,8 +31,32 @@ export interface InstalledMod {
         version: string;
         installedAt: string;
     };
+    location: ModLocation;
+    /** Global archive hidden by a save-local archive with the same Group:Name */
+    shadowed_global?: string;
+    /** A directory with manifest.json at its root rather than an archive */
+    unpacked: boolean;
+    /** Workarounds needed to read the manifest; empty for a well-formed mod */
+    manifest_fallbacks: ManifestFallback[];
+    resource_type: ResourceType;
 }
 
+export type ResourceType =
+    | 'PLUGIN'
+    | 'ASSET_PACK'
+    | 'MOD'
+    | 'DATA_PACK'
+    | 'PREFAB'
+    | 'WORLD';
+
+export type ModLocation = 'save_local' | 'global';
+
+export type ManifestFallback =
+    | 'nested_folder'
+    | 'file_name_casing'
+    | 'byte_order_mark'
+    | 'trailing_commas';
+
 export interface ModConfigEntry {
     Enabled: boolean;
 }
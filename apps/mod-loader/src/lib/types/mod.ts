@@ -55,12 +55,31 @@ export interface ModFilters {
     type?: 'MOD' | 'WORLD' | 'PLUGIN' | 'MODPACK';
 }
 
+export type SaveConfigStatus = 'ok' | 'missing' | 'invalid';
+
+/** A save as returned by the `list_saves` command (see src-tauri/src/saves.rs) */
+export interface SaveInfo {
+    name: string;
+    path: string;
+    size_bytes: number;
+    /** Milliseconds since the Unix epoch of the most recently written world file */
+    last_modified: number | null;
+    enabled_mods: number;
+    disabled_mods: number;
+    config_status: SaveConfigStatus;
+    config_error: string | null;
+}
+
 export interface HytaleSave {
     name: string;
     path: string;
     previewImage?: string;
     lastPlayed?: string;
     installedModsCount: number;
+    disabledModsCount: number;
+    sizeBytes: number;
+    configStatus: SaveConfigStatus;
+    configError?: string;
 }
 
 export interface OrbisModMetadata {
@@ -70,7 +89,10 @@ export interface OrbisModMetadata {
     author: string;
     iconUrl?: string;
     version: string;
+    versionId?: string;
     installedAt: string;
+    /** SHA-256 of the installed file, where the install was verified */
+    sha256?: string;
 }
 
 export type OrbisMetadataFile = Record<string, OrbisModMetadata>;